@@ -41,6 +41,10 @@ pub enum Commands {
         /// Token budget (default: 128000)
         #[arg(long)]
         tokens: Option<usize>,
+
+        /// Prefix file lines with their line numbers when rendering
+        #[arg(long)]
+        line_numbers: bool,
     },
 
     /// Add source to a pack
@@ -48,7 +52,7 @@ pub enum Commands {
         /// Pack name or ID
         pack: String,
 
-        /// Source URI (e.g., file:path, text:content, glob:pattern)
+        /// Source URI (e.g., file:path, text:content, glob:pattern, clipboard:, or - for stdin)
         source: String,
 
         /// Priority (higher = included first when over budget)
@@ -63,15 +67,15 @@ pub enum Commands {
         #[arg(long)]
         end: Option<usize>,
 
-        /// For md_dir: maximum number of files
+        /// For dir/md_dir: maximum number of files
         #[arg(long)]
         max_files: Option<usize>,
 
-        /// For md_dir: patterns to exclude
+        /// For dir/md_dir: glob patterns to exclude
         #[arg(long)]
         exclude: Vec<String>,
 
-        /// For md_dir: recursive scan
+        /// For dir/md_dir: recursive scan
         #[arg(long)]
         recursive: bool,
 
@@ -82,6 +86,14 @@ pub enum Commands {
         /// Max related files to add (default: 5)
         #[arg(long, default_value = "5")]
         related_max: usize,
+
+        /// Render mode: full, outline (bodies elided) or signatures
+        #[arg(long, default_value = "full")]
+        mode: ctx_core::RenderMode,
+
+        /// For stdin/clipboard: label shown in the payload (default: first line)
+        #[arg(long)]
+        title: Option<String>,
     },
 
     /// Remove artifact from a pack
@@ -118,12 +130,28 @@ pub enum Commands {
         /// Show the full rendered payload
         #[arg(long, short)]
         payload: bool,
+
+        /// Prefix file lines with their line numbers
+        #[arg(long)]
+        line_numbers: bool,
+
+        /// Leave lines unnumbered, even if the pack numbers them
+        #[arg(long, conflicts_with = "line_numbers")]
+        no_line_numbers: bool,
     },
 
     /// Copy pack to clipboard
     Cp {
         /// Pack name or ID
         pack: String,
+
+        /// Prefix file lines with their line numbers
+        #[arg(long)]
+        line_numbers: bool,
+
+        /// Leave lines unnumbered, even if the pack numbers them
+        #[arg(long, conflicts_with = "line_numbers")]
+        no_line_numbers: bool,
     },
 
     /// Delete a pack
@@ -146,11 +174,30 @@ pub enum Commands {
         fix: bool,
     },
 
+    /// Point a pack's file paths at another checkout of the workspace
+    RebaseRoot {
+        /// Pack name or ID
+        pack: String,
+
+        /// New workspace root
+        root: std::path::PathBuf,
+
+        /// Old root, for artifacts added with absolute paths
+        #[arg(long)]
+        from: Option<std::path::PathBuf>,
+    },
+
     // ===== Discovery =====
     /// Suggest related files
     Suggest {
         /// File to find suggestions for
-        file: std::path::PathBuf,
+        #[arg(required_unless_present = "pack")]
+        file: Option<std::path::PathBuf>,
+
+        /// Suggest for all files of this pack, leaving out those already in it (with a
+        /// file, only leaves them out)
+        #[arg(long)]
+        pack: Option<String>,
 
         /// Maximum number of suggestions
         #[arg(long, short = 'n', default_value = "10")]
@@ -159,6 +206,25 @@ pub enum Commands {
         /// Output format (text, json)
         #[arg(long, default_value = "text")]
         format: String,
+
+        /// Only count co-changes in commits by this author (name or email, repeatable)
+        #[arg(long)]
+        author: Vec<String>,
+
+        /// Revision range for co-change history, e.g. `main..HEAD` for this branch only
+        #[arg(long)]
+        range: Option<String>,
+
+        /// Also pair tests named like this with their sources, e.g. `{stem}.integration.{ext}`
+        /// (repeatable, on top of `[suggest] test_patterns`)
+        #[arg(long)]
+        test_pattern: Vec<String>,
+    },
+
+    /// Manage the on-disk indexes behind suggestions
+    Index {
+        #[command(subcommand)]
+        action: IndexAction,
     },
 
     // ===== Project config =====
@@ -231,6 +297,35 @@ pub enum Commands {
     },
 }
 
+#[derive(Subcommand)]
+pub enum IndexAction {
+    /// Build the index, or update it with what changed since the last build
+    Build {
+        /// Path inside the workspace (default: current directory)
+        path: Option<std::path::PathBuf>,
+    },
+
+    /// Show what's indexed for a workspace
+    Status {
+        /// Path inside the workspace (default: current directory)
+        path: Option<std::path::PathBuf>,
+
+        /// Output format (text, json)
+        #[arg(long, default_value = "text")]
+        format: String,
+    },
+
+    /// Delete a workspace's index
+    Clear {
+        /// Path inside the workspace (default: current directory)
+        path: Option<std::path::PathBuf>,
+
+        /// Delete the indexes of every workspace
+        #[arg(long, conflicts_with = "path")]
+        all: bool,
+    },
+}
+
 impl Cli {
     /// Generate shell completions and write to stdout
     pub fn print_completions(shell: Shell) {
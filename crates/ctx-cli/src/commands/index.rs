@@ -0,0 +1,113 @@
+//! Index command - manage the on-disk indexes behind suggestions
+
+use std::path::{Path, PathBuf};
+use std::time::Instant;
+
+use anyhow::Result;
+use ctx_suggest::index::{IndexStore, default_index_dir};
+use ctx_suggest::{SuggestConfig, SuggestionEngine};
+
+/// Build or update the indexes of the workspace containing `path`
+pub async fn build(path: Option<PathBuf>, config: &SuggestConfig) -> Result<()> {
+    let workspace = workspace_for(path)?;
+    let engine = SuggestionEngine::new(&workspace, config.clone());
+
+    let start = Instant::now();
+    engine.warm_cache().await?;
+    println!(
+        "✓ Indexed {} in {} ms",
+        workspace.display(),
+        start.elapsed().as_millis()
+    );
+
+    print_status(&IndexStore::new(config.index_dir.clone(), &workspace));
+    Ok(())
+}
+
+/// Show what's indexed for the workspace containing `path`
+pub fn status(path: Option<PathBuf>, format: &str, index_dir: Option<&Path>) -> Result<()> {
+    let workspace = workspace_for(path)?;
+    let store = IndexStore::new(index_dir.map(Path::to_owned), &workspace);
+
+    match format {
+        "json" => println!("{}", serde_json::to_string_pretty(&store.status())?),
+        _ => {
+            println!("Index for: {}", workspace.display());
+            print_status(&store);
+        }
+    }
+    Ok(())
+}
+
+/// Delete the indexes of the workspace containing `path`, or of every workspace
+pub fn clear(path: Option<PathBuf>, all: bool, index_dir: Option<&Path>) -> Result<()> {
+    if all {
+        let dir = index_dir.map_or_else(default_index_dir, Path::to_owned);
+        if dir.exists() {
+            std::fs::remove_dir_all(&dir)?;
+            println!("✓ Removed all indexes in {}", dir.display());
+        } else {
+            println!("No indexes to remove.");
+        }
+        return Ok(());
+    }
+
+    let workspace = workspace_for(path)?;
+    let store = IndexStore::new(index_dir.map(Path::to_owned), &workspace);
+    if store.clear()? {
+        println!("✓ Removed index for {}", workspace.display());
+    } else {
+        println!("No index for {}", workspace.display());
+    }
+    Ok(())
+}
+
+fn workspace_for(path: Option<PathBuf>) -> Result<PathBuf> {
+    let path = match path {
+        Some(path) => path,
+        None => std::env::current_dir()?,
+    };
+    Ok(ctx_core::find_project_root(&path.canonicalize()?))
+}
+
+fn print_status(store: &IndexStore) {
+    let status = store.status();
+    println!("  Location:  {}", status.dir.display());
+    match status.imports {
+        Some(imports) => println!(
+            "  Imports:   {} files, {} imports (updated {})",
+            imports.files,
+            imports.imports,
+            ctx_core::format_age(imports.updated_at as i64)
+        ),
+        None => println!("  Imports:   not built"),
+    }
+    match status.symbols {
+        Some(symbols) => println!(
+            "  Symbols:   {} files, {} definitions (updated {})",
+            symbols.files,
+            symbols.definitions,
+            ctx_core::format_age(symbols.updated_at as i64)
+        ),
+        None => println!("  Symbols:   not built"),
+    }
+    match status.terms {
+        Some(terms) => println!(
+            "  Terms:     {} files, {} distinct terms (updated {})",
+            terms.files,
+            terms.terms,
+            ctx_core::format_age(terms.updated_at as i64)
+        ),
+        None => println!("  Terms:     not built"),
+    }
+    match status.cochange {
+        Some(cochange) => println!(
+            "  Co-change: {} commits up to {} (updated {})",
+            cochange.commits,
+            cochange.head.get(..7).unwrap_or(&cochange.head),
+            ctx_core::format_age(cochange.updated_at as i64)
+        ),
+        None => println!("  Co-change: not built"),
+    }
+    println!("  Size:      {:.1} KB", status.size_bytes as f64 / 1024.0);
+}
@@ -1,5 +1,7 @@
 use crate::config::{ArtifactDefinition, PackDefinition, ProjectConfig};
 use anyhow::Result;
+use ctx_core::ArtifactType;
+use ctx_sources::SourceHandlerRegistry;
 use ctx_storage::Storage;
 use std::path::Path;
 
@@ -49,14 +51,18 @@ pub async fn export_pack_to_definition(
     let pack = storage.get_pack(pack_name).await?;
     let artifacts = storage.get_pack_artifacts(&pack.id).await?;
 
+    let registry = SourceHandlerRegistry::new();
+
+    // Snippets were captured locally and can't be re-created from a source
     let artifact_defs: Vec<ArtifactDefinition> = artifacts
         .into_iter()
+        .filter(|item| !matches!(item.artifact.artifact_type, ArtifactType::Snippet { .. }))
         .map(|item| {
-            // Convert absolute paths to relative
-            let source = make_relative_source(&item.artifact.source_uri, project_root);
+            let source = registry.source_relative_to(&item.artifact, project_root);
             ArtifactDefinition {
                 source,
                 priority: item.priority,
+                mode: item.artifact.metadata.render_mode,
             }
         })
         .collect();
@@ -67,19 +73,9 @@ pub async fn export_pack_to_definition(
 
     let definition = PackDefinition {
         budget: Some(pack.policies.budget_tokens),
+        line_numbers: pack.policies.line_numbers,
         artifacts: artifact_defs,
     };
 
     Ok((local_name, definition))
 }
-
-/// Convert absolute paths in source URIs to relative paths
-fn make_relative_source(source_uri: &str, project_root: &Path) -> String {
-    if let Some(path) = source_uri.strip_prefix("file:")
-        && let Ok(abs_path) = std::fs::canonicalize(path)
-        && let Ok(rel_path) = abs_path.strip_prefix(project_root)
-    {
-        return format!("file:{}", rel_path.display());
-    }
-    source_uri.to_string()
-}
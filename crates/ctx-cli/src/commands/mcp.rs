@@ -1,6 +1,8 @@
 use anyhow::{Result, bail};
 use ctx_mcp::{McpServer, run_stdio};
+use ctx_sources::SourceSettings;
 use ctx_storage::Storage;
+use ctx_suggest::SuggestConfig;
 use std::process::{Child, Command};
 use std::sync::Arc;
 use std::time::Duration;
@@ -10,6 +12,8 @@ pub async fn handle(
     host: String,
     port: u16,
     read_only: bool,
+    sources: SourceSettings,
+    suggest: SuggestConfig,
     tunnel: bool,
 ) -> Result<()> {
     let db = Arc::new(storage.clone());
@@ -21,13 +25,18 @@ pub async fn handle(
     };
 
     eprintln!("Starting MCP server on {}:{}", host, port);
-    McpServer::serve(db, &host, port, read_only).await?;
+    McpServer::serve(db, &host, port, read_only, sources, suggest).await?;
     Ok(())
 }
 
-pub async fn handle_stdio(storage: &Storage, read_only: bool) -> Result<()> {
+pub async fn handle_stdio(
+    storage: &Storage,
+    read_only: bool,
+    sources: SourceSettings,
+    suggest: SuggestConfig,
+) -> Result<()> {
     let db = Arc::new(storage.clone());
-    run_stdio(db, read_only).await?;
+    run_stdio(db, read_only, sources, suggest).await?;
     Ok(())
 }
 
@@ -1,16 +1,25 @@
-use crate::config::{ArtifactDefinition, Config, PackDefinition, ProjectConfig};
+use super::init::export_pack_to_definition;
+use crate::config::{Config, ProjectConfig};
 use anyhow::Result;
-use ctx_core::{OrderingStrategy, Pack, RenderPolicy};
+use ctx_core::{OrderingStrategy, Pack, RenderMode, RenderPolicy};
 use ctx_engine::Renderer;
-use ctx_sources::{Denylist, SourceHandlerRegistry, SourceOptions};
+use ctx_sources::capture::{self, CaptureOrigin};
+use ctx_sources::{Denylist, SourceHandlerRegistry, SourceOptions, SourceSettings, anchor_uri};
 use ctx_storage::Storage;
 use ctx_suggest::{SuggestConfig, SuggestRequest, SuggestionEngine};
+use std::io::IsTerminal;
 use std::path::Path;
 
-pub async fn create(storage: &Storage, name: String, tokens: usize) -> Result<()> {
+pub async fn create(
+    storage: &Storage,
+    name: String,
+    tokens: usize,
+    line_numbers: bool,
+) -> Result<()> {
     let policies = RenderPolicy {
         budget_tokens: tokens,
         ordering: OrderingStrategy::PriorityThenTime,
+        line_numbers,
     };
 
     let pack = Pack::new(name.clone(), policies);
@@ -19,6 +28,9 @@ pub async fn create(storage: &Storage, name: String, tokens: usize) -> Result<()
     println!("✓ Created pack: {}", name);
     println!("  ID: {}", pack.id);
     println!("  Token budget: {}", tokens);
+    if line_numbers {
+        println!("  Line numbers: on");
+    }
 
     Ok(())
 }
@@ -47,6 +59,9 @@ pub async fn show(storage: &Storage, pack_name: String) -> Result<()> {
     println!("Pack: {}", pack.name);
     println!("  ID: {}", pack.id);
     println!("  Token budget: {}", pack.policies.budget_tokens);
+    if pack.policies.line_numbers {
+        println!("  Line numbers: on");
+    }
     println!("  Created: {}", pack.created_at);
     println!("  Updated: {}", pack.updated_at);
 
@@ -63,12 +78,36 @@ pub async fn show(storage: &Storage, pack_name: String) -> Result<()> {
             );
             let type_json = serde_json::to_string_pretty(&item.artifact.artifact_type)?;
             println!("    Type: {}", type_json);
+            if let Some(file) = &item.artifact.metadata.file {
+                println!(
+                    "    File: {}",
+                    describe_file(file, item.artifact.metadata.size_bytes)
+                );
+            }
         }
     }
 
     Ok(())
 }
 
+/// One-line summary of an artifact's file facts, e.g. `rust, 120 lines, 4096 bytes, ...`
+fn describe_file(file: &ctx_core::FileInfo, size_bytes: usize) -> String {
+    let mut parts = Vec::new();
+    if let Some(language) = &file.language {
+        parts.push(language.clone());
+    }
+    parts.push(format!("{} lines", file.rendered_lines));
+    parts.push(format!("{} bytes", size_bytes));
+    if let Some(modified) = file.modified {
+        parts.push(format!("modified {}", modified));
+    }
+    if let (Some(commit), Some(author)) = (&file.git_commit, &file.git_author) {
+        let short = commit.get(..7).unwrap_or(commit);
+        parts.push(format!("last commit {} by {}", short, author));
+    }
+    parts.join(", ")
+}
+
 #[allow(clippy::too_many_arguments)]
 pub async fn add(
     storage: &Storage,
@@ -83,12 +122,23 @@ pub async fn add(
     recursive: bool,
     with_related: bool,
     related_max: usize,
+    mode: RenderMode,
+    title: Option<String>,
+    sources: &SourceSettings,
+    suggest: &SuggestConfig,
 ) -> Result<()> {
-    let registry = SourceHandlerRegistry::new();
+    let registry = SourceHandlerRegistry::with_settings(sources);
 
     // Get pack
     let pack = storage.get_pack(&pack_name).await?;
 
+    // stdin and clipboard can only be read now, so they're stored as snippets
+    if let Some((origin, uri_title)) = capture::parse_capture_uri(&source) {
+        let title = title.as_deref().or(uri_title);
+        let max_bytes = sources.files.max_file_bytes;
+        return add_snippet(storage, &pack, origin, title, priority, max_bytes).await;
+    }
+
     // Parse source into artifact
     let options = SourceOptions {
         range: start.and_then(|s| end.map(|e| (s, e))),
@@ -98,11 +148,12 @@ pub async fn add(
         priority,
     };
 
-    let artifact = registry.parse(&source, options).await?;
+    let mut artifact = registry.parse(&source, options).await?;
+    artifact.metadata.render_mode = mode;
+    let resolved = registry.resolve_paths(&artifact);
 
     // Check denylist for file artifacts
-    if let ctx_core::ArtifactType::File { path } | ctx_core::ArtifactType::FileRange { path, .. } =
-        &artifact.artifact_type
+    if let Some(path) = resolved.artifact_type.file_path()
         && denylist.is_denied(path)
     {
         let pattern = denylist
@@ -116,15 +167,11 @@ pub async fn add(
     }
 
     // Check if artifact is a collection
-    let is_collection = matches!(
-        artifact.artifact_type,
-        ctx_core::ArtifactType::CollectionMdDir { .. }
-            | ctx_core::ArtifactType::CollectionGlob { .. }
-    );
+    let is_collection = artifact.artifact_type.is_collection();
 
     // Extract file path for related files lookup
-    let file_path = match &artifact.artifact_type {
-        ctx_core::ArtifactType::File { path } => Some(path.clone()),
+    let file_path = match &resolved.artifact_type {
+        ctx_core::ArtifactType::File { path, .. } => Some(path.clone()),
         ctx_core::ArtifactType::FileRange { path, .. } => Some(path.clone()),
         _ => None,
     };
@@ -149,6 +196,9 @@ pub async fn add(
     println!("  Artifact ID: {}", artifact.id);
     println!("  Source: {}", artifact.source_uri);
     println!("  Priority: {}", priority);
+    if !mode.is_full() {
+        println!("  Mode: {}", mode);
+    }
 
     // Handle --with-related flag
     if with_related && let Some(file_path) = file_path {
@@ -160,6 +210,7 @@ pub async fn add(
             &file_path,
             priority,
             related_max,
+            suggest,
         )
         .await?;
     }
@@ -167,7 +218,49 @@ pub async fn add(
     Ok(())
 }
 
+/// Capture stdin or clipboard text and store it as a snippet artifact
+async fn add_snippet(
+    storage: &Storage,
+    pack: &Pack,
+    origin: CaptureOrigin,
+    title: Option<&str>,
+    priority: i64,
+    max_bytes: u64,
+) -> Result<()> {
+    let content = match origin {
+        CaptureOrigin::Stdin => {
+            let stdin = std::io::stdin();
+            if stdin.is_terminal() {
+                anyhow::bail!(
+                    "Nothing piped to stdin. Usage: cargo build 2>&1 | ctx add {} - --title \"Build errors\"",
+                    pack.name
+                );
+            }
+            capture::read_capped_text(origin, stdin, max_bytes)?
+        }
+        CaptureOrigin::Clipboard => arboard::Clipboard::new()?.get_text()?,
+    };
+
+    if content.trim().is_empty() {
+        anyhow::bail!("Nothing to add: {} is empty", origin.scheme());
+    }
+
+    let artifact = capture::snippet_artifact(origin, title, &content);
+    storage
+        .add_artifact_to_pack_with_content(&pack.id, &artifact, &content, priority)
+        .await?;
+
+    println!("✓ Added snippet to pack '{}'", pack.name);
+    println!("  Artifact ID: {}", artifact.id);
+    println!("  Source: {}", artifact.source_uri);
+    println!("  Size: {} bytes", content.len());
+    println!("  Priority: {}", priority);
+
+    Ok(())
+}
+
 /// Add related files based on suggestions
+#[allow(clippy::too_many_arguments)]
 async fn add_related_files(
     storage: &Storage,
     denylist: &Denylist,
@@ -176,20 +269,34 @@ async fn add_related_files(
     file_path: &str,
     priority: i64,
     max_related: usize,
+    suggest: &SuggestConfig,
 ) -> Result<()> {
     let file = std::path::Path::new(file_path);
-    let workspace = super::find_workspace_root(file)?;
+    let workspace = ctx_core::find_project_root(file);
 
     let config = SuggestConfig {
         max_results: max_related,
         min_score: 0.2,
-        ..Default::default()
+        ..suggest.clone()
     };
     let engine = SuggestionEngine::new(&workspace, config);
+
+    let existing = storage.get_pack_artifacts(&pack.id).await?;
+    let existing_paths: Vec<String> = existing
+        .iter()
+        .map(|item| registry.resolve_paths(&item.artifact))
+        .filter_map(|a| match a.artifact_type {
+            ctx_core::ArtifactType::File { path, .. } => Some(path),
+            ctx_core::ArtifactType::FileRange { path, .. } => Some(path),
+            _ => None,
+        })
+        .collect();
+
     let request = SuggestRequest {
         file: file_path.to_string(),
         pack_name: Some(pack.name.clone()),
         max_results: Some(max_related),
+        exclude: existing_paths,
     };
 
     let response = engine.suggest(&request).await?;
@@ -199,23 +306,10 @@ async fn add_related_files(
         return Ok(());
     }
 
-    let existing = storage.get_pack_artifacts(&pack.id).await?;
-    let existing_paths: std::collections::HashSet<String> = existing
-        .iter()
-        .filter_map(|a| match &a.artifact.artifact_type {
-            ctx_core::ArtifactType::File { path } => Some(path.clone()),
-            ctx_core::ArtifactType::FileRange { path, .. } => Some(path.clone()),
-            _ => None,
-        })
-        .collect();
-
     println!("\n  Adding related files:");
     let mut added = 0;
 
     for suggestion in response.suggestions {
-        if existing_paths.contains(&suggestion.path) {
-            continue;
-        }
         if denylist.is_denied(&suggestion.path) {
             continue;
         }
@@ -275,13 +369,18 @@ pub async fn preview(
     show_tokens: bool,
     show_redactions: bool,
     show_payload: bool,
+    line_numbers: Option<bool>,
+    sources: &SourceSettings,
 ) -> Result<()> {
-    let renderer = Renderer::new(storage.clone());
+    let registry = SourceHandlerRegistry::with_settings(sources);
+    let renderer = Renderer::with_sources(storage.clone(), registry);
     let pack = storage.get_pack(&pack_name).await?;
 
     println!("Previewing pack: {} ({})", pack.name, pack.id);
 
-    let result = renderer.render_pack(&pack.id, None).await?;
+    let result = renderer
+        .render_pack(&pack.id, with_line_numbers(&pack, line_numbers))
+        .await?;
 
     println!("render_hash: {}", result.render_hash);
     println!(
@@ -356,14 +455,14 @@ pub async fn delete(storage: &Storage, pack_name: String, force: bool) -> Result
     Ok(())
 }
 
-pub async fn sync(storage: &Storage, _config: &Config, denylist: &Denylist) -> Result<()> {
+pub async fn sync(storage: &Storage, config: &Config, denylist: &Denylist) -> Result<()> {
     let (project_root, project_config) = ProjectConfig::find_and_load()?
         .ok_or_else(|| anyhow::anyhow!("No ctx.toml found in current or parent directories"))?;
 
     let namespace = ProjectConfig::project_namespace(&project_root);
     println!("Syncing packs from ctx.toml (project: {})", namespace);
 
-    let registry = SourceHandlerRegistry::new();
+    let registry = SourceHandlerRegistry::with_settings(&config.source_settings());
     let mut synced = 0;
     let mut errors = 0;
 
@@ -375,9 +474,16 @@ pub async fn sync(storage: &Storage, _config: &Config, denylist: &Denylist) -> R
 
         // Check if pack exists, create or update
         let pack = match storage.get_pack(&full_name).await {
-            Ok(existing) => {
-                // Pack exists - for now just use existing
-                // TODO: update budget if changed
+            Ok(mut existing) => {
+                if existing.policies.budget_tokens != budget
+                    || existing.policies.line_numbers != pack_def.line_numbers
+                {
+                    existing.policies.budget_tokens = budget;
+                    existing.policies.line_numbers = pack_def.line_numbers;
+                    storage
+                        .update_pack_policies(&existing.id, &existing.policies)
+                        .await?;
+                }
                 existing
             }
             Err(_) => {
@@ -385,6 +491,7 @@ pub async fn sync(storage: &Storage, _config: &Config, denylist: &Denylist) -> R
                 let policies = RenderPolicy {
                     budget_tokens: budget,
                     ordering: OrderingStrategy::PriorityThenTime,
+                    line_numbers: pack_def.line_numbers,
                 };
                 let new_pack = Pack::new(full_name.clone(), policies);
                 storage.create_pack(&new_pack).await?;
@@ -404,8 +511,8 @@ pub async fn sync(storage: &Storage, _config: &Config, denylist: &Denylist) -> R
 
         // Add artifacts from definition
         for artifact_def in &pack_def.artifacts {
-            // Resolve relative paths to absolute
-            let source = resolve_source(&artifact_def.source, &project_root);
+            // Relative paths in ctx.toml are relative to the project, not the current directory
+            let source = anchor_uri(&artifact_def.source, &project_root);
 
             // Check denylist
             if denylist.is_denied(&source) {
@@ -419,12 +526,9 @@ pub async fn sync(storage: &Storage, _config: &Config, denylist: &Denylist) -> R
             };
 
             match registry.parse(&source, options).await {
-                Ok(artifact) => {
-                    let is_collection = matches!(
-                        artifact.artifact_type,
-                        ctx_core::ArtifactType::CollectionMdDir { .. }
-                            | ctx_core::ArtifactType::CollectionGlob { .. }
-                    );
+                Ok(mut artifact) => {
+                    artifact.metadata.render_mode = artifact_def.mode;
+                    let is_collection = artifact.artifact_type.is_collection();
 
                     if is_collection {
                         storage.create_artifact(&artifact).await?;
@@ -531,12 +635,14 @@ pub async fn lint(
 
     println!("Linting pack: {} ({})", pack.name, pack.id);
 
+    let registry = SourceHandlerRegistry::new();
     let pack_files: std::collections::HashSet<String> = artifacts
         .iter()
-        .filter_map(|a| match &a.artifact.artifact_type {
-            ctx_core::ArtifactType::File { path }
+        .map(|item| registry.resolve_paths(&item.artifact))
+        .filter_map(|a| match a.artifact_type {
+            ctx_core::ArtifactType::File { path, .. }
             | ctx_core::ArtifactType::FileRange { path, .. }
-            | ctx_core::ArtifactType::Markdown { path } => Some(path.clone()),
+            | ctx_core::ArtifactType::Markdown { path } => Some(path),
             _ => None,
         })
         .collect();
@@ -547,7 +653,7 @@ pub async fn lint(
     }
 
     let first_file = pack_files.iter().next().unwrap();
-    let workspace = super::find_workspace_root(std::path::Path::new(first_file))?;
+    let workspace = ctx_core::find_project_root(std::path::Path::new(first_file));
     let mut missing_deps: std::collections::HashMap<String, Vec<String>> =
         std::collections::HashMap::new();
 
@@ -565,13 +671,13 @@ pub async fn lint(
         };
 
         for import in imports {
-            if let Some(resolved) = resolve_import(&workspace, path, ext, &import) {
+            for resolved in ctx_suggest::parsers::resolve_import(&workspace, path, &import) {
                 let resolved_str = resolved.to_string_lossy().to_string();
                 if resolved.exists() && !pack_files.contains(&resolved_str) {
-                    missing_deps
-                        .entry(resolved_str)
-                        .or_default()
-                        .push(file_path.clone());
+                    let importers = missing_deps.entry(resolved_str).or_default();
+                    if !importers.contains(file_path) {
+                        importers.push(file_path.clone());
+                    }
                 }
             }
         }
@@ -583,7 +689,7 @@ pub async fn lint(
     }
 
     let mut sorted_deps: Vec<_> = missing_deps.into_iter().collect();
-    sorted_deps.sort_by(|a, b| b.1.len().cmp(&a.1.len()));
+    sorted_deps.sort_by_key(|b| std::cmp::Reverse(b.1.len()));
 
     println!("\n  Missing dependencies ({}):", sorted_deps.len());
     for (dep, importers) in &sorted_deps {
@@ -600,7 +706,6 @@ pub async fn lint(
 
     if fix {
         println!("\n  Fixing...");
-        let registry = SourceHandlerRegistry::new();
         let mut fixed = 0;
 
         for (dep_path, _) in sorted_deps {
@@ -640,94 +745,107 @@ pub async fn lint(
     Ok(())
 }
 
-/// Resolve an import to a file path (delegates to ctx_suggest parsers)
-fn resolve_import(
-    workspace: &Path,
-    source_file: &Path,
-    ext: &str,
-    import: &str,
-) -> Option<std::path::PathBuf> {
-    match ext {
-        "rs" => ctx_suggest::parsers::rust::resolve_import(workspace, source_file, import),
-        "ts" | "tsx" | "js" | "jsx" | "mts" | "mjs" => {
-            ctx_suggest::parsers::typescript::resolve_import(workspace, source_file, import)
-        }
-        "py" => ctx_suggest::parsers::python::resolve_import(workspace, source_file, import),
-        _ => None,
-    }
-}
-
-/// Export a pack from DB to a PackDefinition
-async fn export_pack_to_definition(
+pub async fn rebase_root(
     storage: &Storage,
-    pack_name: &str,
-    project_root: &Path,
-) -> Result<(String, PackDefinition)> {
-    let pack = storage.get_pack(pack_name).await?;
+    pack_name: String,
+    root: std::path::PathBuf,
+    from: Option<std::path::PathBuf>,
+) -> Result<()> {
+    let pack = storage.get_pack(&pack_name).await?;
     let artifacts = storage.get_pack_artifacts(&pack.id).await?;
 
-    let artifact_defs: Vec<ArtifactDefinition> = artifacts
-        .into_iter()
-        .map(|item| {
-            let source = make_relative_source(&item.artifact.source_uri, project_root);
-            ArtifactDefinition {
-                source,
-                priority: item.priority,
-            }
-        })
-        .collect();
+    // The new root may only exist inside a container, so it isn't required to exist here
+    let root = std::path::absolute(&root)?;
+    let from = from.map(std::path::absolute).transpose()?;
 
-    // Strip namespace if present
-    let local_name = ProjectConfig::strip_namespace(project_root, &pack.name)
-        .unwrap_or_else(|| pack.name.clone());
+    let mut rebased = 0;
+    let mut skipped = Vec::new();
+    let mut missing = Vec::new();
 
-    let definition = PackDefinition {
-        budget: Some(pack.policies.budget_tokens),
-        artifacts: artifact_defs,
-    };
+    for item in artifacts {
+        let mut artifact = item.artifact;
+        let paths = artifact.artifact_type.local_paths();
+        if paths.is_empty() {
+            continue;
+        }
 
-    Ok((local_name, definition))
-}
+        // Artifacts added with absolute paths are re-rooted from --from or their own workspace
+        let old_root = from.clone().or_else(|| {
+            paths
+                .first()
+                .and_then(|path| ctx_core::find_workspace_root(Path::new(path)))
+        });
+
+        if !artifact.rebase_root(&root, old_root.as_deref()) {
+            skipped.push(artifact.source_uri.clone());
+            continue;
+        }
 
-/// Convert absolute paths in source URIs to relative paths
-fn make_relative_source(source_uri: &str, project_root: &Path) -> String {
-    if let Some(path) = source_uri.strip_prefix("file:") {
-        let path_buf = std::path::PathBuf::from(path);
-        if path_buf.is_absolute()
-            && let Ok(rel_path) = path_buf.strip_prefix(project_root)
+        let resolved = artifact.resolve_paths(None);
+        if resolved
+            .artifact_type
+            .file_path()
+            .is_some_and(|path| !Path::new(path).exists())
         {
-            return format!("file:{}", rel_path.display());
+            missing.push(artifact.source_uri.clone());
         }
+
+        storage.update_artifact(&artifact).await?;
+        rebased += 1;
     }
-    source_uri.to_string()
-}
 
-/// Resolve relative source URIs to absolute paths
-fn resolve_source(source_uri: &str, project_root: &Path) -> String {
-    if let Some(path) = source_uri.strip_prefix("file:") {
-        let path_buf = std::path::PathBuf::from(path);
-        if path_buf.is_relative() {
-            let abs_path = project_root.join(&path_buf);
-            return format!("file:{}", abs_path.display());
+    println!(
+        "✓ Rebased {} artifact(s) in pack '{}' onto {}",
+        rebased,
+        pack.name,
+        root.display()
+    );
+    if !skipped.is_empty() {
+        println!("\n  Skipped (paths outside the old root; pass --from <old-root>):");
+        for source in &skipped {
+            println!("    {}", source);
         }
-        source_uri.to_string()
-    } else if let Some(pattern) = source_uri.strip_prefix("glob:") {
-        // For globs, prepend project root to make pattern absolute
-        if !pattern.starts_with('/') {
-            let abs_pattern = project_root.join(pattern);
-            return format!("glob:{}", abs_pattern.display());
+    }
+    if !missing.is_empty() {
+        println!("\n  Not found under the new root on this machine:");
+        for source in &missing {
+            println!("    {}", source);
         }
-        source_uri.to_string()
-    } else {
-        source_uri.to_string()
+    }
+
+    Ok(())
+}
+
+/// `--line-numbers` / `--no-line-numbers`, or None to follow the pack
+pub fn line_numbers_override(line_numbers: bool, no_line_numbers: bool) -> Option<bool> {
+    match (line_numbers, no_line_numbers) {
+        (true, _) => Some(true),
+        (_, true) => Some(false),
+        _ => None,
     }
 }
 
-pub async fn copy_to_clipboard(storage: &Storage, pack_name: String) -> Result<()> {
-    let renderer = Renderer::new(storage.clone());
+/// The pack's policy with line numbers turned on or off, when asked for on the command line
+fn with_line_numbers(pack: &Pack, line_numbers: Option<bool>) -> Option<RenderPolicy> {
+    line_numbers.map(|line_numbers| RenderPolicy {
+        line_numbers,
+        ..pack.policies.clone()
+    })
+}
+
+pub async fn copy_to_clipboard(
+    storage: &Storage,
+    pack_name: String,
+    line_numbers: Option<bool>,
+    sources: &SourceSettings,
+) -> Result<()> {
+    let registry = SourceHandlerRegistry::with_settings(sources);
+    let renderer = Renderer::with_sources(storage.clone(), registry);
     let pack = storage.get_pack(&pack_name).await?;
 
-    let result = renderer.render_pack(&pack.id, None).await?;
+    let result = renderer
+        .render_pack(&pack.id, with_line_numbers(&pack, line_numbers))
+        .await?;
     let payload = result
         .payload
         .ok_or_else(|| anyhow::anyhow!("No payload generated"))?;
@@ -747,6 +865,7 @@ pub async fn quick(
     file: std::path::PathBuf,
     output: bool,
     max_related: usize,
+    suggest: &SuggestConfig,
 ) -> Result<()> {
     let file_abs = if file.is_absolute() {
         file.clone()
@@ -758,20 +877,21 @@ pub async fn quick(
         anyhow::bail!("File not found: {}", file_abs.display());
     }
 
-    let workspace = super::find_workspace_root(&file_abs)?;
+    let workspace = ctx_core::find_project_root(&file_abs);
     let file_str = file_abs.to_string_lossy().to_string();
 
     // Get suggestions
     let config = SuggestConfig {
         max_results: max_related,
         min_score: 0.2,
-        ..Default::default()
+        ..suggest.clone()
     };
     let engine = SuggestionEngine::new(&workspace, config);
     let request = SuggestRequest {
         file: file_str.clone(),
         pack_name: None,
         max_results: Some(max_related),
+        exclude: Vec::new(),
     };
 
     let response = engine.suggest(&request).await?;
@@ -1,29 +1,65 @@
 //! Suggest command - find related files
 
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 use anyhow::Result;
-use ctx_suggest::{SuggestConfig, SuggestRequest, SuggestionEngine};
+use ctx_engine::{PackFiles, Renderer};
+use ctx_sources::{SourceHandlerRegistry, SourceSettings};
+use ctx_storage::Storage;
+use ctx_suggest::{PackSuggestRequest, SuggestConfig, SuggestRequest, SuggestionEngine};
+
+#[allow(clippy::too_many_arguments)]
+pub async fn handle_suggest(
+    storage: &Storage,
+    file: Option<PathBuf>,
+    pack: Option<String>,
+    max: usize,
+    format: &str,
+    sources: &SourceSettings,
+    config: SuggestConfig,
+) -> Result<()> {
+    let PackFiles {
+        files: pack_files,
+        workspace: pack_workspace,
+    } = match &pack {
+        Some(pack) => {
+            let registry = SourceHandlerRegistry::with_settings(sources);
+            let renderer = Renderer::with_sources(storage.clone(), registry);
+            renderer.pack_files(pack).await?
+        }
+        None => PackFiles::default(),
+    };
 
-pub async fn handle_suggest(file: PathBuf, max: usize, format: &str) -> Result<()> {
     // Canonicalize the file path
-    let file = file.canonicalize()?;
+    let file = file.map(|file| file.canonicalize()).transpose()?;
 
     // Find workspace root
-    let workspace = super::find_workspace_root(&file)?;
+    let workspace = match (&file, pack_workspace) {
+        (Some(file), _) => ctx_core::find_project_root(file),
+        (None, Some(workspace)) => workspace,
+        (None, None) => anyhow::bail!(
+            "Pack '{}' has no files to suggest for",
+            pack.unwrap_or_default()
+        ),
+    };
 
     // Create suggestion engine
     let config = SuggestConfig {
         max_results: max,
-        ..Default::default()
+        ..config
     };
     let engine = SuggestionEngine::new(&workspace, config);
 
+    let Some(file) = file else {
+        return suggest_for_pack(&engine, pack_files, max, format).await;
+    };
+
     // Get suggestions
     let request = SuggestRequest {
         file: file.to_string_lossy().to_string(),
-        pack_name: None,
+        pack_name: pack,
         max_results: Some(max),
+        exclude: pack_files,
     };
 
     let response = engine.suggest(&request).await?;
@@ -41,17 +77,10 @@ pub async fn handle_suggest(file: PathBuf, max: usize, format: &str) -> Result<(
                 println!("No suggestions found.");
             } else {
                 for (i, suggestion) in response.suggestions.iter().enumerate() {
-                    // Make path relative to workspace for readability
-                    let display_path = suggestion
-                        .path
-                        .strip_prefix(workspace.to_string_lossy().as_ref())
-                        .map(|p| p.trim_start_matches('/'))
-                        .unwrap_or(&suggestion.path);
-
                     println!(
                         "{}. {} (score: {:.0}%)",
                         i + 1,
-                        display_path,
+                        display_path(&suggestion.path, &workspace),
                         suggestion.score * 100.0
                     );
 
@@ -69,3 +98,56 @@ pub async fn handle_suggest(file: PathBuf, max: usize, format: &str) -> Result<(
 
     Ok(())
 }
+
+async fn suggest_for_pack(
+    engine: &SuggestionEngine,
+    files: Vec<String>,
+    max: usize,
+    format: &str,
+) -> Result<()> {
+    let request = PackSuggestRequest {
+        files,
+        exclude: Vec::new(),
+        max_results: Some(max),
+    };
+    let response = engine.suggest_for_pack(&request).await?;
+    let workspace = engine.workspace();
+
+    match format {
+        "json" => {
+            println!("{}", serde_json::to_string_pretty(&response)?);
+        }
+        _ => {
+            println!("Suggestions for {} pack files", response.files.len());
+            println!("({} ms)\n", response.elapsed_ms);
+
+            if response.suggestions.is_empty() {
+                println!("No suggestions found.");
+            }
+            for (i, suggestion) in response.suggestions.iter().enumerate() {
+                println!(
+                    "{}. {} (+{:.0}% coverage, {} tokens)",
+                    i + 1,
+                    display_path(&suggestion.path, workspace),
+                    suggestion.coverage_gain * 100.0,
+                    suggestion.tokens
+                );
+                let related: Vec<&str> = suggestion
+                    .related_to
+                    .iter()
+                    .map(|file| display_path(file, workspace))
+                    .collect();
+                println!("   related to: {}", related.join(", "));
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// `path` relative to the workspace, for readability
+fn display_path<'a>(path: &'a str, workspace: &Path) -> &'a str {
+    path.strip_prefix(workspace.to_string_lossy().as_ref())
+        .map(|p| p.trim_start_matches('/'))
+        .unwrap_or(path)
+}
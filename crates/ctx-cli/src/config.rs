@@ -1,5 +1,9 @@
+use ctx_core::{RenderMode, TextEncoding};
+use ctx_sources::issue::{GITHUB_API, GITLAB_API, IssueApi};
+use ctx_sources::{CommandPolicy, FileSettings, IssueSettings, SourceSettings};
+use ctx_suggest::SuggestConfig;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::path::{Path, PathBuf};
 
 // ============================================================================
@@ -17,6 +21,22 @@ pub struct Config {
 
     #[serde(default)]
     pub mcp: McpConfig,
+
+    #[serde(default)]
+    pub files: FilesConfig,
+
+    #[serde(default)]
+    pub commands: CommandsConfig,
+
+    #[serde(default)]
+    pub issues: IssuesConfig,
+
+    #[serde(default)]
+    pub suggest: SuggestionsConfig,
+
+    /// Plugin executables by URI scheme, e.g. `jira = "/usr/local/bin/ctx-source-jira"`
+    #[serde(default)]
+    pub plugins: BTreeMap<String, PathBuf>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -37,12 +57,85 @@ pub struct McpConfig {
     pub read_only: bool,
 }
 
+/// Limits for `file:` sources
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FilesConfig {
+    /// Larger files need `?head=N` or `?tail=N`
+    #[serde(default = "default_max_file_bytes")]
+    pub max_file_bytes: u64,
+
+    /// Default for files without `?encoding=` (utf-8, lossy, utf-16, latin-1 or auto)
+    #[serde(default)]
+    pub encoding: TextEncoding,
+}
+
+/// Settings for `cmd:` sources, which run arbitrary commands and are off by default
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CommandsConfig {
+    #[serde(default)]
+    pub enabled: bool,
+
+    /// Also run commands for `ctx mcp` clients, which can add `cmd:` artifacts over REST
+    #[serde(default)]
+    pub allow_mcp: bool,
+
+    /// Run commands here instead of the directory they were added from
+    #[serde(default)]
+    pub working_dir: Option<PathBuf>,
+
+    #[serde(default = "default_command_timeout")]
+    pub timeout_secs: u64,
+
+    #[serde(default = "default_command_max_output")]
+    pub max_output_bytes: usize,
+
+    /// Environment variables passed to commands (`NAME` or `PREFIX_*`)
+    #[serde(default = "default_command_env")]
+    pub env_allowlist: Vec<String>,
+}
+
+/// Settings for `issue:` sources. An API base that isn't an http(s) URL is a directory of exports.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IssuesConfig {
+    #[serde(default = "default_github_api")]
+    pub github_api: String,
+
+    /// Environment variable holding the GitHub token
+    #[serde(default = "default_github_token_env")]
+    pub github_token_env: String,
+
+    #[serde(default = "default_gitlab_api")]
+    pub gitlab_api: String,
+
+    /// Environment variable holding the GitLab token
+    #[serde(default = "default_gitlab_token_env")]
+    pub gitlab_token_env: String,
+
+    /// Reuse fetched issues for this long; 0 fetches on every render
+    #[serde(default = "default_issue_cache_ttl")]
+    pub cache_ttl_secs: u64,
+}
+
+/// Settings for file suggestions
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SuggestionsConfig {
+    /// Test file names tried for every language on top of the built-in conventions,
+    /// e.g. `{stem}.integration.{ext}`
+    #[serde(default)]
+    pub test_patterns: Vec<String>,
+}
+
 impl Default for Config {
     fn default() -> Self {
         Self {
             budget_tokens: default_budget(),
             denylist: DenylistConfig::default(),
             mcp: McpConfig::default(),
+            files: FilesConfig::default(),
+            commands: CommandsConfig::default(),
+            issues: IssuesConfig::default(),
+            suggest: SuggestionsConfig::default(),
+            plugins: BTreeMap::new(),
         }
     }
 }
@@ -65,6 +158,83 @@ impl Default for McpConfig {
     }
 }
 
+impl Default for FilesConfig {
+    fn default() -> Self {
+        Self {
+            max_file_bytes: default_max_file_bytes(),
+            encoding: TextEncoding::default(),
+        }
+    }
+}
+
+impl FilesConfig {
+    pub fn settings(&self) -> FileSettings {
+        FileSettings {
+            max_file_bytes: self.max_file_bytes,
+            encoding: self.encoding,
+        }
+    }
+}
+
+impl Default for CommandsConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            allow_mcp: false,
+            working_dir: None,
+            timeout_secs: default_command_timeout(),
+            max_output_bytes: default_command_max_output(),
+            env_allowlist: default_command_env(),
+        }
+    }
+}
+
+impl Default for IssuesConfig {
+    fn default() -> Self {
+        Self {
+            github_api: default_github_api(),
+            github_token_env: default_github_token_env(),
+            gitlab_api: default_gitlab_api(),
+            gitlab_token_env: default_gitlab_token_env(),
+            cache_ttl_secs: default_issue_cache_ttl(),
+        }
+    }
+}
+
+impl IssuesConfig {
+    pub fn settings(&self) -> IssueSettings {
+        IssueSettings {
+            github: IssueApi {
+                base: self.github_api.clone(),
+                token_env: self.github_token_env.clone(),
+            },
+            gitlab: IssueApi {
+                base: self.gitlab_api.clone(),
+                token_env: self.gitlab_token_env.clone(),
+            },
+            cache_ttl: std::time::Duration::from_secs(self.cache_ttl_secs),
+            cache_dir: None,
+        }
+    }
+}
+
+impl CommandsConfig {
+    /// Policy for running `cmd:` sources, or None if they are disabled
+    pub fn policy(&self) -> Option<CommandPolicy> {
+        self.enabled.then(|| CommandPolicy {
+            working_dir: self.working_dir.clone(),
+            timeout: std::time::Duration::from_secs(self.timeout_secs),
+            max_output_bytes: self.max_output_bytes,
+            env_allowlist: self.env_allowlist.clone(),
+        })
+    }
+
+    /// Policy for `ctx mcp`, None unless `allow_mcp` opts in
+    pub fn mcp_policy(&self) -> Option<CommandPolicy> {
+        self.allow_mcp.then(|| self.policy()).flatten()
+    }
+}
+
 fn default_budget() -> usize {
     128_000
 }
@@ -77,6 +247,45 @@ fn default_port() -> u16 {
     17373
 }
 
+fn default_max_file_bytes() -> u64 {
+    10 * 1024 * 1024
+}
+
+fn default_command_timeout() -> u64 {
+    30
+}
+
+fn default_command_max_output() -> usize {
+    256 * 1024
+}
+
+fn default_command_env() -> Vec<String> {
+    ["PATH", "HOME", "USER", "LANG", "LC_ALL", "TERM", "TMPDIR"]
+        .iter()
+        .map(|s| s.to_string())
+        .collect()
+}
+
+fn default_github_api() -> String {
+    GITHUB_API.to_string()
+}
+
+fn default_github_token_env() -> String {
+    "GITHUB_TOKEN".to_string()
+}
+
+fn default_gitlab_api() -> String {
+    GITLAB_API.to_string()
+}
+
+fn default_gitlab_token_env() -> String {
+    "GITLAB_TOKEN".to_string()
+}
+
+fn default_issue_cache_ttl() -> u64 {
+    300
+}
+
 fn default_patterns() -> Vec<String> {
     vec![
         "**/.env*".to_string(),
@@ -120,6 +329,25 @@ impl Config {
             PathBuf::from("~/.ctx/config.toml")
         }
     }
+
+    /// Source handler settings (`file:`, `cmd:`, `issue:`, plugins) from this config
+    pub fn source_settings(&self) -> SourceSettings {
+        SourceSettings {
+            files: self.files.settings(),
+            command_policy: self.commands.policy(),
+            plugins: self.plugins.clone(),
+            issues: self.issues.settings(),
+            workspace: None,
+        }
+    }
+
+    /// Suggestion engine settings from this config
+    pub fn suggest_config(&self) -> SuggestConfig {
+        SuggestConfig {
+            test_patterns: self.suggest.test_patterns.clone(),
+            ..Default::default()
+        }
+    }
 }
 
 // ============================================================================
@@ -159,6 +387,10 @@ pub struct PackDefinition {
     /// Token budget (optional, uses project default)
     pub budget: Option<usize>,
 
+    /// Prefix file lines with their line numbers when rendering
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub line_numbers: bool,
+
     /// Artifacts in this pack
     #[serde(default)]
     pub artifacts: Vec<ArtifactDefinition>,
@@ -173,6 +405,10 @@ pub struct ArtifactDefinition {
     /// Priority (higher = included first)
     #[serde(default)]
     pub priority: i64,
+
+    /// Render mode (full, outline, signatures)
+    #[serde(default, skip_serializing_if = "RenderMode::is_full")]
+    pub mode: RenderMode,
 }
 
 impl ProjectConfig {
@@ -267,6 +503,76 @@ mod tests {
         assert_eq!(parsed.budget_tokens, config.budget_tokens);
     }
 
+    #[test]
+    fn test_commands_disabled_by_default() {
+        let config: Config = toml::from_str("budget_tokens = 1000").unwrap();
+        assert!(config.commands.policy().is_none());
+
+        let config: Config = toml::from_str(
+            r#"
+[commands]
+enabled = true
+timeout_secs = 5
+"#,
+        )
+        .unwrap();
+        let policy = config.commands.policy().unwrap();
+        assert_eq!(policy.timeout.as_secs(), 5);
+        assert_eq!(policy.max_output_bytes, 256 * 1024);
+        assert!(policy.env_allowlist.contains(&"PATH".to_string()));
+        assert!(config.commands.mcp_policy().is_none());
+
+        let config: Config = toml::from_str(
+            r#"
+[commands]
+enabled = true
+allow_mcp = true
+"#,
+        )
+        .unwrap();
+        assert!(config.commands.mcp_policy().is_some());
+    }
+
+    #[test]
+    fn test_source_settings_config() {
+        let config: Config = toml::from_str(
+            r#"
+[files]
+max_file_bytes = 1048576
+encoding = "latin-1"
+
+[plugins]
+jira = "/usr/local/bin/ctx-source-jira"
+"#,
+        )
+        .unwrap();
+        let settings = config.source_settings();
+        assert!(settings.command_policy.is_none());
+        assert_eq!(settings.files.max_file_bytes, 1024 * 1024);
+        assert_eq!(settings.files.encoding, TextEncoding::Latin1);
+        assert_eq!(settings.issues.github.base, GITHUB_API);
+        assert_eq!(
+            settings.plugins.get("jira"),
+            Some(&PathBuf::from("/usr/local/bin/ctx-source-jira"))
+        );
+    }
+
+    #[test]
+    fn test_suggest_config() {
+        let config: Config = toml::from_str(
+            r#"
+[suggest]
+test_patterns = ["{stem}.integration.{ext}"]
+"#,
+        )
+        .unwrap();
+        let suggest = config.suggest_config();
+        assert_eq!(suggest.test_patterns, ["{stem}.integration.{ext}"]);
+        assert!(suggest.index_dir.is_none());
+
+        assert!(Config::default().suggest_config().test_patterns.is_empty());
+    }
+
     #[test]
     fn test_denylist_patterns() {
         let config = Config::default();
@@ -282,6 +588,7 @@ default_budget = 50000
 
 [packs.style]
 budget = 128000
+line_numbers = true
 artifacts = [
     { source = "file:CONTRIBUTING.md", priority = 10 },
     { source = "glob:src/**/*.rs" },
@@ -300,6 +607,8 @@ artifacts = [
 
         let style = &config.packs["style"];
         assert_eq!(style.budget, Some(128000));
+        assert!(style.line_numbers);
+        assert!(!config.packs["architecture"].line_numbers);
         assert_eq!(style.artifacts.len(), 2);
         assert_eq!(style.artifacts[0].source, "file:CONTRIBUTING.md");
         assert_eq!(style.artifacts[0].priority, 10);
@@ -21,6 +21,12 @@ async fn main() -> Result<()> {
     let config = Config::load()?;
 
     let db_path = cli.data_dir.as_ref().map(|dir| dir.join("state.db"));
+    let mut sources = config.source_settings();
+    let mut suggest = config.suggest_config();
+    if let Some(dir) = &cli.data_dir {
+        sources.issues.cache_dir = Some(dir.join("issues"));
+        suggest.index_dir = Some(dir.join("index"));
+    }
     let storage = Storage::new(db_path).await?;
     let denylist = Denylist::new(config.denylist.patterns.clone());
 
@@ -30,12 +36,16 @@ async fn main() -> Result<()> {
             file,
             output,
             max_related,
-        } => commands::pack::quick(&storage, &denylist, file, output, max_related).await,
+        } => commands::pack::quick(&storage, &denylist, file, output, max_related, &suggest).await,
 
         // Pack management
-        cli::Commands::Create { name, tokens } => {
+        cli::Commands::Create {
+            name,
+            tokens,
+            line_numbers,
+        } => {
             let budget = tokens.unwrap_or(config.budget_tokens);
-            commands::pack::create(&storage, name, budget).await
+            commands::pack::create(&storage, name, budget, line_numbers).await
         }
         cli::Commands::Add {
             pack,
@@ -48,6 +58,8 @@ async fn main() -> Result<()> {
             recursive,
             with_related,
             related_max,
+            mode,
+            title,
         } => {
             commands::pack::add(
                 &storage,
@@ -62,6 +74,10 @@ async fn main() -> Result<()> {
                 recursive,
                 with_related,
                 related_max,
+                mode,
+                title,
+                &sources,
+                &suggest,
             )
             .await
         }
@@ -75,19 +91,63 @@ async fn main() -> Result<()> {
             tokens,
             redactions,
             payload,
-        } => commands::pack::preview(&storage, pack, tokens, redactions, payload).await,
-        cli::Commands::Cp { pack } => commands::pack::copy_to_clipboard(&storage, pack).await,
+            line_numbers,
+            no_line_numbers,
+        } => {
+            commands::pack::preview(
+                &storage,
+                pack,
+                tokens,
+                redactions,
+                payload,
+                commands::pack::line_numbers_override(line_numbers, no_line_numbers),
+                &sources,
+            )
+            .await
+        }
+        cli::Commands::Cp {
+            pack,
+            line_numbers,
+            no_line_numbers,
+        } => {
+            let line_numbers = commands::pack::line_numbers_override(line_numbers, no_line_numbers);
+            commands::pack::copy_to_clipboard(&storage, pack, line_numbers, &sources).await
+        }
         cli::Commands::Delete { pack, force } => {
             commands::pack::delete(&storage, pack, force).await
         }
         cli::Commands::Lint { pack, fix } => {
             commands::pack::lint(&storage, &denylist, pack, fix).await
         }
+        cli::Commands::RebaseRoot { pack, root, from } => {
+            commands::pack::rebase_root(&storage, pack, root, from).await
+        }
 
         // Discovery
-        cli::Commands::Suggest { file, max, format } => {
-            commands::suggest::handle_suggest(file, max, &format).await
+        cli::Commands::Suggest {
+            file,
+            pack,
+            max,
+            format,
+            author,
+            range,
+            test_pattern,
+        } => {
+            suggest.git_authors = author;
+            suggest.git_range = range;
+            suggest.test_patterns.extend(test_pattern);
+            commands::suggest::handle_suggest(&storage, file, pack, max, &format, &sources, suggest)
+                .await
         }
+        cli::Commands::Index { action } => match action {
+            cli::IndexAction::Build { path } => commands::index::build(path, &suggest).await,
+            cli::IndexAction::Status { path, format } => {
+                commands::index::status(path, &format, suggest.index_dir.as_deref())
+            }
+            cli::IndexAction::Clear { path, all } => {
+                commands::index::clear(path, all, suggest.index_dir.as_deref())
+            }
+        },
 
         // Project config
         cli::Commands::Init { import } => commands::init::handle(&storage, import).await,
@@ -103,12 +163,15 @@ async fn main() -> Result<()> {
             tunnel,
         } => {
             let read_only = read_only || config.mcp.read_only;
+            // MCP clients only run commands with an explicit opt-in
+            sources.command_policy = config.commands.mcp_policy();
             if stdio {
-                commands::mcp::handle_stdio(&storage, read_only).await
+                commands::mcp::handle_stdio(&storage, read_only, sources, suggest).await
             } else {
                 let port = port.unwrap_or(config.mcp.port);
                 let host = host.unwrap_or(config.mcp.host);
-                commands::mcp::handle(&storage, host, port, read_only, tunnel).await
+                commands::mcp::handle(&storage, host, port, read_only, sources, suggest, tunnel)
+                    .await
             }
         }
         cli::Commands::Ui { web, port } => {
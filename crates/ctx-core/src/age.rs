@@ -0,0 +1,11 @@
+use time::OffsetDateTime;
+
+/// How long ago a Unix time was, roughly, e.g. `3h ago`
+pub fn format_age(unix_time: i64) -> String {
+    match OffsetDateTime::now_utc().unix_timestamp() - unix_time {
+        secs if secs < 60 => "just now".to_string(),
+        secs if secs < 3600 => format!("{}m ago", secs / 60),
+        secs if secs < 86400 => format!("{}h ago", secs / 3600),
+        secs => format!("{}d ago", secs / 86400),
+    }
+}
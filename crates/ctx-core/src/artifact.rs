@@ -1,4 +1,5 @@
 use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
 use time::OffsetDateTime;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -36,6 +37,97 @@ impl Artifact {
         self.metadata = metadata;
         self
     }
+
+    /// Store local paths relative to `root` and record the root; no-op unless every path is under it
+    pub fn relativize(&mut self, root: &Path) -> bool {
+        let mut paths = self.artifact_type.local_paths_mut();
+        if paths.is_empty()
+            || !paths
+                .iter()
+                .all(|p| Path::new(p.as_str()).strip_prefix(root).is_ok())
+        {
+            return false;
+        }
+
+        for path in &mut paths {
+            let relative = Path::new(path.as_str())
+                .strip_prefix(root)
+                .map(|rel| rel.to_string_lossy().to_string())
+                .unwrap_or_default();
+            **path = if relative.is_empty() {
+                ".".to_string()
+            } else {
+                relative
+            };
+        }
+        self.metadata.workspace_root = Some(root.to_string_lossy().to_string());
+        true
+    }
+
+    /// Copy with absolute paths under `workspace` when the files exist there, so each checkout renders
+    /// its own copies, and under the recorded root otherwise. Paths that are just the root exist in any
+    /// workspace, so they only move there once the recorded root is gone.
+    pub fn resolve_paths(&self, workspace: Option<&Path>) -> Artifact {
+        let mut resolved = self.clone();
+        let Some(recorded) = resolved.metadata.workspace_root.take() else {
+            return resolved;
+        };
+
+        let recorded = PathBuf::from(recorded);
+        let root = workspace
+            .filter(|workspace| {
+                let paths = self.artifact_type.local_paths();
+                (!recorded.exists() || paths.iter().any(|path| *path != "."))
+                    && paths
+                        .iter()
+                        .all(|path| workspace.join(literal_prefix(path)).exists())
+            })
+            .unwrap_or(&recorded);
+
+        for path in resolved.artifact_type.local_paths_mut() {
+            *path = if path == "." {
+                root.to_string_lossy().to_string()
+            } else {
+                root.join(path.as_str()).to_string_lossy().to_string()
+            };
+        }
+        resolved
+    }
+
+    /// Point workspace-relative paths at `new_root`. Absolute paths are made relative to `old_root` first.
+    pub fn rebase_root(&mut self, new_root: &Path, old_root: Option<&Path>) -> bool {
+        if self.metadata.workspace_root.is_none() {
+            match old_root {
+                Some(old_root) if self.relativize(old_root) => {}
+                _ => return false,
+            }
+        }
+        self.metadata.workspace_root = Some(new_root.to_string_lossy().to_string());
+        true
+    }
+}
+
+/// Fingerprints of the lines just outside a line range
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RangeAnchor {
+    /// Lines before the range, in file order
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub before: Vec<String>,
+    /// Lines after the range, in file order
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub after: Vec<String>,
+}
+
+/// Path components before the first glob metacharacter
+fn literal_prefix(path: &str) -> PathBuf {
+    Path::new(path)
+        .components()
+        .take_while(|c| {
+            !c.as_os_str()
+                .to_string_lossy()
+                .contains(['*', '?', '[', '{'])
+        })
+        .collect()
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -43,23 +135,72 @@ impl Artifact {
 pub enum ArtifactType {
     File {
         path: String,
+        /// Only the first or last lines, for logs too big to include whole
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        window: Option<LineWindow>,
+        /// None uses the configured default
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        encoding: Option<TextEncoding>,
     },
     FileRange {
         path: String,
         start: usize,
         end: usize,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        encoding: Option<TextEncoding>,
+        /// Lines around the range at add time, to find it again after edits
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        anchor: Option<RangeAnchor>,
     },
     Markdown {
         path: String,
     },
-    CollectionMdDir {
+    Pdf {
+        path: String,
+    },
+    Docx {
+        path: String,
+    },
+    Notebook {
+        path: String,
+        #[serde(default)]
+        strip_outputs: bool,
+    },
+    Command {
+        command: String,
+        /// Directory the command was added from
+        cwd: String,
+    },
+    Symbol {
+        path: String,
+        /// `::`-separated item path, e.g. `AuthService::login`
+        symbol: String,
+    },
+    /// Files in a directory (`dir:`, plus the `md_dir:` and `docs_dir:` aliases).
+    /// `collection_md_dir` rows are rewritten to this by storage migration 2.
+    #[serde(alias = "collection_md_dir")]
+    CollectionDir {
         path: String,
         #[serde(skip_serializing_if = "Option::is_none")]
         max_files: Option<usize>,
+        /// Stop adding files once their total size would exceed this
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        max_bytes: Option<u64>,
+        /// Glob patterns matched against the path relative to `path` and its parent directories
         #[serde(default)]
         exclude: Vec<String>,
         #[serde(default)]
         recursive: bool,
+        /// File extensions to collect (with `include` empty too: markdown only)
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        extensions: Vec<String>,
+        /// Glob patterns matched against the relative path or file name
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        include: Vec<String>,
+        #[serde(default = "default_true")]
+        gitignore: bool,
+        #[serde(default)]
+        sort: DirSort,
     },
     CollectionGlob {
         pattern: String,
@@ -67,6 +208,29 @@ pub enum ArtifactType {
     Text {
         content: String,
     },
+    /// Content from an external plugin; `data` is whatever the plugin returned from parse
+    Plugin {
+        plugin: String,
+        #[serde(default, skip_serializing_if = "serde_json::Value::is_null")]
+        data: serde_json::Value,
+    },
+    /// Gitignore-aware directory listing, regenerated on every render
+    Tree {
+        path: String,
+        max_depth: usize,
+        #[serde(default)]
+        sizes: bool,
+        #[serde(default)]
+        tokens: bool,
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        include: Vec<String>,
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        exclude: Vec<String>,
+    },
+    /// Text captured once at add time (stdin, clipboard); content lives only in the blob store
+    Snippet {
+        title: String,
+    },
     GitDiff {
         base: String,
         #[serde(skip_serializing_if = "Option::is_none")]
@@ -77,6 +241,85 @@ pub enum ArtifactType {
         #[serde(skip_serializing_if = "Option::is_none")]
         title: Option<String>,
     },
+    Crawl {
+        url: String,
+        max_depth: usize,
+        max_pages: usize,
+        #[serde(default)]
+        include: Vec<String>,
+        #[serde(default)]
+        exclude: Vec<String>,
+    },
+    /// Issue or pull/merge request with its comments, fetched on render
+    Issue {
+        provider: IssueProvider,
+        /// `owner/repo` on GitHub, `group/project` on GitLab
+        repo: String,
+        number: u64,
+        #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+        pull_request: bool,
+    },
+}
+
+impl ArtifactType {
+    /// Path of the local file backing this artifact, if any
+    pub fn file_path(&self) -> Option<&str> {
+        match self {
+            ArtifactType::File { path, .. }
+            | ArtifactType::FileRange { path, .. }
+            | ArtifactType::Markdown { path }
+            | ArtifactType::Pdf { path }
+            | ArtifactType::Docx { path }
+            | ArtifactType::Notebook { path, .. }
+            | ArtifactType::Symbol { path, .. } => Some(path),
+            _ => None,
+        }
+    }
+
+    /// Local filesystem paths (files, directories, globs, working directories) this artifact reads
+    pub fn local_paths(&self) -> Vec<&str> {
+        match self {
+            ArtifactType::File { path, .. }
+            | ArtifactType::FileRange { path, .. }
+            | ArtifactType::Markdown { path }
+            | ArtifactType::Pdf { path }
+            | ArtifactType::Docx { path }
+            | ArtifactType::Notebook { path, .. }
+            | ArtifactType::Symbol { path, .. }
+            | ArtifactType::CollectionDir { path, .. }
+            | ArtifactType::Tree { path, .. } => vec![path],
+            ArtifactType::CollectionGlob { pattern } => vec![pattern],
+            ArtifactType::Command { cwd, .. } => vec![cwd],
+            _ => Vec::new(),
+        }
+    }
+
+    pub fn local_paths_mut(&mut self) -> Vec<&mut String> {
+        match self {
+            ArtifactType::File { path, .. }
+            | ArtifactType::FileRange { path, .. }
+            | ArtifactType::Markdown { path }
+            | ArtifactType::Pdf { path }
+            | ArtifactType::Docx { path }
+            | ArtifactType::Notebook { path, .. }
+            | ArtifactType::Symbol { path, .. }
+            | ArtifactType::CollectionDir { path, .. }
+            | ArtifactType::Tree { path, .. } => vec![path],
+            ArtifactType::CollectionGlob { pattern } => vec![pattern],
+            ArtifactType::Command { cwd, .. } => vec![cwd],
+            _ => Vec::new(),
+        }
+    }
+
+    /// Collections have no content of their own and are expanded at render time
+    pub fn is_collection(&self) -> bool {
+        matches!(
+            self,
+            ArtifactType::CollectionDir { .. }
+                | ArtifactType::CollectionGlob { .. }
+                | ArtifactType::Crawl { .. }
+        )
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
@@ -84,6 +327,304 @@ pub struct ArtifactMetadata {
     pub size_bytes: usize,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub mime_type: Option<String>,
+    #[serde(default, skip_serializing_if = "RenderMode::is_full")]
+    pub render_mode: RenderMode,
+    /// Workspace root that the artifact's local paths are relative to; None for absolute paths
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub workspace_root: Option<String>,
+    /// Facts about the source file, for file-backed artifacts
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub file: Option<FileInfo>,
+    /// When generated content (`cmd:`, `tree:`, `issue:`, `crawl:`) was last stored; None means at
+    /// add time
+    #[serde(
+        default,
+        with = "time::serde::timestamp::option",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub captured_at: Option<OffsetDateTime>,
     #[serde(flatten)]
     pub extra: serde_json::Value,
 }
+
+/// Language, size and history of a file, taken at add time and refreshed on render
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FileInfo {
+    /// From the extension, file name or shebang, e.g. `rust`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub language: Option<String>,
+    /// Lines in the artifact's rendered content: the range, window or symbol, not the whole file
+    #[serde(alias = "line_count")]
+    pub rendered_lines: usize,
+    #[serde(
+        default,
+        with = "time::serde::timestamp::option",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub modified: Option<OffsetDateTime>,
+    /// Last commit that touched the file
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub git_commit: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub git_author: Option<String>,
+    /// `HEAD` when the last commit was looked up; it's only looked up again once `HEAD` moves
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub git_head: Option<String>,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+/// Order of files in a directory collection
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DirSort {
+    /// By relative path
+    #[default]
+    Path,
+    /// Most recently modified first
+    Mtime,
+    /// Smallest first, so more files fit in `max_bytes`
+    Size,
+}
+
+impl std::str::FromStr for DirSort {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s {
+            "path" | "name" => Ok(DirSort::Path),
+            "mtime" => Ok(DirSort::Mtime),
+            "size" => Ok(DirSort::Size),
+            _ => Err(format!(
+                "Unknown sort '{}' (expected path, mtime or size)",
+                s
+            )),
+        }
+    }
+}
+
+/// Leading or trailing lines of a file (`?head=N`, `?tail=N`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LineWindow {
+    Head(usize),
+    Tail(usize),
+}
+
+/// How file bytes are decoded to text
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum TextEncoding {
+    /// Strict UTF-8; invalid files are refused
+    #[default]
+    #[serde(rename = "utf-8")]
+    Utf8,
+    /// UTF-8 with invalid sequences replaced by U+FFFD
+    #[serde(rename = "lossy")]
+    Lossy,
+    /// UTF-16 using the byte order mark, little-endian without one
+    #[serde(rename = "utf-16")]
+    Utf16,
+    #[serde(rename = "utf-16le")]
+    Utf16le,
+    #[serde(rename = "utf-16be")]
+    Utf16be,
+    /// ISO-8859-1; every byte is a character
+    #[serde(rename = "latin-1")]
+    Latin1,
+    /// Byte order mark, then UTF-8, then Latin-1
+    #[serde(rename = "auto")]
+    Auto,
+}
+
+impl TextEncoding {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TextEncoding::Utf8 => "utf-8",
+            TextEncoding::Lossy => "lossy",
+            TextEncoding::Utf16 => "utf-16",
+            TextEncoding::Utf16le => "utf-16le",
+            TextEncoding::Utf16be => "utf-16be",
+            TextEncoding::Latin1 => "latin-1",
+            TextEncoding::Auto => "auto",
+        }
+    }
+}
+
+impl std::str::FromStr for TextEncoding {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "utf-8" | "utf8" => Ok(TextEncoding::Utf8),
+            "lossy" => Ok(TextEncoding::Lossy),
+            "utf-16" | "utf16" => Ok(TextEncoding::Utf16),
+            "utf-16le" | "utf16le" => Ok(TextEncoding::Utf16le),
+            "utf-16be" | "utf16be" => Ok(TextEncoding::Utf16be),
+            "latin-1" | "latin1" | "iso-8859-1" => Ok(TextEncoding::Latin1),
+            "auto" => Ok(TextEncoding::Auto),
+            _ => Err(format!(
+                "Unknown encoding '{}' (expected utf-8, lossy, utf-16, utf-16le, utf-16be, latin-1 or auto)",
+                s
+            )),
+        }
+    }
+}
+
+/// Hosted issue tracker behind an `issue:` source
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum IssueProvider {
+    #[default]
+    Github,
+    Gitlab,
+}
+
+impl IssueProvider {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            IssueProvider::Github => "github",
+            IssueProvider::Gitlab => "gitlab",
+        }
+    }
+}
+
+impl std::str::FromStr for IssueProvider {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s {
+            "github" | "gh" => Ok(IssueProvider::Github),
+            "gitlab" | "gl" => Ok(IssueProvider::Gitlab),
+            _ => Err(format!(
+                "Unknown issue provider '{}' (expected github or gitlab)",
+                s
+            )),
+        }
+    }
+}
+
+/// How much of an artifact's content is rendered
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RenderMode {
+    /// Content as-is
+    #[default]
+    Full,
+    /// Declarations, doc comments and signatures with function bodies elided
+    Outline,
+    /// One line per item declaration
+    Signatures,
+}
+
+impl RenderMode {
+    pub fn is_full(&self) -> bool {
+        *self == RenderMode::Full
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RenderMode::Full => "full",
+            RenderMode::Outline => "outline",
+            RenderMode::Signatures => "signatures",
+        }
+    }
+}
+
+impl std::fmt::Display for RenderMode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl std::str::FromStr for RenderMode {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s {
+            "full" => Ok(RenderMode::Full),
+            "outline" => Ok(RenderMode::Outline),
+            "signatures" => Ok(RenderMode::Signatures),
+            _ => Err(format!(
+                "Unknown render mode '{}' (expected full, outline or signatures)",
+                s
+            )),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn file_artifact(path: &Path) -> Artifact {
+        Artifact::new(
+            ArtifactType::File {
+                path: path.to_string_lossy().to_string(),
+                window: None,
+                encoding: None,
+            },
+            "file:src/lib.rs".to_string(),
+        )
+    }
+
+    fn temp_repo(name: &str) -> PathBuf {
+        let root = std::env::temp_dir().join(format!("ctx-{}-{}", name, uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(root.join("src")).unwrap();
+        std::fs::write(root.join("src/lib.rs"), "").unwrap();
+        root
+    }
+
+    #[test]
+    fn test_resolve_paths_prefers_current_workspace() {
+        // Two checkouts holding the same file, e.g. a clone and a worktree
+        let recorded = temp_repo("recorded");
+        let current = temp_repo("current");
+
+        let mut artifact = file_artifact(&recorded.join("src/lib.rs"));
+        assert!(artifact.relativize(&recorded));
+
+        let resolved = artifact.resolve_paths(Some(&current));
+        assert_eq!(
+            resolved.artifact_type.local_paths(),
+            vec![current.join("src/lib.rs").to_string_lossy().as_ref()]
+        );
+
+        // Rendering from a workspace without the file keeps the recorded copy
+        std::fs::remove_file(current.join("src/lib.rs")).unwrap();
+        let resolved = artifact.resolve_paths(Some(&current));
+        assert_eq!(
+            resolved.artifact_type.local_paths(),
+            vec![recorded.join("src/lib.rs").to_string_lossy().as_ref()]
+        );
+
+        std::fs::remove_dir_all(&recorded).unwrap();
+        std::fs::remove_dir_all(&current).unwrap();
+    }
+
+    #[test]
+    fn test_resolve_paths_falls_back_when_recorded_root_is_gone() {
+        let current = temp_repo("moved");
+        let gone = std::env::temp_dir().join(format!("ctx-gone-{}", uuid::Uuid::new_v4()));
+
+        let mut artifact = file_artifact(&gone.join("src/lib.rs"));
+        assert!(artifact.relativize(&gone));
+
+        let resolved = artifact.resolve_paths(Some(&current));
+        assert_eq!(
+            resolved.artifact_type.local_paths(),
+            vec![current.join("src/lib.rs").to_string_lossy().as_ref()]
+        );
+
+        // Files missing from the current workspace too: keep the recorded root
+        let elsewhere = std::env::temp_dir();
+        let resolved = artifact.resolve_paths(Some(&elsewhere));
+        assert_eq!(
+            resolved.artifact_type.local_paths(),
+            vec![gone.join("src/lib.rs").to_string_lossy().as_ref()]
+        );
+
+        std::fs::remove_dir_all(&current).unwrap();
+    }
+}
@@ -1,11 +1,17 @@
+pub mod age;
 pub mod artifact;
 pub mod error;
 pub mod pack;
 pub mod render;
 pub mod security;
 pub mod tokens;
+pub mod workspace;
 
-pub use artifact::{Artifact, ArtifactMetadata, ArtifactType};
+pub use age::format_age;
+pub use artifact::{
+    Artifact, ArtifactMetadata, ArtifactType, DirSort, FileInfo, IssueProvider, LineWindow,
+    RangeAnchor, RenderMode, TextEncoding,
+};
 pub use error::{Error, Result};
 pub use pack::{OrderingStrategy, Pack, RenderPolicy};
 pub use render::{
@@ -14,6 +20,7 @@ pub use render::{
 };
 pub use security::{RedactionInfo, Redactor};
 pub use tokens::TokenEstimator;
+pub use workspace::{find_project_root, find_workspace_root};
 
 #[cfg(test)]
 mod tests {
@@ -33,6 +40,8 @@ mod tests {
         let artifact = Artifact::new(
             ArtifactType::File {
                 path: "/test/file.txt".to_string(),
+                window: None,
+                encoding: None,
             },
             "file:/test/file.txt".to_string(),
         );
@@ -40,4 +49,54 @@ mod tests {
         assert!(!artifact.id.is_empty());
         assert_eq!(artifact.source_uri, "file:/test/file.txt");
     }
+
+    #[test]
+    fn test_workspace_relative_paths() {
+        let root = std::path::Path::new("/work/repo");
+        let mut glob = Artifact::new(
+            ArtifactType::CollectionGlob {
+                pattern: "/work/repo/src/**/*.rs".to_string(),
+            },
+            "glob:src/**/*.rs".to_string(),
+        );
+        assert!(glob.relativize(root));
+        assert!(
+            matches!(&glob.artifact_type, ArtifactType::CollectionGlob { pattern } if pattern == "src/**/*.rs")
+        );
+        assert_eq!(glob.metadata.workspace_root.as_deref(), Some("/work/repo"));
+
+        // Nothing under the workspace here, so the recorded root wins
+        let resolved = glob.resolve_paths(Some(std::path::Path::new("/elsewhere")));
+        assert!(
+            matches!(&resolved.artifact_type, ArtifactType::CollectionGlob { pattern } if pattern == "/work/repo/src/**/*.rs")
+        );
+        assert!(resolved.metadata.workspace_root.is_none());
+
+        let mut tree = Artifact::new(
+            ArtifactType::Tree {
+                path: "/work/repo".to_string(),
+                max_depth: 3,
+                sizes: false,
+                tokens: false,
+                include: Vec::new(),
+                exclude: Vec::new(),
+            },
+            "tree:.".to_string(),
+        );
+        assert!(!tree.relativize(std::path::Path::new("/other")));
+        assert!(tree.rebase_root(std::path::Path::new("/workspaces/repo"), Some(root)));
+        let resolved = tree.resolve_paths(None);
+        assert_eq!(
+            resolved.artifact_type.local_paths(),
+            vec!["/workspaces/repo"]
+        );
+
+        let mut text = Artifact::new(
+            ArtifactType::Text {
+                content: "notes".to_string(),
+            },
+            "text:notes".to_string(),
+        );
+        assert!(!text.rebase_root(root, Some(root)));
+    }
 }
@@ -29,6 +29,9 @@ impl Pack {
 pub struct RenderPolicy {
     pub budget_tokens: usize,
     pub ordering: OrderingStrategy,
+    /// Prefix file lines with their line numbers in the source
+    #[serde(default)]
+    pub line_numbers: bool,
 }
 
 impl Default for RenderPolicy {
@@ -36,6 +39,7 @@ impl Default for RenderPolicy {
         Self {
             budget_tokens: 128000, // Default to 128k tokens
             ordering: OrderingStrategy::PriorityThenTime,
+            line_numbers: false,
         }
     }
 }
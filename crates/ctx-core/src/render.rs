@@ -1,10 +1,12 @@
-use crate::{Artifact, Result};
+use crate::{Artifact, RenderMode, Result};
 use serde::{Deserialize, Serialize};
 
 /// Request to render packs into a payload
 #[derive(Debug, Clone)]
 pub struct RenderRequest {
     pub pack_ids: Vec<String>,
+    /// Overrides each pack's `line_numbers` policy
+    pub line_numbers: Option<bool>,
 }
 
 /// Result of rendering
@@ -25,6 +27,8 @@ pub struct ArtifactSummary {
     pub artifact_id: String,
     pub source_uri: String,
     pub token_estimate: usize,
+    #[serde(default, skip_serializing_if = "RenderMode::is_full")]
+    pub render_mode: RenderMode,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -48,6 +52,16 @@ pub struct ProcessedArtifact {
     pub content: String,
     pub token_count: usize,
     pub redacted: bool,
+    /// Mode `content` was rendered in
+    pub render_mode: RenderMode,
+    /// Reduced rendering used when the full content doesn't fit the budget
+    pub outline: Option<OutlineFallback>,
+}
+
+#[derive(Debug, Clone)]
+pub struct OutlineFallback {
+    pub content: String,
+    pub token_count: usize,
 }
 
 impl ProcessedArtifact {
@@ -56,6 +70,7 @@ impl ProcessedArtifact {
             artifact_id: self.artifact.id.clone(),
             source_uri: self.artifact.source_uri.clone(),
             token_estimate: self.token_count,
+            render_mode: self.render_mode,
         }
     }
 
@@ -68,6 +83,18 @@ impl ProcessedArtifact {
     }
 }
 
+/// Code fence tag for an artifact; prose isn't fenced
+fn fence_language(artifact: &Artifact) -> Option<&str> {
+    let language = artifact.metadata.file.as_ref()?.language.as_deref()?;
+    (language != "markdown").then_some(language)
+}
+
+/// Backtick fence longer than any backtick run in `content`
+fn fence_for(content: &str) -> String {
+    let longest = content.split(|c| c != '`').map(str::len).max().unwrap_or(0);
+    "`".repeat(longest.max(2) + 1)
+}
+
 /// Simple deterministic render engine
 pub struct RenderEngine;
 
@@ -116,7 +143,8 @@ impl RenderEngine {
         })
     }
 
-    /// Apply budget: include artifacts until budget is reached
+    /// Apply budget: include artifacts until budget is reached, falling back to
+    /// an artifact's outline when the full content doesn't fit
     fn apply_budget(
         &self,
         artifacts: Vec<ProcessedArtifact>,
@@ -126,12 +154,22 @@ impl RenderEngine {
         let mut excluded = Vec::new();
         let mut total_tokens = 0;
 
-        for artifact in artifacts {
+        for mut artifact in artifacts {
             if total_tokens + artifact.token_count <= budget {
                 total_tokens += artifact.token_count;
                 included.push(artifact);
-            } else {
-                excluded.push((artifact, "over_budget".to_string()));
+                continue;
+            }
+
+            match artifact.outline.take() {
+                Some(outline) if total_tokens + outline.token_count <= budget => {
+                    total_tokens += outline.token_count;
+                    artifact.content = outline.content;
+                    artifact.token_count = outline.token_count;
+                    artifact.render_mode = RenderMode::Outline;
+                    included.push(artifact);
+                }
+                _ => excluded.push((artifact, "over_budget".to_string())),
             }
         }
 
@@ -144,11 +182,28 @@ impl RenderEngine {
 
         for artifact in artifacts {
             // Add header with source info
-            payload.push_str(&format!("\n--- {} ---\n", artifact.artifact.source_uri));
+            if artifact.render_mode.is_full() {
+                payload.push_str(&format!("\n--- {} ---\n", artifact.artifact.source_uri));
+            } else {
+                payload.push_str(&format!(
+                    "\n--- {} ({}) ---\n",
+                    artifact.artifact.source_uri, artifact.render_mode
+                ));
+            }
 
-            // Add content
-            payload.push_str(&artifact.content);
-            payload.push('\n');
+            // Add content, fenced and tagged when it's code
+            match fence_language(&artifact.artifact) {
+                Some(language) => {
+                    let fence = fence_for(&artifact.content);
+                    payload.push_str(&format!("{}{}\n", fence, language));
+                    payload.push_str(artifact.content.trim_end_matches('\n'));
+                    payload.push_str(&format!("\n{}\n", fence));
+                }
+                None => {
+                    payload.push_str(&artifact.content);
+                    payload.push('\n');
+                }
+            }
         }
 
         payload
@@ -164,6 +219,9 @@ impl RenderEngine {
             if let Some(hash) = &artifact.artifact.content_hash {
                 hasher.update(hash.as_bytes());
             }
+            if !artifact.render_mode.is_full() {
+                hasher.update(artifact.render_mode.as_str().as_bytes());
+            }
         }
 
         hasher.finalize().to_hex().to_string()
@@ -221,6 +279,8 @@ mod tests {
             content: content.to_string(),
             token_count: tokens,
             redacted: false,
+            render_mode: RenderMode::Full,
+            outline: None,
         }
     }
 
@@ -240,6 +300,65 @@ mod tests {
         assert_eq!(excluded.len(), 1);
     }
 
+    #[test]
+    fn test_budget_outline_fallback() {
+        let engine = RenderEngine::new();
+
+        let mut big = create_test_artifact("big", "fn a() { body }", 200);
+        big.outline = Some(OutlineFallback {
+            content: "fn a() { ... }".to_string(),
+            token_count: 40,
+        });
+        let mut too_big = create_test_artifact("too_big", "fn b() { body }", 200);
+        too_big.outline = Some(OutlineFallback {
+            content: "fn b() { ... }".to_string(),
+            token_count: 100,
+        });
+
+        let artifacts = vec![create_test_artifact("a", "content a", 100), big, too_big];
+
+        let result = engine.render(artifacts, 200, vec![], vec![]).unwrap();
+
+        assert_eq!(result.included.len(), 2);
+        assert_eq!(result.included[1].render_mode, RenderMode::Outline);
+        assert_eq!(result.token_estimate, 140);
+        assert_eq!(result.excluded.len(), 1);
+        assert_eq!(result.excluded[0].artifact_id, "too_big");
+
+        let payload = result.payload.unwrap();
+        assert!(payload.contains("--- text:big (outline) ---\nfn a() { ... }"));
+    }
+
+    #[test]
+    fn test_code_fences_use_language() {
+        let engine = RenderEngine::new();
+
+        let with_language = |id: &str, content: &str, language: &str| {
+            let mut processed = create_test_artifact(id, content, 10);
+            processed.artifact.metadata.file = Some(crate::FileInfo {
+                language: Some(language.to_string()),
+                ..Default::default()
+            });
+            processed
+        };
+        let artifacts = vec![
+            with_language("code", "fn main() {}\n", "rust"),
+            with_language("doc", "/// ```\n/// ok\n/// ```", "rust"),
+            with_language("readme", "# Title", "markdown"),
+            create_test_artifact("note", "plain", 10),
+        ];
+
+        let payload = engine
+            .render(artifacts, 1000, vec![], vec![])
+            .unwrap()
+            .payload
+            .unwrap();
+        assert!(payload.contains("--- text:code ---\n```rust\nfn main() {}\n```\n"));
+        assert!(payload.contains("````rust\n/// ```\n/// ok\n/// ```\n````\n"));
+        assert!(payload.contains("--- text:readme ---\n# Title\n"));
+        assert!(payload.contains("--- text:note ---\nplain\n"));
+    }
+
     #[test]
     fn test_render_determinism() {
         let engine = RenderEngine::new();
@@ -0,0 +1,91 @@
+use std::path::{Path, PathBuf};
+
+/// Nearest directory at or above `start` containing `ctx.toml` or `.git`
+pub fn find_workspace_root(start: &Path) -> Option<PathBuf> {
+    let mut current = if start.is_file() {
+        start.parent()?.to_path_buf()
+    } else {
+        start.to_path_buf()
+    };
+
+    loop {
+        if current.join("ctx.toml").exists() || current.join(".git").exists() {
+            return Some(current);
+        }
+        if !current.pop() {
+            return None;
+        }
+    }
+}
+
+/// Directory to index for suggestions about `start`: its [`find_workspace_root`], else the
+/// Cargo or npm workspace containing it, else the nearest directory with a `Cargo.toml` or
+/// `package.json`, else `start`'s own directory
+pub fn find_project_root(start: &Path) -> PathBuf {
+    if let Some(root) = find_workspace_root(start) {
+        return root;
+    }
+
+    let dir = if start.is_file() {
+        start.parent().unwrap_or(start)
+    } else {
+        start
+    };
+    let mut nearest = None;
+    for current in dir.ancestors() {
+        let cargo = std::fs::read_to_string(current.join("Cargo.toml")).ok();
+        let npm = std::fs::read_to_string(current.join("package.json")).ok();
+        if cargo.as_deref().is_some_and(is_cargo_workspace)
+            || npm.as_deref().is_some_and(is_npm_workspace)
+        {
+            return current.to_path_buf();
+        }
+        if nearest.is_none() && (cargo.is_some() || npm.is_some()) {
+            nearest = Some(current);
+        }
+    }
+    nearest.unwrap_or(dir).to_path_buf()
+}
+
+fn is_cargo_workspace(manifest: &str) -> bool {
+    manifest.lines().any(|line| line.trim() == "[workspace]")
+}
+
+fn is_npm_workspace(manifest: &str) -> bool {
+    serde_json::from_str::<serde_json::Value>(manifest)
+        .is_ok_and(|package| package.get("workspaces").is_some())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_find_project_root_uses_cargo_workspace() {
+        let root = std::env::temp_dir().join(format!("ctx-project-{}", uuid::Uuid::new_v4()));
+        let member = root.join("crates/app");
+        std::fs::create_dir_all(member.join("src")).unwrap();
+        std::fs::write(
+            root.join("Cargo.toml"),
+            "[workspace]\nmembers = [\"crates/*\"]\n",
+        )
+        .unwrap();
+        std::fs::write(member.join("Cargo.toml"), "[package]\nname = \"app\"\n").unwrap();
+        std::fs::write(member.join("src/main.rs"), "").unwrap();
+
+        assert_eq!(find_project_root(&member.join("src/main.rs")), root);
+
+        // A lone package is its own root
+        std::fs::write(root.join("Cargo.toml"), "[package]\nname = \"top\"\n").unwrap();
+        assert_eq!(find_project_root(&member.join("src/main.rs")), member);
+
+        // ctx.toml wins over manifests
+        std::fs::write(member.join("src/ctx.toml"), "").unwrap();
+        assert_eq!(
+            find_project_root(&member.join("src/main.rs")),
+            member.join("src")
+        );
+
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+}
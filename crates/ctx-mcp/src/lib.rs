@@ -60,6 +60,7 @@ mod tests {
         assert!(tool_names.contains(&"ctx_packs_list"));
         assert!(tool_names.contains(&"ctx_packs_get"));
         assert!(tool_names.contains(&"ctx_packs_preview"));
+        assert!(tool_names.contains(&"ctx_packs_suggest"));
     }
 
     #[tokio::test]
@@ -70,6 +71,8 @@ mod tests {
             db: storage.clone(),
             renderer,
             read_only: true,
+            sources: Default::default(),
+            suggest: Default::default(),
         });
 
         // Create a test pack
@@ -99,6 +102,8 @@ mod tests {
             db: storage.clone(),
             renderer,
             read_only: true,
+            sources: Default::default(),
+            suggest: Default::default(),
         });
 
         // Create a test pack
@@ -132,6 +137,8 @@ mod tests {
             db: storage.clone(),
             renderer,
             read_only: true,
+            sources: Default::default(),
+            suggest: Default::default(),
         });
 
         // Call get_pack with nonexistent pack
@@ -146,6 +153,244 @@ mod tests {
         assert!(result.is_err());
     }
 
+    #[tokio::test]
+    async fn test_call_tool_suggest_without_files() {
+        let storage = Arc::new(create_test_storage().await);
+        let renderer = Arc::new(ctx_engine::Renderer::new((*storage).clone()));
+        let server = Arc::new(McpServer {
+            db: storage.clone(),
+            renderer,
+            read_only: true,
+            sources: Default::default(),
+            suggest: Default::default(),
+        });
+
+        let pack = Pack::new("notes".to_string(), RenderPolicy::default());
+        storage.create_pack(&pack).await.unwrap();
+
+        // A pack of text artifacts has nothing to relate files to
+        let params = serde_json::json!({
+            "name": "ctx_packs_suggest",
+            "arguments": {
+                "pack": "notes"
+            }
+        });
+
+        let result = call_tool(&server, &params).await.unwrap();
+        let text = result["content"][0]["text"].as_str().unwrap();
+        let response: serde_json::Value = serde_json::from_str(text).unwrap();
+        assert!(response["suggestions"].as_array().unwrap().is_empty());
+    }
+
+    /// Server with a pack `code` of `src/main.rs` and the `src/util/*.rs` collection, in a git
+    /// workspace where `main.rs` also imports `src/auth.rs`
+    async fn suggest_workspace() -> (std::path::PathBuf, McpServer) {
+        let dir = std::env::temp_dir().join(format!("ctx-mcp-suggest-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(dir.join("src/util")).unwrap();
+        let dir = dir.canonicalize().unwrap();
+        let files = [
+            ("src/main.rs", "mod auth;\nmod util;\n\nfn main() {\n    auth::login();\n    util::fmt::show();\n}\n"),
+            ("src/auth.rs", "pub fn login() {}\n"),
+            ("src/util/mod.rs", "pub mod fmt;\n"),
+            ("src/util/fmt.rs", "pub fn show() {}\n"),
+        ];
+        for (file, content) in files {
+            std::fs::write(dir.join(file), content).unwrap();
+        }
+        for args in [
+            &["init", "-q"][..],
+            &["add", "."],
+            &[
+                "-c",
+                "user.name=Ada",
+                "-c",
+                "user.email=ada@example.com",
+                "commit",
+                "-qm",
+                "Start",
+            ],
+        ] {
+            let output = std::process::Command::new("git")
+                .args(args)
+                .current_dir(&dir)
+                .output()
+                .unwrap();
+            assert!(output.status.success(), "git {:?} failed", args);
+        }
+
+        let storage = Arc::new(create_test_storage().await);
+        let pack = Pack::new("code".to_string(), RenderPolicy::default());
+        storage.create_pack(&pack).await.unwrap();
+        let registry = ctx_sources::SourceHandlerRegistry::new();
+        for uri in [
+            format!("file:{}", dir.join("src/main.rs").display()),
+            format!("glob:{}/src/util/*.rs", dir.display()),
+        ] {
+            let artifact = registry.parse(&uri, Default::default()).await.unwrap();
+            storage.create_artifact(&artifact).await.unwrap();
+            storage
+                .add_artifact_to_pack(&pack.id, &artifact.id, 0)
+                .await
+                .unwrap();
+        }
+
+        let renderer = Arc::new(ctx_engine::Renderer::new((*storage).clone()));
+        let server = McpServer {
+            db: storage,
+            renderer,
+            read_only: true,
+            sources: Default::default(),
+            suggest: ctx_suggest::SuggestConfig {
+                index_dir: Some(dir.join(".index")),
+                ..Default::default()
+            },
+        };
+        (dir, server)
+    }
+
+    fn suggested_paths(response: &serde_json::Value) -> Vec<&str> {
+        response["suggestions"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|suggestion| suggestion["path"].as_str().unwrap())
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn test_call_tool_suggest_for_pack() {
+        let (dir, server) = suggest_workspace().await;
+
+        let params = serde_json::json!({
+            "name": "ctx_packs_suggest",
+            "arguments": {
+                "pack": "code"
+            }
+        });
+        let result = call_tool(&server, &params).await.unwrap();
+        let text = result["content"][0]["text"].as_str().unwrap();
+        let response: serde_json::Value = serde_json::from_str(text).unwrap();
+
+        // Files of the glob are pack files too, so they aren't suggested
+        let path = |file: &str| dir.join(file).to_string_lossy().to_string();
+        assert_eq!(
+            response["files"],
+            serde_json::json!([
+                path("src/main.rs"),
+                path("src/util/fmt.rs"),
+                path("src/util/mod.rs")
+            ])
+        );
+        assert_eq!(suggested_paths(&response), [path("src/auth.rs")]);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_api_suggest_for_pack() {
+        use axum::body::Body;
+        use axum::http::{Request, StatusCode};
+        use tower::ServiceExt;
+
+        let (dir, server) = suggest_workspace().await;
+        let empty = Pack::new("empty".to_string(), RenderPolicy::default());
+        server.db.create_pack(&empty).await.unwrap();
+        let app = server::router(Arc::new(server));
+        let get = |uri: String| {
+            let app = app.clone();
+            async move {
+                let request = Request::builder().uri(uri).body(Body::empty()).unwrap();
+                let response = app.oneshot(request).await.unwrap();
+                let status = response.status();
+                let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+                    .await
+                    .unwrap();
+                (status, String::from_utf8(body.to_vec()).unwrap())
+            }
+        };
+        let auth = dir.join("src/auth.rs").to_string_lossy().to_string();
+
+        let (status, body) = get("/api/suggest?pack=code".to_string()).await;
+        assert_eq!(status, StatusCode::OK);
+        let response: serde_json::Value = serde_json::from_str(&body).unwrap();
+        assert_eq!(suggested_paths(&response), [auth.as_str()]);
+
+        // For one file, the pack's files (its collection's included) are left out
+        let main = dir.join("src/main.rs");
+        let (status, body) = get(format!("/api/suggest?file={}&pack=code", main.display())).await;
+        assert_eq!(status, StatusCode::OK);
+        let response: serde_json::Value = serde_json::from_str(&body).unwrap();
+        assert_eq!(suggested_paths(&response), [auth.as_str()]);
+
+        // A pack without files has nothing to suggest
+        let (status, body) = get("/api/suggest?pack=empty".to_string()).await;
+        assert_eq!(status, StatusCode::OK);
+        let response: serde_json::Value = serde_json::from_str(&body).unwrap();
+        assert!(response["suggestions"].as_array().unwrap().is_empty());
+
+        let (status, body) = get("/api/suggest?pack=missing".to_string()).await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+        assert!(body.contains("Pack not found: missing"));
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_api_add_command_artifact() {
+        use axum::body::Body;
+        use axum::http::{Request, StatusCode};
+        use tower::ServiceExt;
+
+        let storage = Arc::new(create_test_storage().await);
+        let pack = Pack::new("cmds".to_string(), RenderPolicy::default());
+        storage.create_pack(&pack).await.unwrap();
+        let server = |command_policy| McpServer {
+            db: storage.clone(),
+            renderer: Arc::new(ctx_engine::Renderer::new((*storage).clone())),
+            read_only: false,
+            sources: ctx_sources::SourceSettings {
+                command_policy,
+                ..Default::default()
+            },
+            suggest: Default::default(),
+        };
+        let post = |server: McpServer| async move {
+            let request = Request::builder()
+                .method("POST")
+                .uri("/api/packs/cmds/artifacts")
+                .header("content-type", "application/json")
+                .body(Body::from(
+                    r#"{"type": "command", "command": "ls", "cwd": "/etc"}"#,
+                ))
+                .unwrap();
+            server::router(Arc::new(server))
+                .oneshot(request)
+                .await
+                .unwrap()
+                .status()
+        };
+
+        // Without a policy (no `allow_mcp`), nothing is added
+        assert_eq!(post(server(None)).await, StatusCode::FORBIDDEN);
+
+        let policy = ctx_sources::CommandPolicy {
+            working_dir: Some(std::env::temp_dir()),
+            timeout: std::time::Duration::from_secs(5),
+            max_output_bytes: 1024,
+            env_allowlist: vec!["PATH".to_string()],
+        };
+        assert_eq!(post(server(Some(policy))).await, StatusCode::CREATED);
+
+        // The client's cwd is replaced by the configured one
+        let artifacts = storage.get_pack_artifacts(&pack.id).await.unwrap();
+        match &artifacts[0].artifact.artifact_type {
+            ArtifactType::Command { cwd, .. } => {
+                assert_eq!(cwd, &std::env::temp_dir().to_string_lossy())
+            }
+            other => panic!("unexpected artifact {:?}", other),
+        }
+    }
+
     #[tokio::test]
     async fn test_call_tool_preview() {
         let storage = Arc::new(create_test_storage().await);
@@ -154,6 +399,8 @@ mod tests {
             db: storage.clone(),
             renderer,
             read_only: true,
+            sources: Default::default(),
+            suggest: Default::default(),
         });
 
         // Create a pack with artifact
@@ -199,6 +446,8 @@ mod tests {
             db: storage.clone(),
             renderer,
             read_only: true,
+            sources: Default::default(),
+            suggest: Default::default(),
         });
 
         // Call unknown tool
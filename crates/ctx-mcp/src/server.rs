@@ -1,6 +1,6 @@
 use axum::{
     extract::{Path, Query, State},
-    http::StatusCode,
+    http::{Method, StatusCode},
     response::{IntoResponse, Response},
     routing::{delete, get, post},
     Json, Router,
@@ -17,10 +17,11 @@ use tracing::info;
 
 use ctx_core::RenderRequest;
 use ctx_engine::Renderer;
+use ctx_sources::{SourceHandlerRegistry, SourceSettings};
 use ctx_storage::Storage;
 
 use crate::protocol::{JsonRpcRequest, JsonRpcResponse};
-use crate::tools::handle_jsonrpc;
+use crate::tools::{handle_jsonrpc, suggest_for_pack};
 
 // Request body structs for REST API
 #[derive(Deserialize)]
@@ -28,6 +29,16 @@ struct CreatePackRequest {
     name: String,
     #[serde(default)]
     budget_tokens: Option<usize>,
+    #[serde(default)]
+    line_numbers: bool,
+}
+
+/// Query parameters for rendering
+#[derive(Deserialize)]
+struct RenderParams {
+    /// Overrides the pack's line_numbers policy
+    #[serde(default)]
+    line_numbers: Option<bool>,
 }
 
 #[derive(Deserialize)]
@@ -42,6 +53,10 @@ pub struct McpServer {
     pub db: Arc<Storage>,
     pub renderer: Arc<Renderer>,
     pub read_only: bool,
+    /// Source handler settings; `cmd:` is refused when its policy is None
+    pub sources: SourceSettings,
+    /// Settings every suggestion engine starts from
+    pub suggest: SuggestConfig,
 }
 
 #[derive(Clone)]
@@ -56,53 +71,26 @@ impl McpServer {
         host: &str,
         port: u16,
         read_only: bool,
+        mut sources: SourceSettings,
+        suggest: SuggestConfig,
     ) -> anyhow::Result<()> {
-        let renderer = Arc::new(Renderer::new((*db).clone()));
+        // Read-only servers run nothing on this machine
+        if read_only {
+            sources.command_policy = None;
+            sources.plugins.clear();
+        }
+        let registry = SourceHandlerRegistry::with_settings(&sources);
+        let renderer = Arc::new(Renderer::with_sources((*db).clone(), registry));
 
         let server = Arc::new(Self {
             db,
             renderer,
             read_only,
+            sources,
+            suggest,
         });
 
-        let app_state = AppState {
-            server,
-            suggestion_engine: Arc::new(RwLock::new(None)),
-        };
-
-        // Add CORS layer to allow connections from any origin
-        let cors = CorsLayer::new()
-            .allow_origin(Any)
-            .allow_methods(Any)
-            .allow_headers(Any);
-
-        let app = Router::new()
-            // MCP endpoints
-            .route("/", get(handle_info))
-            .route("/", post(handle_mcp_post))
-            .route("/mcp", get(handle_info))
-            .route("/mcp", post(handle_mcp_post))
-            .route("/sse", get(handle_info))
-            .route("/sse", post(handle_mcp_post))
-            // REST API endpoints (for ChatGPT Actions, Gemini, etc.)
-            .route("/api/packs", get(api_list_packs).post(api_create_pack))
-            .route(
-                "/api/packs/:name",
-                get(api_get_pack).delete(api_delete_pack),
-            )
-            .route("/api/packs/:name/render", get(api_render_pack))
-            .route(
-                "/api/packs/:name/artifacts",
-                get(api_list_pack_artifacts).post(api_add_artifact),
-            )
-            .route(
-                "/api/packs/:name/artifacts/:artifact_id",
-                delete(api_remove_artifact),
-            )
-            // Suggestion endpoint
-            .route("/api/suggest", get(api_suggest))
-            .layer(cors)
-            .with_state(app_state);
+        let app = router(server);
 
         let addr = format!("{}:{}", host, port);
         let listener = TcpListener::bind(&addr).await?;
@@ -115,6 +103,48 @@ impl McpServer {
     }
 }
 
+/// Routes of the HTTP server: MCP over POST and the REST API
+pub(crate) fn router(server: Arc<McpServer>) -> Router {
+    let app_state = AppState {
+        server,
+        suggestion_engine: Arc::new(RwLock::new(None)),
+    };
+
+    // Any origin may read; browsers can't send the JSON POSTs and DELETEs that change packs
+    let cors = CorsLayer::new()
+        .allow_origin(Any)
+        .allow_methods([Method::GET])
+        .allow_headers(Any);
+
+    Router::new()
+        // MCP endpoints
+        .route("/", get(handle_info))
+        .route("/", post(handle_mcp_post))
+        .route("/mcp", get(handle_info))
+        .route("/mcp", post(handle_mcp_post))
+        .route("/sse", get(handle_info))
+        .route("/sse", post(handle_mcp_post))
+        // REST API endpoints (for ChatGPT Actions, Gemini, etc.)
+        .route("/api/packs", get(api_list_packs).post(api_create_pack))
+        .route(
+            "/api/packs/:name",
+            get(api_get_pack).delete(api_delete_pack),
+        )
+        .route("/api/packs/:name/render", get(api_render_pack))
+        .route(
+            "/api/packs/:name/artifacts",
+            get(api_list_pack_artifacts).post(api_add_artifact),
+        )
+        .route(
+            "/api/packs/:name/artifacts/:artifact_id",
+            delete(api_remove_artifact),
+        )
+        // Suggestion endpoint
+        .route("/api/suggest", get(api_suggest))
+        .layer(cors)
+        .with_state(app_state)
+}
+
 /// GET handler for server info/health check
 async fn handle_info() -> Json<serde_json::Value> {
     Json(serde_json::json!({
@@ -154,7 +184,11 @@ async fn api_get_pack(State(state): State<AppState>, Path(name): Path<String>) -
 }
 
 /// GET /api/packs/:name/render - Render pack content
-async fn api_render_pack(State(state): State<AppState>, Path(name): Path<String>) -> Response {
+async fn api_render_pack(
+    State(state): State<AppState>,
+    Path(name): Path<String>,
+    Query(params): Query<RenderParams>,
+) -> Response {
     // First get the pack to verify it exists
     let pack = match state.server.db.get_pack(&name).await {
         Ok(p) => p,
@@ -169,6 +203,7 @@ async fn api_render_pack(State(state): State<AppState>, Path(name): Path<String>
         .renderer
         .render_request(RenderRequest {
             pack_ids: vec![pack.id],
+            line_numbers: params.line_numbers,
         })
         .await
     {
@@ -193,6 +228,7 @@ async fn api_create_pack(
 
     let policies = RenderPolicy {
         budget_tokens: req.budget_tokens.unwrap_or(128000),
+        line_numbers: req.line_numbers,
         ..Default::default()
     };
 
@@ -246,12 +282,48 @@ async fn api_delete_pack(State(state): State<AppState>, Path(name): Path<String>
 async fn api_add_artifact(
     State(state): State<AppState>,
     Path(name): Path<String>,
-    Json(req): Json<AddArtifactRequest>,
+    Json(mut req): Json<AddArtifactRequest>,
 ) -> Response {
     if state.server.read_only {
         return (StatusCode::FORBIDDEN, "Server is in read-only mode").into_response();
     }
 
+    if matches!(req.artifact_type, ArtifactType::Command { .. })
+        && state.server.sources.command_policy.is_none()
+    {
+        return (StatusCode::FORBIDDEN, "cmd: sources are disabled").into_response();
+    }
+
+    // Commands run in the configured directory, never one the client picks
+    if let (ArtifactType::Command { cwd, .. }, Some(policy)) =
+        (&mut req.artifact_type, &state.server.sources.command_policy)
+    {
+        let dir = match &policy.working_dir {
+            Some(dir) => dir.clone(),
+            None => match std::env::current_dir() {
+                Ok(dir) => dir,
+                Err(e) => return error_response(e.into()),
+            },
+        };
+        *cwd = dir.to_string_lossy().to_string();
+    }
+
+    if matches!(req.artifact_type, ArtifactType::Snippet { .. }) {
+        return (
+            StatusCode::BAD_REQUEST,
+            "Snippets are captured from stdin or the clipboard with `ctx add`; use type \"text\"",
+        )
+            .into_response();
+    }
+
+    if matches!(req.artifact_type, ArtifactType::Plugin { .. }) {
+        return (
+            StatusCode::BAD_REQUEST,
+            "Plugin sources must be added by URI",
+        )
+            .into_response();
+    }
+
     // First get the pack to get its ID
     let pack = match state.server.db.get_pack(&name).await {
         Ok(p) => p,
@@ -262,18 +334,49 @@ async fn api_add_artifact(
 
     // Create source_uri from artifact type
     let source_uri = match &req.artifact_type {
-        ArtifactType::File { path } => format!("file://{}", path),
-        ArtifactType::FileRange { path, start, end } => {
+        ArtifactType::File { path, .. } => format!("file://{}", path),
+        ArtifactType::FileRange {
+            path, start, end, ..
+        } => {
             format!("file://{}#L{}-L{}", path, start, end)
         }
         ArtifactType::Markdown { path } => format!("md://{}", path),
-        ArtifactType::CollectionMdDir { path, .. } => format!("mddir://{}", path),
+        ArtifactType::Pdf { path } | ArtifactType::Docx { path } => format!("file://{}", path),
+        ArtifactType::Notebook {
+            path,
+            strip_outputs,
+        } => {
+            if *strip_outputs {
+                format!("file://{}?outputs=false", path)
+            } else {
+                format!("file://{}", path)
+            }
+        }
+        ArtifactType::Symbol { path, symbol } => format!("symbol:{}::{}", path, symbol),
+        ArtifactType::CollectionDir { path, .. } => format!("dir:{}", path),
         ArtifactType::CollectionGlob { pattern } => format!("glob://{}", pattern),
         ArtifactType::Text { .. } => "text://inline".to_string(),
+        ArtifactType::Snippet { title } => format!("snippet:{}", title),
         ArtifactType::GitDiff { base, head } => {
             format!("git://diff/{}..{}", base, head.as_deref().unwrap_or("HEAD"))
         }
         ArtifactType::Url { url, .. } => format!("url:{}", url),
+        ArtifactType::Command { command, .. } => format!("cmd:{}", command),
+        ArtifactType::Crawl { url, .. } => format!("crawl:{}", url),
+        ArtifactType::Tree { path, .. } => format!("tree:{}", path),
+        ArtifactType::Plugin { plugin, .. } => format!("{}:", plugin),
+        ArtifactType::Issue {
+            provider,
+            repo,
+            number,
+            pull_request,
+        } => format!(
+            "issue:{}:{}{}{}",
+            provider.as_str(),
+            repo,
+            if *pull_request { '!' } else { '#' },
+            number
+        ),
     };
 
     let artifact = Artifact::new(req.artifact_type.clone(), source_uri);
@@ -363,34 +466,59 @@ async fn api_remove_artifact(
 /// Query parameters for suggestion endpoint
 #[derive(Deserialize)]
 struct SuggestParams {
-    file: String,
+    /// Without a file, suggestions are for the whole pack
+    #[serde(default)]
+    file: Option<String>,
     #[serde(default)]
     pack: Option<String>,
     #[serde(default)]
     max_results: Option<usize>,
 }
 
-/// GET /api/suggest - Get file suggestions
+/// GET /api/suggest - Get file suggestions, for a file or a whole pack
 async fn api_suggest(
     State(state): State<AppState>,
     Query(params): Query<SuggestParams>,
 ) -> Response {
+    let server = state.server.as_ref();
+    let Some(file) = params.file else {
+        let Some(pack) = params.pack else {
+            return (StatusCode::BAD_REQUEST, "Missing file or pack parameter").into_response();
+        };
+        return match suggest_for_pack(server, &pack, params.max_results).await {
+            Ok(response) => Json(response).into_response(),
+            Err(e) => error_response(e),
+        };
+    };
+
     // Canonicalize the file path
-    let file_path = match std::path::Path::new(&params.file).canonicalize() {
+    let file_path = match std::path::Path::new(&file).canonicalize() {
         Ok(p) => p,
         Err(e) => {
             return (StatusCode::BAD_REQUEST, format!("Invalid file path: {}", e)).into_response()
         }
     };
 
+    // Files already in the pack aren't worth suggesting
+    let exclude = match &params.pack {
+        Some(pack) => match server.renderer.pack_files(pack).await {
+            Ok(pack_files) => pack_files.files,
+            Err(e) => return error_response(e),
+        },
+        None => Vec::new(),
+    };
+
     // Find workspace root
-    let workspace = find_workspace_root(&file_path);
+    let workspace = ctx_core::find_project_root(&file_path);
 
-    // Ensure suggestion engine exists
+    // Ensure suggestion engine exists, for this workspace
     {
         let mut engine_guard = state.suggestion_engine.write().await;
-        if engine_guard.is_none() {
-            *engine_guard = Some(SuggestionEngine::new(&workspace, SuggestConfig::default()));
+        if engine_guard
+            .as_ref()
+            .is_none_or(|engine| engine.workspace() != workspace)
+        {
+            *engine_guard = Some(SuggestionEngine::new(&workspace, server.suggest.clone()));
         }
     }
 
@@ -399,6 +527,7 @@ async fn api_suggest(
         file: file_path.to_string_lossy().to_string(),
         pack_name: params.pack,
         max_results: params.max_results,
+        exclude,
     };
 
     // Get suggestions using read lock
@@ -411,20 +540,13 @@ async fn api_suggest(
     }
 }
 
-/// Find workspace root by looking for .git or Cargo.toml
-fn find_workspace_root(file: &std::path::Path) -> std::path::PathBuf {
-    let mut current = if file.is_file() {
-        file.parent().unwrap_or(file).to_owned()
-    } else {
-        file.to_owned()
-    };
-
-    loop {
-        if current.join(".git").exists() || current.join("Cargo.toml").exists() {
-            return current;
-        }
-        if !current.pop() {
-            return file.parent().unwrap_or(file).to_owned();
+/// 404 for unknown packs and artifacts, 500 for anything else
+fn error_response(e: anyhow::Error) -> Response {
+    let status = match e.downcast_ref::<ctx_core::Error>() {
+        Some(ctx_core::Error::PackNotFound(_) | ctx_core::Error::ArtifactNotFound(_)) => {
+            StatusCode::NOT_FOUND
         }
-    }
+        _ => StatusCode::INTERNAL_SERVER_ERROR,
+    };
+    (status, e.to_string()).into_response()
 }
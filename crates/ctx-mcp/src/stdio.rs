@@ -2,18 +2,33 @@ use std::io::{self, BufRead, Write};
 use std::sync::Arc;
 
 use ctx_engine::Renderer;
+use ctx_sources::{SourceHandlerRegistry, SourceSettings};
 use ctx_storage::Storage;
+use ctx_suggest::SuggestConfig;
 
 use crate::protocol::{JsonRpcRequest, JsonRpcResponse};
 use crate::server::McpServer;
 use crate::tools::handle_jsonrpc;
 
-pub async fn run_stdio(db: Arc<Storage>, read_only: bool) -> anyhow::Result<()> {
-    let renderer = Arc::new(Renderer::new((*db).clone()));
+pub async fn run_stdio(
+    db: Arc<Storage>,
+    read_only: bool,
+    mut sources: SourceSettings,
+    suggest: SuggestConfig,
+) -> anyhow::Result<()> {
+    // Read-only servers run nothing on this machine
+    if read_only {
+        sources.command_policy = None;
+        sources.plugins.clear();
+    }
+    let registry = SourceHandlerRegistry::with_settings(&sources);
+    let renderer = Arc::new(Renderer::with_sources((*db).clone(), registry));
     let server = McpServer {
         db,
         renderer,
         read_only,
+        sources,
+        suggest,
     };
 
     let stdin = io::stdin();
@@ -1,7 +1,9 @@
 use crate::protocol::{JsonRpcRequest, JsonRpcResponse};
 use crate::server::McpServer;
-use ctx_core::{OrderingStrategy, Pack, RenderPolicy, RenderRequest};
+use ctx_core::{OrderingStrategy, Pack, RenderMode, RenderPolicy, RenderRequest};
+use ctx_engine::PackFiles;
 use ctx_sources::{SourceHandlerRegistry, SourceOptions};
+use ctx_suggest::{PackSuggestRequest, PackSuggestResponse, SuggestionEngine};
 use serde_json::json;
 
 /// Helper to extract a required string argument
@@ -62,7 +64,10 @@ pub async fn call_tool(
 
             let mut result = server
                 .renderer
-                .render_request(RenderRequest { pack_ids })
+                .render_request(RenderRequest {
+                    pack_ids,
+                    line_numbers: args["line_numbers"].as_bool(),
+                })
                 .await?;
 
             if !show_payload {
@@ -79,12 +84,19 @@ pub async fn call_tool(
                 .renderer
                 .render_request(RenderRequest {
                     pack_ids: vec![pack.id],
+                    line_numbers: args["line_numbers"].as_bool(),
                 })
                 .await?;
 
             // Return the rendered content directly for LLM consumption
             result.payload.unwrap_or_default()
         }
+        "ctx_packs_suggest" => {
+            let max_results = args["max_results"].as_u64().map(|max| max as usize);
+            let response =
+                suggest_for_pack(server, required_str(args, "pack")?, max_results).await?;
+            serde_json::to_string_pretty(&response)?
+        }
         "ctx_packs_create" => {
             if server.read_only {
                 anyhow::bail!("Server is in read-only mode");
@@ -97,6 +109,7 @@ pub async fn call_tool(
                 RenderPolicy {
                     budget_tokens: budget,
                     ordering: OrderingStrategy::PriorityThenTime,
+                    line_numbers: args["line_numbers"].as_bool().unwrap_or(false),
                 },
             );
             server.db.create_pack(&pack).await?;
@@ -113,20 +126,21 @@ pub async fn call_tool(
             let pack_name = required_str(args, "pack")?;
             let source = required_str(args, "source")?;
             let priority = args["priority"].as_i64().unwrap_or(0);
+            let mode: RenderMode = match args["mode"].as_str() {
+                Some(mode) => mode.parse().map_err(|e: String| anyhow::anyhow!(e))?,
+                None => RenderMode::Full,
+            };
 
             let pack = server.db.get_pack(pack_name).await?;
-            let registry = SourceHandlerRegistry::new();
+            let registry = SourceHandlerRegistry::with_settings(&server.sources);
             let options = SourceOptions {
                 priority,
                 ..Default::default()
             };
 
-            let artifact = registry.parse(source, options).await?;
-            let is_collection = matches!(
-                artifact.artifact_type,
-                ctx_core::ArtifactType::CollectionMdDir { .. }
-                    | ctx_core::ArtifactType::CollectionGlob { .. }
-            );
+            let mut artifact = registry.parse(source, options).await?;
+            artifact.metadata.render_mode = mode;
+            let is_collection = artifact.artifact_type.is_collection();
 
             if is_collection {
                 server.db.create_artifact(&artifact).await?;
@@ -195,7 +209,8 @@ pub fn list_tools(read_only: bool) -> serde_json::Value {
                         "items": {"type": "string"},
                         "description": "Pack names or IDs to render"
                     },
-                    "show_payload": {"type": "boolean", "default": false, "description": "Include rendered content"}
+                    "show_payload": {"type": "boolean", "default": false, "description": "Include rendered content"},
+                    "line_numbers": {"type": "boolean", "description": "Prefix file lines with their line numbers (default: each pack's setting)"}
                 },
                 "required": ["packs"]
             }),
@@ -206,7 +221,20 @@ pub fn list_tools(read_only: bool) -> serde_json::Value {
             json!({
                 "type": "object",
                 "properties": {
-                    "pack": {"type": "string", "description": "Pack name or ID to load"}
+                    "pack": {"type": "string", "description": "Pack name or ID to load"},
+                    "line_numbers": {"type": "boolean", "description": "Prefix file lines with their line numbers, e.g. before suggesting edits (default: the pack's setting)"}
+                },
+                "required": ["pack"]
+            }),
+        ),
+        tool_schema(
+            "ctx_packs_suggest",
+            "Suggest files to add to a pack: those related to most of its files (by git history, imports, symbols, tests and vocabulary) for the fewest tokens",
+            json!({
+                "type": "object",
+                "properties": {
+                    "pack": {"type": "string", "description": "Pack name or ID"},
+                    "max_results": {"type": "integer", "description": "Maximum suggestions (default: 10)"}
                 },
                 "required": ["pack"]
             }),
@@ -222,20 +250,22 @@ pub fn list_tools(read_only: bool) -> serde_json::Value {
                     "type": "object",
                     "properties": {
                         "name": {"type": "string", "description": "Pack name"},
-                        "budget": {"type": "integer", "description": "Token budget (default: 128000)"}
+                        "budget": {"type": "integer", "description": "Token budget (default: 128000)"},
+                        "line_numbers": {"type": "boolean", "description": "Prefix file lines with their line numbers when rendering (default: false)"}
                     },
                     "required": ["name"]
                 }),
             ),
             tool_schema(
                 "ctx_packs_add_artifact",
-                "Add artifact to pack. Sources: file:path, glob:pattern, text:content, git:diff",
+                "Add artifact to pack. Sources: file:path, glob:pattern, text:content, git:diff, url:https://..., crawl:https://...",
                 json!({
                     "type": "object",
                     "properties": {
                         "pack": {"type": "string", "description": "Pack name or ID"},
-                        "source": {"type": "string", "description": "Source URI (file:path, glob:src/**/*.rs, text:content, git:diff --base=main)"},
-                        "priority": {"type": "integer", "description": "Priority (higher = included first, default: 0)"}
+                        "source": {"type": "string", "description": "Source URI (file:path, glob:src/**/*.rs, text:content, git:diff --base=main, crawl:https://docs.rs/tokio --depth=2)"},
+                        "priority": {"type": "integer", "description": "Priority (higher = included first, default: 0)"},
+                        "mode": {"type": "string", "enum": ["full", "outline", "signatures"], "description": "Render mode for code files (default: full)"}
                     },
                     "required": ["pack", "source"]
                 }),
@@ -257,6 +287,31 @@ pub fn list_tools(read_only: bool) -> serde_json::Value {
     json!({ "tools": tools })
 }
 
+/// Files to add to a pack: those related to most of its files for the fewest tokens
+pub(crate) async fn suggest_for_pack(
+    server: &McpServer,
+    pack: &str,
+    max_results: Option<usize>,
+) -> anyhow::Result<PackSuggestResponse> {
+    let PackFiles { files, workspace } = server.renderer.pack_files(pack).await?;
+    // Nothing to suggest for is an empty answer, not a failure
+    let Some(workspace) = workspace else {
+        return Ok(PackSuggestResponse {
+            files,
+            suggestions: Vec::new(),
+            elapsed_ms: 0,
+        });
+    };
+    let engine = SuggestionEngine::new(&workspace, server.suggest.clone());
+    engine
+        .suggest_for_pack(&PackSuggestRequest {
+            files,
+            exclude: Vec::new(),
+            max_results,
+        })
+        .await
+}
+
 fn tool_schema(
     name: &str,
     description: &str,
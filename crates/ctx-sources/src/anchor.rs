@@ -0,0 +1,235 @@
+use ctx_core::RangeAnchor;
+
+/// Lines fingerprinted on each side of a range, and at each end of it when matching
+pub const CONTEXT_LINES: usize = 3;
+/// Relocations at or above this are used without a warning
+pub const CONFIDENT: f64 = 0.8;
+/// Below this the match is likely wrong code, so the cached content is used instead
+pub const MIN_CONFIDENCE: f64 = 0.5;
+/// Cells allowed in the LCS table before falling back to comparing lines in place
+const MAX_LCS_CELLS: usize = 4_000_000;
+
+/// Where a range was found in the current file (0-indexed, inclusive)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Relocation {
+    pub start: usize,
+    pub end: usize,
+    /// 1.0 when the range is unchanged
+    pub confidence: f64,
+}
+
+/// Short hash of a line, ignoring indentation and whitespace changes
+pub fn fingerprint(line: &str) -> String {
+    let normalized = line.split_whitespace().collect::<Vec<_>>().join(" ");
+    blake3::hash(normalized.as_bytes()).to_hex()[..16].to_string()
+}
+
+/// Fingerprints of the lines around `start..=end`
+pub fn anchor_range(lines: &[&str], start: usize, end: usize) -> RangeAnchor {
+    let start = start.min(lines.len());
+    let after = (end + 1).min(lines.len());
+    RangeAnchor {
+        before: lines[start.saturating_sub(CONTEXT_LINES)..start]
+            .iter()
+            .map(|l| fingerprint(l))
+            .collect(),
+        after: lines[after..(after + CONTEXT_LINES).min(lines.len())]
+            .iter()
+            .map(|l| fingerprint(l))
+            .collect(),
+    }
+}
+
+/// Find the lines that were `original` at `start..=end` in the file now made of `lines`.
+///
+/// The start is found by matching the context before the range plus its first lines,
+/// the end by its last lines plus the context after, preferring positions nearest the
+/// old ones. Confidence blends those matches with how much of the original body the
+/// new range still contains. Returns None when neither end can be found.
+pub fn relocate(
+    lines: &[&str],
+    original: &str,
+    start: usize,
+    end: usize,
+    anchor: Option<&RangeAnchor>,
+) -> Option<Relocation> {
+    let body: Vec<String> = original.lines().map(fingerprint).collect();
+    if body.is_empty() {
+        return None;
+    }
+    let current: Vec<String> = lines.iter().map(|l| fingerprint(l)).collect();
+
+    if start <= end && end < current.len() && current[start..=end] == body[..] {
+        return Some(Relocation {
+            start,
+            end,
+            confidence: 1.0,
+        });
+    }
+
+    let (before, after) = anchor
+        .map(|a| (a.before.as_slice(), a.after.as_slice()))
+        .unwrap_or_default();
+    let edge = CONTEXT_LINES.min(body.len());
+
+    // (offset from the range's first line, fingerprint)
+    let head: Vec<(isize, &str)> = before
+        .iter()
+        .rev()
+        .enumerate()
+        .map(|(i, f)| (-(i as isize) - 1, f.as_str()))
+        .chain(
+            body[..edge]
+                .iter()
+                .enumerate()
+                .map(|(i, f)| (i as isize, f.as_str())),
+        )
+        .collect();
+    // (offset from the range's last line, fingerprint)
+    let tail: Vec<(isize, &str)> = body[body.len() - edge..]
+        .iter()
+        .enumerate()
+        .map(|(i, f)| (i as isize + 1 - edge as isize, f.as_str()))
+        .chain(
+            after
+                .iter()
+                .enumerate()
+                .map(|(i, f)| (i as isize + 1, f.as_str())),
+        )
+        .collect();
+
+    let (new_start, head_score) = best_match(&current, &head, 0, start)?;
+    let (new_end, tail_score) = best_match(&current, &tail, new_start, new_start + body.len() - 1)?;
+
+    let window = &current[new_start..=new_end];
+    let similarity = 2.0 * common_lines(window, &body) as f64 / (window.len() + body.len()) as f64;
+
+    Some(Relocation {
+        start: new_start,
+        end: new_end,
+        confidence: (head_score + tail_score) / 4.0 + similarity / 2.0,
+    })
+}
+
+/// Position at or after `from` where the most of `signature` matches, nearest `expected` on ties
+fn best_match(
+    current: &[String],
+    signature: &[(isize, &str)],
+    from: usize,
+    expected: usize,
+) -> Option<(usize, f64)> {
+    let mut best: Option<(usize, usize)> = None;
+    for pos in from..current.len() {
+        let hits = signature
+            .iter()
+            .filter(|(offset, fp)| {
+                pos.checked_add_signed(*offset)
+                    .and_then(|i| current.get(i))
+                    .is_some_and(|line| line == fp)
+            })
+            .count();
+        let better = match best {
+            None => hits > 0,
+            Some((best_pos, best_hits)) => {
+                hits > best_hits
+                    || (hits == best_hits && pos.abs_diff(expected) < best_pos.abs_diff(expected))
+            }
+        };
+        if better {
+            best = Some((pos, hits));
+        }
+    }
+    best.map(|(pos, hits)| (pos, hits as f64 / signature.len() as f64))
+}
+
+/// Length of the longest common subsequence of lines
+fn common_lines(a: &[String], b: &[String]) -> usize {
+    if a.len().saturating_mul(b.len()) > MAX_LCS_CELLS {
+        return a.iter().zip(b).filter(|(x, y)| x == y).count();
+    }
+    let mut prev = vec![0; b.len() + 1];
+    let mut row = vec![0; b.len() + 1];
+    for x in a {
+        for (j, y) in b.iter().enumerate() {
+            row[j + 1] = if x == y {
+                prev[j] + 1
+            } else {
+                row[j].max(prev[j + 1])
+            };
+        }
+        std::mem::swap(&mut prev, &mut row);
+    }
+    prev[b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn numbered(n: usize) -> Vec<String> {
+        (1..=n)
+            .map(|i| format!("let line_{} = {};", i, i))
+            .collect()
+    }
+
+    #[test]
+    fn test_relocate_ranges() {
+        let file = numbered(40);
+        let lines: Vec<&str> = file.iter().map(String::as_str).collect();
+        let original = lines[10..=14].join("\n");
+        let anchor = anchor_range(&lines, 10, 14);
+        assert_eq!(anchor.before.len(), 3);
+        assert_eq!(anchor.after.len(), 3);
+
+        // Unchanged
+        let found = relocate(&lines, &original, 10, 14, Some(&anchor)).unwrap();
+        assert_eq!((found.start, found.end, found.confidence), (10, 14, 1.0));
+
+        // Lines inserted above, and the range reindented
+        let mut moved: Vec<String> = vec!["// header".to_string(); 7];
+        moved.extend(file.iter().enumerate().map(|(i, l)| {
+            if (10..=14).contains(&i) {
+                format!("    {}", l)
+            } else {
+                l.clone()
+            }
+        }));
+        let moved: Vec<&str> = moved.iter().map(String::as_str).collect();
+        let found = relocate(&moved, &original, 10, 14, Some(&anchor)).unwrap();
+        assert_eq!((found.start, found.end), (17, 21));
+        assert_eq!(found.confidence, 1.0);
+
+        // A line added inside the range grows it
+        let mut grown = file.clone();
+        grown.insert(12, "let inserted = 0;".to_string());
+        let grown: Vec<&str> = grown.iter().map(String::as_str).collect();
+        let found = relocate(&grown, &original, 10, 14, Some(&anchor)).unwrap();
+        assert_eq!((found.start, found.end), (10, 15));
+        assert!(found.confidence >= CONFIDENT);
+
+        // Rewritten range: only the context still matches
+        let mut rewritten = file.clone();
+        for line in &mut rewritten[10..=14] {
+            *line = "todo!();".to_string();
+        }
+        let rewritten: Vec<&str> = rewritten.iter().map(String::as_str).collect();
+        let found = relocate(&rewritten, &original, 10, 14, Some(&anchor)).unwrap();
+        assert!(found.confidence < MIN_CONFIDENCE);
+
+        // Gone entirely
+        let other = ["fn main() {}"];
+        assert!(relocate(&other, &original, 10, 14, Some(&anchor)).is_none());
+    }
+
+    #[test]
+    fn test_relocate_without_anchor() {
+        let file = numbered(20);
+        let lines: Vec<&str> = file.iter().map(String::as_str).collect();
+        let original = lines[2..=5].join("\n");
+
+        let shifted: Vec<&str> = lines[1..].to_vec();
+        let found = relocate(&shifted, &original, 2, 5, None).unwrap();
+        assert_eq!((found.start, found.end), (1, 4));
+        assert_eq!(found.confidence, 1.0);
+    }
+}
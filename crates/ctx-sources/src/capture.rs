@@ -0,0 +1,145 @@
+use std::io::Read;
+
+use ctx_core::{Artifact, ArtifactMetadata, ArtifactType, Error, Result};
+
+use crate::tree::format_size;
+
+/// Where a snippet's content was captured from
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CaptureOrigin {
+    Stdin,
+    Clipboard,
+}
+
+impl CaptureOrigin {
+    pub fn scheme(&self) -> &'static str {
+        match self {
+            CaptureOrigin::Stdin => "stdin",
+            CaptureOrigin::Clipboard => "clipboard",
+        }
+    }
+}
+
+/// Titles longer than this are cut when derived from the content
+const MAX_DERIVED_TITLE_CHARS: usize = 60;
+
+/// Recognize `-`, `stdin:[title]` and `clipboard:[title]`, returning the origin and any title in the URI
+pub fn parse_capture_uri(uri: &str) -> Option<(CaptureOrigin, Option<&str>)> {
+    let (origin, title) = if uri == "-" {
+        (CaptureOrigin::Stdin, "")
+    } else if let Some(title) = uri.strip_prefix("stdin:") {
+        (CaptureOrigin::Stdin, title)
+    } else if let Some(title) = uri.strip_prefix("clipboard:") {
+        (CaptureOrigin::Clipboard, title)
+    } else {
+        return None;
+    };
+
+    let title = title.trim();
+    Some((origin, (!title.is_empty()).then_some(title)))
+}
+
+/// Build a snippet artifact for captured content. Without a title, the first non-empty line is used.
+///
+/// The content isn't kept on the artifact; store it with the artifact in the blob store.
+pub fn snippet_artifact(origin: CaptureOrigin, title: Option<&str>, content: &str) -> Artifact {
+    let title = match title.map(str::trim).filter(|t| !t.is_empty()) {
+        Some(title) => title.to_string(),
+        None => derive_title(content).unwrap_or_else(|| origin.scheme().to_string()),
+    };
+
+    let content_hash = blake3::hash(content.as_bytes()).to_hex().to_string();
+
+    let metadata = ArtifactMetadata {
+        size_bytes: content.len(),
+        mime_type: Some("text/plain".to_string()),
+        extra: serde_json::json!({}),
+        ..Default::default()
+    };
+
+    let source_uri = format!("{}:{}", origin.scheme(), title);
+
+    Artifact::new(ArtifactType::Snippet { title }, source_uri)
+        .with_hash(content_hash)
+        .with_metadata(metadata)
+}
+
+/// Read captured text, refusing more than `max_bytes` (the file size limit) instead of
+/// buffering whatever is piped in
+pub fn read_capped_text(
+    origin: CaptureOrigin,
+    reader: impl Read,
+    max_bytes: u64,
+) -> Result<String> {
+    let mut content = String::new();
+    reader
+        .take(max_bytes.saturating_add(1))
+        .read_to_string(&mut content)
+        .map_err(|e| Error::Other(anyhow::anyhow!("Failed to read {}: {}", origin.scheme(), e)))?;
+    if content.len() as u64 > max_bytes {
+        return Err(Error::Other(anyhow::anyhow!(
+            "{} is over the {} limit. Raise `[files] max_file_bytes` in ~/.ctx/config.toml to capture more",
+            origin.scheme(),
+            format_size(max_bytes)
+        )));
+    }
+    Ok(content)
+}
+
+fn derive_title(content: &str) -> Option<String> {
+    let line = content.lines().map(str::trim).find(|l| !l.is_empty())?;
+    if line.chars().count() <= MAX_DERIVED_TITLE_CHARS {
+        return Some(line.to_string());
+    }
+    let cut: String = line.chars().take(MAX_DERIVED_TITLE_CHARS).collect();
+    Some(format!("{}…", cut.trim_end()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_capture_uri() {
+        assert_eq!(parse_capture_uri("-"), Some((CaptureOrigin::Stdin, None)));
+        assert_eq!(
+            parse_capture_uri("stdin:"),
+            Some((CaptureOrigin::Stdin, None))
+        );
+        assert_eq!(
+            parse_capture_uri("clipboard: Stack trace "),
+            Some((CaptureOrigin::Clipboard, Some("Stack trace")))
+        );
+        assert_eq!(parse_capture_uri("text:hello"), None);
+    }
+
+    #[test]
+    fn test_snippet_title() {
+        let log = "\n   Compiling ctx v0.1.0\nerror[E0308]: mismatched types\n";
+
+        let artifact = snippet_artifact(CaptureOrigin::Stdin, Some("Build errors"), log);
+        assert_eq!(artifact.source_uri, "stdin:Build errors");
+        assert_eq!(
+            artifact.content_hash.as_deref(),
+            Some(blake3::hash(log.as_bytes()).to_hex().as_str())
+        );
+
+        let artifact = snippet_artifact(CaptureOrigin::Clipboard, None, log);
+        assert_eq!(artifact.source_uri, "clipboard:Compiling ctx v0.1.0");
+
+        let artifact = snippet_artifact(CaptureOrigin::Stdin, None, &"x".repeat(100));
+        assert_eq!(artifact.source_uri, format!("stdin:{}…", "x".repeat(60)));
+
+        let artifact = snippet_artifact(CaptureOrigin::Stdin, Some("  "), "");
+        assert_eq!(artifact.source_uri, "stdin:stdin");
+    }
+
+    #[test]
+    fn test_read_capped_text() {
+        let content = read_capped_text(CaptureOrigin::Stdin, "0123456789".as_bytes(), 10).unwrap();
+        assert_eq!(content, "0123456789");
+
+        let err = read_capped_text(CaptureOrigin::Stdin, "0123456789!".as_bytes(), 10).unwrap_err();
+        assert!(err.to_string().contains("stdin is over the"));
+    }
+}
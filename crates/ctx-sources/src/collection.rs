@@ -1,27 +1,52 @@
 use async_trait::async_trait;
-use ctx_core::{Artifact, ArtifactMetadata, ArtifactType, Error, Result};
-use std::path::Path;
+use ctx_core::{Artifact, ArtifactMetadata, ArtifactType, DirSort, Error, Result};
+use glob::Pattern;
+use ignore::WalkBuilder;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
 
+use crate::document::DOCUMENT_EXTENSIONS;
 use crate::handler::{SourceHandler, SourceOptions};
 
+const MARKDOWN_EXTENSIONS: &[&str] = &["md", "markdown"];
+
 pub struct CollectionHandler;
 
+/// Filters and limits for expanding a directory collection
+#[derive(Debug, Clone)]
+pub struct DirOptions {
+    pub recursive: bool,
+    /// File extensions to collect; with `include` also empty, markdown only
+    pub extensions: Vec<String>,
+    pub include: Vec<String>,
+    pub exclude: Vec<String>,
+    pub gitignore: bool,
+    pub sort: DirSort,
+    pub max_files: Option<usize>,
+    pub max_bytes: Option<u64>,
+}
+
 #[async_trait]
 impl SourceHandler for CollectionHandler {
     async fn parse(&self, uri: &str, options: SourceOptions) -> Result<Artifact> {
-        if let Some(path) = uri.strip_prefix("md_dir:") {
-            // Collection of markdown files in directory
-            let artifact_type = ArtifactType::CollectionMdDir {
-                path: path.to_string(),
-                max_files: options.max_files,
-                exclude: options.exclude,
-                recursive: options.recursive,
-            };
+        // md_dir: and docs_dir: are dir: with a preset extension list
+        let dir = if let Some(spec) = uri.strip_prefix("dir:") {
+            Some((spec, Vec::new()))
+        } else if let Some(spec) = uri.strip_prefix("md_dir:") {
+            Some((spec, MARKDOWN_EXTENSIONS.to_vec()))
+        } else {
+            uri.strip_prefix("docs_dir:")
+                .map(|spec| (spec, DOCUMENT_EXTENSIONS.to_vec()))
+        };
+
+        if let Some((spec, preset)) = dir {
+            let artifact_type = parse_dir_spec(uri, spec, &preset, options)?;
 
             let metadata = ArtifactMetadata {
                 size_bytes: 0, // Collections don't have a direct size
                 mime_type: Some("application/x-ctx-collection".to_string()),
                 extra: serde_json::json!({}),
+                ..Default::default()
             };
 
             Ok(Artifact::new(artifact_type, uri.to_string()).with_metadata(metadata))
@@ -35,6 +60,7 @@ impl SourceHandler for CollectionHandler {
                 size_bytes: 0,
                 mime_type: Some("application/x-ctx-collection".to_string()),
                 extra: serde_json::json!({}),
+                ..Default::default()
             };
 
             Ok(Artifact::new(artifact_type, uri.to_string()).with_metadata(metadata))
@@ -54,62 +80,117 @@ impl SourceHandler for CollectionHandler {
     }
 
     fn can_handle(&self, uri: &str) -> bool {
-        uri.starts_with("md_dir:") || uri.starts_with("glob:")
+        uri.starts_with("dir:")
+            || uri.starts_with("md_dir:")
+            || uri.starts_with("docs_dir:")
+            || uri.starts_with("glob:")
     }
 }
 
-impl CollectionHandler {
-    /// Expand md_dir into individual file artifacts
-    pub async fn expand_md_dir(
-        &self,
-        path: &str,
-        max_files: Option<usize>,
-        exclude: &[String],
-        recursive: bool,
-    ) -> Result<Vec<String>> {
-        let mut files = Vec::new();
-        let path = Path::new(path);
+/// Parse `<path> [--ext=rs,toml] [--include=glob] [--exclude=glob] [--recursive] [--sort=path|mtime|size]
+/// [--max-files=N] [--max-bytes=N] [--no-gitignore]`
+fn parse_dir_spec(
+    uri: &str,
+    spec: &str,
+    preset_extensions: &[&str],
+    options: SourceOptions,
+) -> Result<ArtifactType> {
+    let mut path_parts = Vec::new();
+    let mut extensions: Vec<String> = preset_extensions.iter().map(|e| e.to_string()).collect();
+    let mut include = Vec::new();
+    let mut exclude = options.exclude;
+    let mut recursive = options.recursive;
+    let mut gitignore = true;
+    let mut sort = DirSort::default();
+    let mut max_files = options.max_files;
+    let mut max_bytes = None;
 
-        if !path.exists() {
-            return Err(Error::Other(anyhow::anyhow!(
-                "Directory does not exist: {}",
-                path.display()
+    for part in spec.split_whitespace() {
+        if let Some(val) = part.strip_prefix("--ext=") {
+            extensions.extend(
+                val.split(',')
+                    .map(|e| e.trim().trim_start_matches('.').to_lowercase())
+                    .filter(|e| !e.is_empty()),
+            );
+        } else if let Some(val) = part.strip_prefix("--include=") {
+            include.push(val.to_string());
+        } else if let Some(val) = part.strip_prefix("--exclude=") {
+            exclude.push(val.to_string());
+        } else if part == "--recursive" {
+            recursive = true;
+        } else if part == "--no-gitignore" {
+            gitignore = false;
+        } else if let Some(val) = part.strip_prefix("--sort=") {
+            sort = val.parse().map_err(Error::InvalidSourceUri)?;
+        } else if let Some(val) = part.strip_prefix("--max-files=") {
+            max_files = Some(parse_number("max-files", val)?);
+        } else if let Some(val) = part.strip_prefix("--max-bytes=") {
+            max_bytes = Some(parse_number("max-bytes", val)?);
+        } else if part.starts_with("--") {
+            return Err(Error::InvalidSourceUri(format!(
+                "Unknown directory option '{}'. Expected --ext, --include, --exclude, --recursive, --sort, --max-files, --max-bytes or --no-gitignore",
+                part
             )));
-        }
-
-        if recursive {
-            // Use walkdir for recursive scanning
-            for entry in walkdir::WalkDir::new(path)
-                .into_iter()
-                .filter_entry(|e| !is_excluded(e.path(), exclude))
-            {
-                let entry = entry.map_err(|e| Error::Other(e.into()))?;
-                if entry.file_type().is_file() && is_markdown(entry.path()) {
-                    files.push(entry.path().display().to_string());
-                }
-            }
         } else {
-            // Non-recursive: only immediate children
-            let mut dir_entries = tokio::fs::read_dir(path).await?;
-            while let Some(entry) = dir_entries.next_entry().await? {
-                if entry.file_type().await?.is_file() {
-                    let path = entry.path();
-                    if is_markdown(&path) && !is_excluded(&path, exclude) {
-                        files.push(path.display().to_string());
-                    }
-                }
-            }
+            path_parts.push(part);
         }
+    }
 
-        // Sort for determinism
-        files.sort();
+    if path_parts.is_empty() {
+        return Err(Error::InvalidSourceUri(format!(
+            "Expected a directory path in: {}",
+            uri
+        )));
+    }
+
+    // A bare dir: takes every file
+    if extensions.is_empty() && include.is_empty() {
+        include.push("*".to_string());
+    }
 
-        // Apply max_files limit
-        if let Some(max) = max_files {
-            files.truncate(max);
+    compile_patterns(&include)?;
+    compile_patterns(&exclude)?;
+
+    Ok(ArtifactType::CollectionDir {
+        path: path_parts.join(" "),
+        max_files,
+        max_bytes,
+        exclude,
+        recursive,
+        extensions,
+        include,
+        gitignore,
+        sort,
+    })
+}
+
+fn parse_number<T: std::str::FromStr>(name: &str, val: &str) -> Result<T> {
+    val.parse()
+        .map_err(|_| Error::InvalidSourceUri(format!("Invalid {} '{}'", name, val)))
+}
+
+struct DirEntry {
+    path: PathBuf,
+    rel_path: String,
+    size: u64,
+    modified: SystemTime,
+}
+
+impl CollectionHandler {
+    /// Expand a directory collection into file paths, in `options.sort` order
+    pub async fn expand_dir(&self, path: &str, options: &DirOptions) -> Result<Vec<String>> {
+        let root = PathBuf::from(path);
+        if !root.is_dir() {
+            return Err(Error::Other(anyhow::anyhow!(
+                "Directory does not exist: {}",
+                root.display()
+            )));
         }
 
-        Ok(files)
+        let options = options.clone();
+        tokio::task::spawn_blocking(move || expand_dir_blocking(&root, &options))
+            .await
+            .map_err(|e| Error::Other(e.into()))?
     }
 
     /// Expand glob pattern into individual file artifacts
@@ -130,14 +211,311 @@ impl CollectionHandler {
     }
 }
 
-fn is_markdown(path: &Path) -> bool {
-    path.extension()
-        .and_then(|e| e.to_str())
-        .map(|e| e.eq_ignore_ascii_case("md") || e.eq_ignore_ascii_case("markdown"))
-        .unwrap_or(false)
+fn expand_dir_blocking(root: &Path, options: &DirOptions) -> Result<Vec<String>> {
+    let include = compile_patterns(&options.include)?;
+    let exclude = compile_patterns(&options.exclude)?;
+
+    let walker = WalkBuilder::new(root)
+        .standard_filters(options.gitignore)
+        .require_git(false)
+        .max_depth(if options.recursive { None } else { Some(1) })
+        .build();
+
+    let mut entries = Vec::new();
+    for entry in walker {
+        let entry = entry.map_err(|e| Error::Other(e.into()))?;
+        if !entry.file_type().is_some_and(|t| t.is_file()) {
+            continue;
+        }
+        let Ok(rel) = entry.path().strip_prefix(root) else {
+            continue;
+        };
+        let components = path_components(rel);
+        if is_excluded(&components, &exclude)
+            || !(has_extension(entry.path(), &options.extensions, &include)
+                || matches_any(&components, &include))
+        {
+            continue;
+        }
+
+        let metadata = entry.metadata().map_err(|e| Error::Other(e.into()))?;
+        entries.push(DirEntry {
+            path: entry.path().to_path_buf(),
+            rel_path: components.join("/"),
+            size: metadata.len(),
+            modified: metadata.modified().unwrap_or(SystemTime::UNIX_EPOCH),
+        });
+    }
+
+    // Ties fall back to the path so the order is deterministic
+    match options.sort {
+        DirSort::Path => entries.sort_by(|a, b| a.rel_path.cmp(&b.rel_path)),
+        DirSort::Mtime => entries.sort_by(|a, b| {
+            b.modified
+                .cmp(&a.modified)
+                .then_with(|| a.rel_path.cmp(&b.rel_path))
+        }),
+        DirSort::Size => entries.sort_by(|a, b| {
+            a.size
+                .cmp(&b.size)
+                .then_with(|| a.rel_path.cmp(&b.rel_path))
+        }),
+    }
+
+    if let Some(max) = options.max_files {
+        entries.truncate(max);
+    }
+
+    let mut files = Vec::new();
+    let mut total_bytes = 0;
+    for entry in entries {
+        total_bytes += entry.size;
+        if options.max_bytes.is_some_and(|max| total_bytes > max) {
+            break;
+        }
+        files.push(entry.path.display().to_string());
+    }
+
+    Ok(files)
+}
+
+/// With no extensions or include patterns, only markdown is collected (md_dir: rows saved before dir:)
+fn has_extension(path: &Path, extensions: &[String], include: &[Pattern]) -> bool {
+    let Some(ext) = path.extension().and_then(|e| e.to_str()) else {
+        return false;
+    };
+    if extensions.is_empty() && include.is_empty() {
+        return MARKDOWN_EXTENSIONS
+            .iter()
+            .any(|e| ext.eq_ignore_ascii_case(e));
+    }
+    extensions.iter().any(|e| ext.eq_ignore_ascii_case(e))
+}
+
+pub(crate) fn path_components(rel_path: &Path) -> Vec<String> {
+    rel_path
+        .components()
+        .map(|c| c.as_os_str().to_string_lossy().to_string())
+        .collect()
+}
+
+pub(crate) fn compile_patterns(patterns: &[String]) -> Result<Vec<Pattern>> {
+    patterns
+        .iter()
+        .map(|p| {
+            Pattern::new(p).map_err(|e| {
+                Error::InvalidSourceUri(format!("Invalid glob pattern '{}': {}", p, e))
+            })
+        })
+        .collect()
+}
+
+/// Whether a pattern matches the relative path or any of its parent directories
+pub(crate) fn is_excluded(components: &[String], exclude: &[Pattern]) -> bool {
+    (1..=components.len()).any(|n| {
+        let prefix = components[..n].join("/");
+        exclude.iter().any(|p| p.matches(&prefix))
+    })
 }
 
-fn is_excluded(path: &Path, exclude: &[String]) -> bool {
-    let path_str = path.display().to_string();
-    exclude.iter().any(|pattern| path_str.contains(pattern))
+/// Whether a pattern matches the relative path or the file name
+pub(crate) fn matches_any(components: &[String], patterns: &[Pattern]) -> bool {
+    let rel_path = components.join("/");
+    let file_name = components.last().map(String::as_str).unwrap_or_default();
+    patterns
+        .iter()
+        .any(|p| p.matches(&rel_path) || p.matches(file_name))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fixture(name: &str) -> PathBuf {
+        let dir =
+            std::env::temp_dir().join(format!("ctx-collection-{}-{}", name, uuid::Uuid::new_v4()));
+        for (path, content) in [
+            ("README.md", "# Readme\n"),
+            ("guide.markdown", "guide\n"),
+            ("src/main.rs", "fn main() {}\n"),
+            ("src/lib.rs", "pub mod big;\n"),
+            ("src/big.rs", "// a much larger file than the others\n"),
+            ("src/drafts/wip.rs", "\n"),
+            ("notes/drafts.md", "drafts\n"),
+            ("target/out.rs", "\n"),
+            (".gitignore", "target/\n"),
+        ] {
+            let path = dir.join(path);
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(path, content).unwrap();
+        }
+        dir
+    }
+
+    async fn expand(dir: &Path, uri_options: &str) -> Vec<String> {
+        let uri = format!("dir:{} {}", dir.display(), uri_options);
+        let artifact = CollectionHandler
+            .parse(&uri, SourceOptions::default())
+            .await
+            .unwrap();
+        let ArtifactType::CollectionDir {
+            path,
+            max_files,
+            max_bytes,
+            exclude,
+            recursive,
+            extensions,
+            include,
+            gitignore,
+            sort,
+        } = artifact.artifact_type
+        else {
+            panic!("expected a directory collection");
+        };
+        let options = DirOptions {
+            recursive,
+            extensions,
+            include,
+            exclude,
+            gitignore,
+            sort,
+            max_files,
+            max_bytes,
+        };
+        CollectionHandler
+            .expand_dir(&path, &options)
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|p| {
+                Path::new(&p)
+                    .strip_prefix(dir)
+                    .unwrap()
+                    .display()
+                    .to_string()
+            })
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn test_expand_dir_filters() {
+        let dir = fixture("filters");
+
+        assert_eq!(
+            expand(&dir, "--ext=rs --recursive").await,
+            vec![
+                "src/big.rs",
+                "src/drafts/wip.rs",
+                "src/lib.rs",
+                "src/main.rs"
+            ]
+        );
+
+        // Excludes are globs matched against directories too, not substrings
+        assert_eq!(
+            expand(&dir, "--recursive --exclude=src/drafts --exclude=*.rs").await,
+            vec!["README.md", "guide.markdown", "notes/drafts.md"]
+        );
+
+        assert_eq!(
+            expand(&dir, "--recursive --include=src/m*").await,
+            vec!["src/main.rs"]
+        );
+
+        // Gitignored files only show up when asked for
+        assert!(
+            !expand(&dir, "--recursive")
+                .await
+                .contains(&"target/out.rs".to_string())
+        );
+        assert!(
+            expand(&dir, "--recursive --no-gitignore")
+                .await
+                .contains(&"target/out.rs".to_string())
+        );
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_expand_dir_sort_and_limits() {
+        let dir = fixture("limits");
+
+        assert_eq!(
+            expand(&dir.join("src"), "--ext=rs --sort=size --max-files=2").await,
+            vec!["lib.rs", "main.rs"]
+        );
+
+        // Stops at lib.rs, which would push the total past 40 bytes
+        assert_eq!(
+            expand(&dir, "--ext=rs --recursive --max-bytes=40").await,
+            vec!["src/big.rs", "src/drafts/wip.rs"]
+        );
+        assert_eq!(
+            expand(&dir, "--ext=rs --recursive --sort=size --max-bytes=40").await,
+            vec!["src/drafts/wip.rs", "src/lib.rs", "src/main.rs"]
+        );
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_md_dir_alias() {
+        let dir = fixture("alias");
+
+        let uri = format!("md_dir:{}", dir.display());
+        let artifact = CollectionHandler
+            .parse(
+                &uri,
+                SourceOptions {
+                    recursive: true,
+                    ..Default::default()
+                },
+            )
+            .await
+            .unwrap();
+        let ArtifactType::CollectionDir {
+            path, extensions, ..
+        } = &artifact.artifact_type
+        else {
+            panic!("expected a directory collection");
+        };
+        assert_eq!(extensions, &vec!["md".to_string(), "markdown".to_string()]);
+
+        // Rows saved before dir: existed still load, and still mean markdown only. Their
+        // substring excludes were migrated to globs matching the same paths.
+        let legacy: ArtifactType = serde_json::from_value(serde_json::json!({
+            "type": "collection_md_dir",
+            "path": path,
+            "recursive": true,
+            "exclude": ["*drafts*"],
+        }))
+        .unwrap();
+        let ArtifactType::CollectionDir {
+            exclude,
+            extensions,
+            include,
+            gitignore,
+            ..
+        } = legacy
+        else {
+            panic!("expected a directory collection");
+        };
+        let options = DirOptions {
+            recursive: true,
+            extensions,
+            include,
+            exclude,
+            gitignore,
+            sort: DirSort::Path,
+            max_files: None,
+            max_bytes: None,
+        };
+        let files = CollectionHandler.expand_dir(path, &options).await.unwrap();
+        assert_eq!(files.len(), 2);
+        assert!(files[0].ends_with("README.md"));
+        assert!(files[1].ends_with("guide.markdown"));
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
 }
@@ -0,0 +1,258 @@
+use async_trait::async_trait;
+use ctx_core::{Artifact, ArtifactMetadata, ArtifactType, Error, Result};
+use std::path::PathBuf;
+use std::process::Stdio;
+use std::time::Duration;
+use tokio::io::{AsyncRead, AsyncReadExt};
+
+use crate::handler::{SourceHandler, SourceOptions};
+
+/// Limits for running `cmd:` sources
+#[derive(Debug, Clone)]
+pub struct CommandPolicy {
+    /// Directory commands run in; defaults to the directory the artifact was added from
+    pub working_dir: Option<PathBuf>,
+    pub timeout: Duration,
+    pub max_output_bytes: usize,
+    /// Environment variables passed through (`NAME` or `PREFIX_*`); everything else is cleared
+    pub env_allowlist: Vec<String>,
+}
+
+/// Handles `cmd:<command line>` sources. Commands are refused unless a policy is set.
+pub struct CommandHandler {
+    policy: Option<CommandPolicy>,
+}
+
+impl CommandHandler {
+    pub fn new(policy: Option<CommandPolicy>) -> Self {
+        Self { policy }
+    }
+
+    fn policy(&self) -> Result<&CommandPolicy> {
+        self.policy.as_ref().ok_or_else(|| {
+            Error::Other(anyhow::anyhow!(
+                "cmd: sources are disabled. Set `[commands] enabled = true` in ~/.ctx/config.toml to allow running commands"
+            ))
+        })
+    }
+}
+
+#[async_trait]
+impl SourceHandler for CommandHandler {
+    async fn parse(&self, uri: &str, _options: SourceOptions) -> Result<Artifact> {
+        self.policy()?;
+
+        let command = uri
+            .strip_prefix("cmd:")
+            .map(str::trim)
+            .ok_or_else(|| Error::InvalidSourceUri(format!("Invalid command URI: {}", uri)))?;
+
+        split_command(command)?;
+
+        let cwd = std::env::current_dir()
+            .map_err(|e| Error::Other(anyhow::anyhow!("Failed to get current directory: {}", e)))?
+            .to_string_lossy()
+            .to_string();
+
+        let artifact_type = ArtifactType::Command {
+            command: command.to_string(),
+            cwd,
+        };
+
+        let metadata = ArtifactMetadata {
+            size_bytes: 0,
+            mime_type: Some("text/plain".to_string()),
+            extra: serde_json::json!({}),
+            ..Default::default()
+        };
+
+        Ok(Artifact::new(artifact_type, uri.to_string()).with_metadata(metadata))
+    }
+
+    async fn load(&self, artifact: &Artifact) -> Result<String> {
+        let policy = self.policy()?;
+        match &artifact.artifact_type {
+            ArtifactType::Command { command, cwd } => run_command(command, cwd, policy).await,
+            _ => Err(Error::Other(anyhow::anyhow!(
+                "Unsupported artifact type for CommandHandler"
+            ))),
+        }
+    }
+
+    fn can_handle(&self, uri: &str) -> bool {
+        uri.starts_with("cmd:")
+    }
+}
+
+fn split_command(command: &str) -> Result<Vec<String>> {
+    let argv = shlex::split(command)
+        .ok_or_else(|| Error::InvalidSourceUri(format!("Unbalanced quotes in: {}", command)))?;
+    if argv.is_empty() {
+        return Err(Error::InvalidSourceUri(
+            "Expected cmd:<program> [args...]".to_string(),
+        ));
+    }
+    Ok(argv)
+}
+
+fn env_allowed(name: &str, allowlist: &[String]) -> bool {
+    allowlist
+        .iter()
+        .any(|allowed| match allowed.strip_suffix('*') {
+            Some(prefix) => name.starts_with(prefix),
+            None => name == allowed,
+        })
+}
+
+/// Run a command without a shell and render its output, exit status included
+async fn run_command(command: &str, cwd: &str, policy: &CommandPolicy) -> Result<String> {
+    let argv = split_command(command)?;
+    let dir = policy
+        .working_dir
+        .clone()
+        .unwrap_or_else(|| PathBuf::from(cwd));
+
+    let mut cmd = tokio::process::Command::new(&argv[0]);
+    cmd.args(&argv[1..])
+        .current_dir(&dir)
+        .env_clear()
+        .envs(std::env::vars().filter(|(name, _)| env_allowed(name, &policy.env_allowlist)))
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true);
+
+    let mut child = cmd
+        .spawn()
+        .map_err(|e| Error::Other(anyhow::anyhow!("Failed to run '{}': {}", command, e)))?;
+    let stdout = child.stdout.take();
+    let stderr = child.stderr.take();
+    let cap = policy.max_output_bytes;
+
+    let run = async {
+        let (out, err) = tokio::join!(read_capped(stdout, cap), read_capped(stderr, cap));
+        let (out, err) = (out?, err?);
+        // No point waiting for a command whose output we're discarding
+        if out.1 || err.1 {
+            let _ = child.start_kill();
+        }
+        let status = child.wait().await?;
+        Ok::<_, std::io::Error>((out, err, status))
+    };
+
+    let ((mut output, out_truncated), (stderr, err_truncated), status) =
+        match tokio::time::timeout(policy.timeout, run).await {
+            Ok(result) => result
+                .map_err(|e| Error::Other(anyhow::anyhow!("Failed to run '{}': {}", command, e)))?,
+            Err(_) => {
+                let _ = child.kill().await;
+                return Err(Error::Other(anyhow::anyhow!(
+                    "Command '{}' timed out after {}s",
+                    command,
+                    policy.timeout.as_secs()
+                )));
+            }
+        };
+
+    output.extend_from_slice(&stderr);
+    let truncated = out_truncated || err_truncated || output.len() > cap;
+    output.truncate(cap);
+
+    let mut rendered = format!("$ {}\n", command);
+    rendered.push_str(String::from_utf8_lossy(&output).trim_end());
+    if truncated {
+        rendered.push_str(&format!("\n[output truncated at {} bytes]", cap));
+    } else if !status.success() {
+        match status.code() {
+            Some(code) => rendered.push_str(&format!("\n[exit status: {}]", code)),
+            None => rendered.push_str("\n[terminated by signal]"),
+        }
+    }
+
+    Ok(rendered)
+}
+
+/// Read up to `cap` bytes; the flag is set if there was more
+pub(crate) async fn read_capped<R: AsyncRead + Unpin>(
+    reader: Option<R>,
+    cap: usize,
+) -> std::io::Result<(Vec<u8>, bool)> {
+    let mut buf = Vec::new();
+    if let Some(reader) = reader {
+        reader.take(cap as u64 + 1).read_to_end(&mut buf).await?;
+    }
+    let truncated = buf.len() > cap;
+    buf.truncate(cap);
+    Ok((buf, truncated))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn policy() -> CommandPolicy {
+        CommandPolicy {
+            working_dir: None,
+            timeout: Duration::from_secs(5),
+            max_output_bytes: 1024,
+            env_allowlist: vec!["PATH".to_string(), "CTX_TEST_*".to_string()],
+        }
+    }
+
+    #[tokio::test]
+    async fn test_disabled_by_default() {
+        let handler = CommandHandler::new(None);
+        let err = handler
+            .parse("cmd:echo hi", SourceOptions::default())
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("cmd: sources are disabled"));
+    }
+
+    #[tokio::test]
+    async fn test_run_command() {
+        let handler = CommandHandler::new(Some(policy()));
+        let artifact = handler
+            .parse(
+                "cmd:sh -c 'echo out; echo err >&2; exit 3'",
+                SourceOptions::default(),
+            )
+            .await
+            .unwrap();
+
+        let output = handler.load(&artifact).await.unwrap();
+        assert_eq!(
+            output,
+            "$ sh -c 'echo out; echo err >&2; exit 3'\nout\nerr\n[exit status: 3]"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_env_allowlist() {
+        let dir = std::env::temp_dir();
+        let output = run_command("env", &dir.to_string_lossy(), &policy())
+            .await
+            .unwrap();
+        assert!(output.contains("PATH="));
+        assert!(!output.contains("HOME="));
+
+        assert!(env_allowed("CTX_TEST_TOKEN", &policy().env_allowlist));
+        assert!(!env_allowed("CTX_OTHER", &policy().env_allowlist));
+    }
+
+    #[tokio::test]
+    async fn test_output_cap_and_timeout() {
+        let dir = std::env::temp_dir().to_string_lossy().to_string();
+
+        let output = run_command("yes", &dir, &policy()).await.unwrap();
+        assert!(output.ends_with("[output truncated at 1024 bytes]"));
+        assert!(output.len() < 1200);
+
+        let slow = CommandPolicy {
+            timeout: Duration::from_millis(200),
+            ..policy()
+        };
+        let err = run_command("sleep 5", &dir, &slow).await.unwrap_err();
+        assert!(err.to_string().contains("timed out"));
+    }
+}
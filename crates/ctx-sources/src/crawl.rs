@@ -0,0 +1,631 @@
+use async_trait::async_trait;
+use ctx_core::{Artifact, ArtifactMetadata, ArtifactType, Error, Result};
+use glob::Pattern;
+use regex::Regex;
+use reqwest::Url;
+use std::collections::{HashSet, VecDeque};
+use std::sync::LazyLock;
+
+use crate::handler::{SourceHandler, SourceOptions};
+use crate::url::UrlHandler;
+
+const DEFAULT_MAX_DEPTH: usize = 2;
+const DEFAULT_MAX_PAGES: usize = 50;
+/// Larger pages are skipped rather than read into memory
+const MAX_PAGE_BYTES: usize = 5 * 1024 * 1024;
+
+static HREF_REGEX: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r#"(?i)<a\s[^>]*?href\s*=\s*["']([^"']+)["']"#).unwrap());
+
+pub struct CrawlHandler;
+
+/// A page fetched while crawling
+#[derive(Debug, Clone)]
+pub struct CrawledPage {
+    pub url: String,
+    pub title: Option<String>,
+    pub content: String,
+}
+
+/// Pages of a crawl, and why others were skipped
+#[derive(Debug, Default)]
+pub struct CrawlOutcome {
+    pub pages: Vec<CrawledPage>,
+    pub warnings: Vec<String>,
+}
+
+#[async_trait]
+impl SourceHandler for CrawlHandler {
+    async fn parse(&self, uri: &str, options: SourceOptions) -> Result<Artifact> {
+        // Format: crawl:https://docs.rs/tokio --depth=2 --max-pages=50 --include=/tokio/** --exclude=/src/**
+        let spec = uri
+            .strip_prefix("crawl:")
+            .ok_or_else(|| Error::InvalidSourceUri(format!("Invalid crawl URI: {}", uri)))?;
+
+        let mut parts = spec.split_whitespace();
+        let url = parts.next().unwrap_or_default();
+        let start = Url::parse(url)
+            .map_err(|e| Error::InvalidSourceUri(format!("Invalid crawl URL {}: {}", url, e)))?;
+        if start.scheme() != "http" && start.scheme() != "https" {
+            return Err(Error::InvalidSourceUri(format!(
+                "Crawl URL must start with http:// or https://: {}",
+                url
+            )));
+        }
+
+        let mut max_depth = DEFAULT_MAX_DEPTH;
+        let mut max_pages = options.max_files.unwrap_or(DEFAULT_MAX_PAGES);
+        let mut include = Vec::new();
+        let mut exclude = options.exclude;
+
+        for part in parts {
+            if let Some(val) = part.strip_prefix("--depth=") {
+                max_depth = parse_number("depth", val)?;
+            } else if let Some(val) = part.strip_prefix("--max-pages=") {
+                max_pages = parse_number("max-pages", val)?;
+            } else if let Some(val) = part.strip_prefix("--include=") {
+                include.push(val.to_string());
+            } else if let Some(val) = part.strip_prefix("--exclude=") {
+                exclude.push(val.to_string());
+            } else {
+                return Err(Error::InvalidSourceUri(format!(
+                    "Unknown crawl option '{}'. Expected --depth, --max-pages, --include or --exclude",
+                    part
+                )));
+            }
+        }
+
+        for pattern in include.iter().chain(&exclude) {
+            Pattern::new(pattern).map_err(|e| {
+                Error::InvalidSourceUri(format!("Invalid path pattern '{}': {}", pattern, e))
+            })?;
+        }
+
+        let artifact_type = ArtifactType::Crawl {
+            url: start.to_string(),
+            max_depth,
+            max_pages,
+            include,
+            exclude,
+        };
+
+        let metadata = ArtifactMetadata {
+            size_bytes: 0,
+            mime_type: Some("application/x-ctx-collection".to_string()),
+            extra: serde_json::json!({}),
+            ..Default::default()
+        };
+
+        Ok(Artifact::new(artifact_type, uri.to_string()).with_metadata(metadata))
+    }
+
+    async fn load(&self, _artifact: &Artifact) -> Result<String> {
+        // Crawls are expanded during rendering, not loaded directly
+        Err(Error::Other(anyhow::anyhow!(
+            "Crawls must be expanded before loading"
+        )))
+    }
+
+    fn can_handle(&self, uri: &str) -> bool {
+        uri.starts_with("crawl:")
+    }
+}
+
+impl CrawlHandler {
+    /// Crawl same-origin pages breadth-first, starting from `url`. Fails if the start page
+    /// can't be fetched or robots.txt disallows it; other pages that can't be fetched are
+    /// skipped with a warning.
+    pub async fn expand_crawl(
+        &self,
+        url: &str,
+        max_depth: usize,
+        max_pages: usize,
+        include: &[String],
+        exclude: &[String],
+    ) -> Result<CrawlOutcome> {
+        let start = Url::parse(url)
+            .map_err(|e| Error::InvalidSourceUri(format!("Invalid crawl URL {}: {}", url, e)))?;
+        let client = UrlHandler::client()?;
+        let robots = fetch_robots(&client, &start).await;
+        let filter = PathFilter::new(include, exclude);
+
+        let mut pages = Vec::new();
+        let mut warnings = Vec::new();
+        let mut seen = HashSet::from([start.to_string()]);
+        let mut queue = VecDeque::from([(start, 0)]);
+
+        while let Some((page_url, depth)) = queue.pop_front() {
+            if pages.len() >= max_pages {
+                break;
+            }
+            let fetched = if robots.is_allowed(page_url.path()) {
+                fetch_page(&client, &page_url).await
+            } else if depth == 0 {
+                Err("disallowed by robots.txt".to_string())
+            } else {
+                continue;
+            };
+            let (body, content_type) = match fetched {
+                Ok(page) => page,
+                Err(message) if depth == 0 => {
+                    return Err(Error::Other(anyhow::anyhow!(
+                        "Failed to fetch {}: {}",
+                        page_url,
+                        message
+                    )));
+                }
+                Err(message) => {
+                    warnings.push(format!("Skipped {}: {}", page_url, message));
+                    continue;
+                }
+            };
+            let is_html = content_type.contains("text/html");
+
+            if is_html && depth < max_depth {
+                for link in extract_links(&page_url, &body) {
+                    if link.origin() == page_url.origin()
+                        && filter.matches(link.path())
+                        && seen.insert(link.to_string())
+                    {
+                        queue.push_back((link, depth + 1));
+                    }
+                }
+            }
+
+            // Only keep pages that match the filters; the start page may just be an index
+            if !filter.matches(page_url.path()) {
+                continue;
+            }
+            if !is_html && !content_type.starts_with("text/") {
+                continue;
+            }
+
+            let title = if is_html {
+                UrlHandler::extract_title(&body)
+            } else {
+                None
+            };
+            pages.push(CrawledPage {
+                url: page_url.to_string(),
+                title,
+                content: UrlHandler::body_to_text(body, &content_type),
+            });
+        }
+
+        // Sort for determinism
+        pages.sort_by(|a, b| a.url.cmp(&b.url));
+
+        Ok(CrawlOutcome { pages, warnings })
+    }
+}
+
+fn parse_number(name: &str, value: &str) -> Result<usize> {
+    value.parse().map_err(|e| {
+        Error::InvalidSourceUri(format!("Invalid --{} value '{}': {}", name, value, e))
+    })
+}
+
+/// Fetch a page, returning its body and content type, or why it couldn't be. Only text is
+/// kept, so the body of anything else is left unread and returned empty.
+async fn fetch_page(
+    client: &reqwest::Client,
+    url: &Url,
+) -> std::result::Result<(String, String), String> {
+    let mut response = client
+        .get(url.clone())
+        .send()
+        .await
+        .map_err(|e| e.to_string())?;
+    if !response.status().is_success() {
+        return Err(format!("HTTP error {}", response.status().as_u16()));
+    }
+
+    let content_type = response
+        .headers()
+        .get("content-type")
+        .and_then(|v| v.to_str().ok())
+        .map(|s| s.to_string())
+        .unwrap_or_default();
+    if !content_type.starts_with("text/") {
+        return Ok((String::new(), content_type));
+    }
+
+    let mut body = Vec::new();
+    while let Some(chunk) = response.chunk().await.map_err(|e| e.to_string())? {
+        if body.len() + chunk.len() > MAX_PAGE_BYTES {
+            return Err(format!("larger than {} bytes", MAX_PAGE_BYTES));
+        }
+        body.extend_from_slice(&chunk);
+    }
+    Ok((String::from_utf8_lossy(&body).into_owned(), content_type))
+}
+
+/// Extract absolute, fragment-free links from an HTML page
+fn extract_links(base: &Url, html: &str) -> Vec<Url> {
+    HREF_REGEX
+        .captures_iter(html)
+        .filter_map(|cap| base.join(cap[1].trim()).ok())
+        .filter(|link| link.scheme() == "http" || link.scheme() == "https")
+        .map(|mut link| {
+            link.set_fragment(None);
+            link
+        })
+        .collect()
+}
+
+/// Include/exclude glob patterns applied to URL paths
+struct PathFilter {
+    include: Vec<Pattern>,
+    exclude: Vec<Pattern>,
+}
+
+impl PathFilter {
+    fn new(include: &[String], exclude: &[String]) -> Self {
+        let compile = |patterns: &[String]| -> Vec<Pattern> {
+            patterns
+                .iter()
+                .filter_map(|p| Pattern::new(p).ok())
+                .collect()
+        };
+        Self {
+            include: compile(include),
+            exclude: compile(exclude),
+        }
+    }
+
+    fn matches(&self, path: &str) -> bool {
+        (self.include.is_empty() || self.include.iter().any(|p| p.matches(path)))
+            && !self.exclude.iter().any(|p| p.matches(path))
+    }
+}
+
+/// User agents of a robots.txt group and its (allow, pattern) rules
+type RobotsGroup = (Vec<String>, Vec<(bool, String)>);
+
+/// Allow/disallow rules from robots.txt that apply to ctx
+#[derive(Debug, Default)]
+struct RobotsRules {
+    /// (allow, path prefix pattern)
+    rules: Vec<(bool, String)>,
+}
+
+impl RobotsRules {
+    /// Parse robots.txt, using the `ctx` group if present and `*` otherwise
+    fn parse(content: &str) -> Self {
+        let mut groups: Vec<RobotsGroup> = Vec::new();
+        let mut in_agents = false;
+
+        for line in content.lines() {
+            let line = line.split('#').next().unwrap_or("").trim();
+            let Some((key, value)) = line.split_once(':') else {
+                continue;
+            };
+            let key = key.trim().to_ascii_lowercase();
+            let value = value.trim();
+
+            match key.as_str() {
+                "user-agent" => {
+                    if !in_agents {
+                        groups.push((Vec::new(), Vec::new()));
+                        in_agents = true;
+                    }
+                    if let Some(group) = groups.last_mut() {
+                        group.0.push(value.to_ascii_lowercase());
+                    }
+                }
+                "allow" | "disallow" => {
+                    in_agents = false;
+                    // An empty Disallow means "allow everything"
+                    if value.is_empty() {
+                        continue;
+                    }
+                    if let Some(group) = groups.last_mut() {
+                        group.1.push((key == "allow", value.to_string()));
+                    }
+                }
+                _ => in_agents = false,
+            }
+        }
+
+        let find = |agent: &str| {
+            groups
+                .iter()
+                .find(|(agents, _)| agents.iter().any(|a| a == agent))
+                .map(|(_, rules)| rules.clone())
+        };
+
+        Self {
+            rules: find("ctx").or_else(|| find("*")).unwrap_or_default(),
+        }
+    }
+
+    /// Longest matching rule wins; ties go to Allow
+    fn is_allowed(&self, path: &str) -> bool {
+        let mut best: Option<(usize, bool)> = None;
+
+        for (allow, pattern) in &self.rules {
+            if robots_pattern_matches(pattern, path) {
+                let len = pattern.len();
+                let better = match best {
+                    None => true,
+                    Some((best_len, best_allow)) => {
+                        len > best_len || (len == best_len && *allow && !best_allow)
+                    }
+                };
+                if better {
+                    best = Some((len, *allow));
+                }
+            }
+        }
+
+        best.is_none_or(|(_, allow)| allow)
+    }
+}
+
+/// Match a robots.txt path pattern (`*` wildcard, optional trailing `$` anchor)
+fn robots_pattern_matches(pattern: &str, path: &str) -> bool {
+    let (pattern, anchored) = match pattern.strip_suffix('$') {
+        Some(p) => (p, true),
+        None => (pattern, false),
+    };
+
+    let mut regex = String::from("^");
+    for (i, part) in pattern.split('*').enumerate() {
+        if i > 0 {
+            regex.push_str(".*");
+        }
+        regex.push_str(&regex::escape(part));
+    }
+    if anchored {
+        regex.push('$');
+    }
+
+    Regex::new(&regex).is_ok_and(|re| re.is_match(path))
+}
+
+/// Fetch robots.txt for the crawl origin; a missing file allows everything
+async fn fetch_robots(client: &reqwest::Client, start: &Url) -> RobotsRules {
+    let Ok(robots_url) = start.join("/robots.txt") else {
+        return RobotsRules::default();
+    };
+
+    match fetch_page(client, &robots_url).await {
+        Ok((body, _)) => RobotsRules::parse(&body),
+        Err(_) => RobotsRules::default(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    #[test]
+    fn test_robots_rules() {
+        let robots = RobotsRules::parse(
+            "User-agent: *\nDisallow: /private/\nAllow: /private/public\nDisallow: /*.pdf$\n",
+        );
+
+        assert!(robots.is_allowed("/docs/index.html"));
+        assert!(!robots.is_allowed("/private/secret.html"));
+        assert!(robots.is_allowed("/private/public/page.html"));
+        assert!(!robots.is_allowed("/files/spec.pdf"));
+        assert!(robots.is_allowed("/files/spec.pdf.html"));
+    }
+
+    #[test]
+    fn test_robots_prefers_ctx_group() {
+        let robots = RobotsRules::parse(
+            "User-agent: *\nDisallow: /\n\nUser-agent: ctx\nUser-agent: other\nDisallow: /tmp\n",
+        );
+
+        assert!(robots.is_allowed("/docs"));
+        assert!(!robots.is_allowed("/tmp/file"));
+    }
+
+    #[test]
+    fn test_extract_links() {
+        let base = Url::parse("https://example.com/docs/index.html").unwrap();
+        let html = r##"<a href="a.html">A</a> <A class="x" HREF='/b.html#top'>B</A>
+            <a href="https://other.com/c">C</a> <a href="mailto:me@example.com">M</a>"##;
+
+        let links: Vec<String> = extract_links(&base, html)
+            .into_iter()
+            .map(|u| u.to_string())
+            .collect();
+
+        assert_eq!(
+            links,
+            vec![
+                "https://example.com/docs/a.html",
+                "https://example.com/b.html",
+                "https://other.com/c",
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_parse_crawl_uri() {
+        let artifact = CrawlHandler
+            .parse(
+                "crawl:https://example.com/docs/ --depth=1 --max-pages=5 --include=/docs/**",
+                SourceOptions::default(),
+            )
+            .await
+            .unwrap();
+
+        match artifact.artifact_type {
+            ArtifactType::Crawl {
+                url,
+                max_depth,
+                max_pages,
+                include,
+                exclude,
+            } => {
+                assert_eq!(url, "https://example.com/docs/");
+                assert_eq!(max_depth, 1);
+                assert_eq!(max_pages, 5);
+                assert_eq!(include, vec!["/docs/**"]);
+                assert!(exclude.is_empty());
+            }
+            other => panic!("Expected Crawl type, got {:?}", other),
+        }
+
+        assert!(
+            CrawlHandler
+                .parse("crawl:ftp://example.com", SourceOptions::default())
+                .await
+                .is_err()
+        );
+    }
+
+    /// Serve a fixed set of pages over HTTP on a random local port
+    async fn serve_site(pages: HashMap<&'static str, &'static str>) -> String {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+
+        tokio::spawn(async move {
+            while let Ok((mut socket, _)) = listener.accept().await {
+                let mut buf = vec![0; 4096];
+                let n = socket.read(&mut buf).await.unwrap_or(0);
+                let request = String::from_utf8_lossy(&buf[..n]);
+                let path = request.split_whitespace().nth(1).unwrap_or("/");
+
+                let response = match pages.get(path) {
+                    Some(body) => {
+                        let content_type = if path.ends_with(".txt") {
+                            "text/plain"
+                        } else if path.ends_with(".bin") {
+                            "application/octet-stream"
+                        } else {
+                            "text/html"
+                        };
+                        format!(
+                            "HTTP/1.1 200 OK\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                            content_type,
+                            body.len(),
+                            body
+                        )
+                    }
+                    None => {
+                        "HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\nConnection: close\r\n\r\n"
+                            .to_string()
+                    }
+                };
+                let _ = socket.write_all(response.as_bytes()).await;
+            }
+        });
+
+        format!("http://{}", addr)
+    }
+
+    #[tokio::test]
+    async fn test_expand_crawl() {
+        let base = serve_site(HashMap::from([
+            ("/robots.txt", "User-agent: *\nDisallow: /docs/private"),
+            (
+                "/docs/index.html",
+                r#"<title>Index</title><a href="a.html">A</a><a href="b.html">B</a>
+                   <a href="private.html">P</a><a href="/blog/post.html">Blog</a>
+                   <a href="https://example.com/docs/x.html">External</a>"#,
+            ),
+            (
+                "/docs/a.html",
+                r#"<title>A</title>Page A <a href="c.html">C</a>"#,
+            ),
+            ("/docs/b.html", "<title>B</title>Page B"),
+            ("/docs/c.html", "<title>C</title>Page C"),
+            ("/docs/private.html", "Private"),
+            ("/blog/post.html", "Blog post"),
+        ]))
+        .await;
+
+        let crawl = CrawlHandler
+            .expand_crawl(
+                &format!("{}/docs/index.html", base),
+                1,
+                10,
+                &["/docs/**".to_string()],
+                &[],
+            )
+            .await
+            .unwrap();
+        let pages = crawl.pages;
+
+        let urls: Vec<String> = pages
+            .iter()
+            .map(|p| p.url.trim_start_matches(&base).to_string())
+            .collect();
+        assert_eq!(
+            urls,
+            vec!["/docs/a.html", "/docs/b.html", "/docs/index.html"]
+        );
+        assert_eq!(pages[0].title.as_deref(), Some("A"));
+        assert!(pages[0].content.starts_with("# A"));
+
+        // Depth 2 reaches c.html; the page limit still applies
+        let crawl = CrawlHandler
+            .expand_crawl(&format!("{}/docs/index.html", base), 2, 3, &[], &[])
+            .await
+            .unwrap();
+        assert_eq!(crawl.pages.len(), 3);
+        assert!(crawl.pages.iter().all(|p| !p.url.contains("private")));
+    }
+
+    #[tokio::test]
+    async fn test_crawl_failures() {
+        let huge: &'static str = "x".repeat(MAX_PAGE_BYTES + 1).leak();
+        let base = serve_site(HashMap::from([
+            ("/robots.txt", "User-agent: *\nDisallow: /docs/secret"),
+            (
+                "/docs/index.html",
+                r#"<a href="gone.html">Gone</a> <a href="huge.txt">Huge</a> <a href="ok.html">OK</a>
+                   <a href="huge.bin">Binary</a>"#,
+            ),
+            ("/docs/huge.txt", huge),
+            ("/docs/huge.bin", huge),
+            ("/docs/ok.html", "OK"),
+            ("/docs/secret.html", "Secret"),
+        ]))
+        .await;
+
+        // Missing and oversized pages are reported, not silently dropped. Non-text pages
+        // aren't downloaded, so their size doesn't matter.
+        let crawl = CrawlHandler
+            .expand_crawl(&format!("{}/docs/index.html", base), 1, 10, &[], &[])
+            .await
+            .unwrap();
+        assert_eq!(crawl.pages.len(), 2);
+        assert_eq!(
+            crawl.warnings,
+            vec![
+                format!("Skipped {}/docs/gone.html: HTTP error 404", base),
+                format!(
+                    "Skipped {}/docs/huge.txt: larger than {} bytes",
+                    base, MAX_PAGE_BYTES
+                ),
+            ]
+        );
+
+        // Without a start page there's no crawl at all
+        let err = CrawlHandler
+            .expand_crawl(&format!("{}/docs/missing.html", base), 1, 10, &[], &[])
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("HTTP error 404"));
+
+        // Nor with one robots.txt disallows
+        let err = CrawlHandler
+            .expand_crawl(&format!("{}/docs/secret.html", base), 1, 10, &[], &[])
+            .await
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            format!(
+                "Other error: Failed to fetch {}/docs/secret.html: disallowed by robots.txt",
+                base
+            )
+        );
+    }
+}
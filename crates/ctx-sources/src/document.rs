@@ -0,0 +1,390 @@
+use std::io::{Cursor, Read};
+
+use ctx_core::{Error, Result};
+use quick_xml::Reader;
+use quick_xml::events::Event;
+
+/// File extensions collected by `docs_dir:`
+pub const DOCUMENT_EXTENSIONS: &[&str] = &["md", "markdown", "pdf", "docx", "ipynb"];
+
+/// Binary or structured formats that need text extraction before rendering
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DocumentFormat {
+    Pdf,
+    Docx,
+    Notebook,
+}
+
+impl DocumentFormat {
+    pub fn from_path(path: &str) -> Option<Self> {
+        let ext = std::path::Path::new(path)
+            .extension()?
+            .to_str()?
+            .to_ascii_lowercase();
+        match ext.as_str() {
+            "pdf" => Some(Self::Pdf),
+            "docx" => Some(Self::Docx),
+            "ipynb" => Some(Self::Notebook),
+            _ => None,
+        }
+    }
+
+    pub fn mime_type(&self) -> &'static str {
+        match self {
+            Self::Pdf => "application/pdf",
+            Self::Docx => "application/vnd.openxmlformats-officedocument.wordprocessingml.document",
+            Self::Notebook => "application/x-ipynb+json",
+        }
+    }
+}
+
+/// Read a document from disk and extract its text
+pub async fn load_document(
+    path: &str,
+    format: DocumentFormat,
+    strip_outputs: bool,
+) -> Result<String> {
+    let bytes = tokio::fs::read(path)
+        .await
+        .map_err(|e| Error::Other(anyhow::anyhow!("Failed to read file {}: {}", path, e)))?;
+
+    let path_owned = path.to_string();
+    tokio::task::spawn_blocking(move || match format {
+        DocumentFormat::Pdf => extract_pdf(&bytes),
+        DocumentFormat::Docx => extract_docx(&bytes),
+        DocumentFormat::Notebook => {
+            let json = String::from_utf8_lossy(&bytes);
+            render_notebook(&json, strip_outputs)
+        }
+    })
+    .await
+    .map_err(|e| {
+        Error::Other(anyhow::anyhow!(
+            "Text extraction panicked for {}: {}",
+            path_owned,
+            e
+        ))
+    })?
+    .map_err(|e| Error::Other(anyhow::anyhow!("Failed to extract {}: {}", path_owned, e)))
+}
+
+/// Extract plain text from a PDF
+pub fn extract_pdf(bytes: &[u8]) -> Result<String> {
+    let text = pdf_extract::extract_text_from_mem(bytes)
+        .map_err(|e| Error::Other(anyhow::anyhow!("Invalid PDF: {}", e)))?;
+
+    // pdf-extract pads pages with blank lines; collapse runs of them
+    let mut out = String::new();
+    let mut blank_run = 0;
+    for line in text.lines() {
+        let line = line.trim_end();
+        if line.is_empty() {
+            blank_run += 1;
+            if blank_run > 1 {
+                continue;
+            }
+        } else {
+            blank_run = 0;
+        }
+        out.push_str(line);
+        out.push('\n');
+    }
+    Ok(out.trim().to_string())
+}
+
+/// Extract paragraph text from a DOCX (`word/document.xml`)
+pub fn extract_docx(bytes: &[u8]) -> Result<String> {
+    let mut archive = zip::ZipArchive::new(Cursor::new(bytes))
+        .map_err(|e| Error::Other(anyhow::anyhow!("Invalid DOCX archive: {}", e)))?;
+    let mut xml = String::new();
+    archive
+        .by_name("word/document.xml")
+        .map_err(|e| Error::Other(anyhow::anyhow!("DOCX is missing document.xml: {}", e)))?
+        .read_to_string(&mut xml)
+        .map_err(|e| Error::Other(anyhow::anyhow!("Failed to read document.xml: {}", e)))?;
+
+    let mut reader = Reader::from_str(&xml);
+    let mut paragraphs = Vec::new();
+    let mut current = String::new();
+    let mut heading_level = 0usize;
+    let mut in_text = false;
+
+    loop {
+        match reader.read_event() {
+            Ok(Event::Start(e)) => match e.name().as_ref() {
+                b"w:t" => in_text = true,
+                b"w:p" => {
+                    current.clear();
+                    heading_level = 0;
+                }
+                _ => {}
+            },
+            Ok(Event::Empty(e)) => match e.name().as_ref() {
+                b"w:tab" => current.push('\t'),
+                b"w:br" | b"w:cr" => current.push('\n'),
+                b"w:pStyle" => {
+                    for attr in e.attributes().flatten() {
+                        if attr.key.as_ref() == b"w:val" {
+                            heading_level = heading_level_from_style(&attr.value);
+                        }
+                    }
+                }
+                _ => {}
+            },
+            Ok(Event::Text(t)) if in_text => {
+                let text = t
+                    .unescape()
+                    .map_err(|e| Error::Other(anyhow::anyhow!("Invalid DOCX text: {}", e)))?;
+                current.push_str(&text);
+            }
+            Ok(Event::End(e)) => match e.name().as_ref() {
+                b"w:t" => in_text = false,
+                b"w:p" => {
+                    let text = current.trim_end();
+                    if !text.is_empty() {
+                        if heading_level > 0 {
+                            paragraphs.push(format!("{} {}", "#".repeat(heading_level), text));
+                        } else {
+                            paragraphs.push(text.to_string());
+                        }
+                    }
+                    current.clear();
+                }
+                _ => {}
+            },
+            Ok(Event::Eof) => break,
+            Err(e) => {
+                return Err(Error::Other(anyhow::anyhow!(
+                    "Invalid DOCX XML at {}: {}",
+                    reader.buffer_position(),
+                    e
+                )));
+            }
+            _ => {}
+        }
+    }
+
+    Ok(paragraphs.join("\n\n"))
+}
+
+/// Map Word styles like `Heading2` or `Title` to a markdown heading level
+fn heading_level_from_style(style: &[u8]) -> usize {
+    let style = String::from_utf8_lossy(style);
+    if style == "Title" {
+        return 1;
+    }
+    style
+        .strip_prefix("Heading")
+        .and_then(|n| n.parse::<usize>().ok())
+        .map(|n| n.clamp(1, 6))
+        .unwrap_or(0)
+}
+
+/// Render a Jupyter notebook as markdown with fenced code cells
+pub fn render_notebook(json: &str, strip_outputs: bool) -> Result<String> {
+    let notebook: serde_json::Value = serde_json::from_str(json)
+        .map_err(|e| Error::Other(anyhow::anyhow!("Invalid notebook JSON: {}", e)))?;
+
+    let language = notebook
+        .pointer("/metadata/language_info/name")
+        .or_else(|| notebook.pointer("/metadata/kernelspec/language"))
+        .and_then(|v| v.as_str())
+        .unwrap_or("python");
+
+    let cells = notebook
+        .get("cells")
+        .and_then(|c| c.as_array())
+        .ok_or_else(|| Error::Other(anyhow::anyhow!("Notebook has no cells")))?;
+
+    let mut blocks = Vec::new();
+    for cell in cells {
+        let source = join_text(cell.get("source"));
+        match cell.get("cell_type").and_then(|t| t.as_str()) {
+            Some("code") => {
+                if !source.trim().is_empty() {
+                    blocks.push(format!("```{}\n{}\n```", language, source.trim_end()));
+                }
+                if !strip_outputs {
+                    let outputs = cell
+                        .get("outputs")
+                        .and_then(|o| o.as_array())
+                        .map(|o| o.as_slice())
+                        .unwrap_or_default();
+                    let text: String = outputs.iter().map(output_text).collect();
+                    if !text.trim().is_empty() {
+                        blocks.push(format!("Output:\n```\n{}\n```", text.trim_end()));
+                    }
+                }
+            }
+            _ => {
+                if !source.trim().is_empty() {
+                    blocks.push(source.trim_end().to_string());
+                }
+            }
+        }
+    }
+
+    Ok(blocks.join("\n\n"))
+}
+
+/// Text content of a single code cell output (images and HTML are skipped)
+fn output_text(output: &serde_json::Value) -> String {
+    match output.get("output_type").and_then(|t| t.as_str()) {
+        Some("stream") => join_text(output.get("text")),
+        Some("execute_result") | Some("display_data") => {
+            join_text(output.pointer("/data/text~1plain"))
+        }
+        Some("error") => {
+            let name = output.get("ename").and_then(|v| v.as_str()).unwrap_or("");
+            let value = output.get("evalue").and_then(|v| v.as_str()).unwrap_or("");
+            format!("{}: {}\n", name, value)
+        }
+        _ => String::new(),
+    }
+}
+
+/// Notebook text fields are either a string or a list of lines
+fn join_text(value: Option<&serde_json::Value>) -> String {
+    match value {
+        Some(serde_json::Value::String(s)) => s.clone(),
+        Some(serde_json::Value::Array(lines)) => {
+            lines.iter().filter_map(|l| l.as_str()).collect::<String>()
+        }
+        _ => String::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    #[test]
+    fn test_format_from_path() {
+        assert_eq!(
+            DocumentFormat::from_path("/a/b/Report.PDF"),
+            Some(DocumentFormat::Pdf)
+        );
+        assert_eq!(
+            DocumentFormat::from_path("spec.docx"),
+            Some(DocumentFormat::Docx)
+        );
+        assert_eq!(
+            DocumentFormat::from_path("analysis.ipynb"),
+            Some(DocumentFormat::Notebook)
+        );
+        assert_eq!(DocumentFormat::from_path("main.rs"), None);
+    }
+
+    #[test]
+    fn test_render_notebook() {
+        let nb = r##"{
+            "metadata": {"language_info": {"name": "python"}},
+            "cells": [
+                {"cell_type": "markdown", "source": ["# Analysis\n", "Intro text"]},
+                {"cell_type": "code", "source": "print(1 + 1)", "outputs": [
+                    {"output_type": "stream", "name": "stdout", "text": ["2\n"]},
+                    {"output_type": "display_data", "data": {"image/png": "AAAA"}}
+                ]},
+                {"cell_type": "code", "source": "x", "outputs": [
+                    {"output_type": "execute_result", "data": {"text/plain": "42"}}
+                ]}
+            ]
+        }"##;
+
+        let rendered = render_notebook(nb, false).unwrap();
+        assert!(rendered.starts_with("# Analysis\nIntro text"));
+        assert!(rendered.contains("```python\nprint(1 + 1)\n```"));
+        assert!(rendered.contains("Output:\n```\n2\n```"));
+        assert!(rendered.contains("Output:\n```\n42\n```"));
+        assert!(!rendered.contains("AAAA"));
+
+        let stripped = render_notebook(nb, true).unwrap();
+        assert!(stripped.contains("```python\nprint(1 + 1)\n```"));
+        assert!(!stripped.contains("Output:"));
+    }
+
+    #[test]
+    fn test_extract_docx() {
+        let xml = r#"<?xml version="1.0" encoding="UTF-8"?>
+<w:document xmlns:w="http://schemas.openxmlformats.org/wordprocessingml/2006/main">
+  <w:body>
+    <w:p><w:pPr><w:pStyle w:val="Heading1"/></w:pPr><w:r><w:t>Design</w:t></w:r></w:p>
+    <w:p><w:r><w:t xml:space="preserve">Tokens &amp; </w:t></w:r><w:r><w:t>budgets</w:t></w:r></w:p>
+    <w:p></w:p>
+    <w:p><w:r><w:t>a</w:t><w:tab/><w:t>b</w:t></w:r></w:p>
+  </w:body>
+</w:document>"#;
+
+        let mut buf = Cursor::new(Vec::new());
+        {
+            let mut zip = zip::ZipWriter::new(&mut buf);
+            let opts = zip::write::SimpleFileOptions::default()
+                .compression_method(zip::CompressionMethod::Stored);
+            zip.start_file("word/document.xml", opts).unwrap();
+            zip.write_all(xml.as_bytes()).unwrap();
+            zip.finish().unwrap();
+        }
+
+        let text = extract_docx(buf.get_ref()).unwrap();
+        assert_eq!(text, "# Design\n\nTokens & budgets\n\na\tb");
+    }
+
+    #[test]
+    fn test_extract_docx_rejects_garbage() {
+        assert!(extract_docx(b"not a zip").is_err());
+    }
+
+    #[test]
+    fn test_extract_pdf() {
+        use lopdf::content::{Content, Operation};
+        use lopdf::{Document, Object, Stream, dictionary};
+
+        let mut doc = Document::with_version("1.5");
+        let pages_id = doc.new_object_id();
+        let font_id = doc.add_object(dictionary! {
+            "Type" => "Font",
+            "Subtype" => "Type1",
+            "BaseFont" => "Helvetica",
+        });
+        let resources_id = doc.add_object(dictionary! {
+            "Font" => dictionary! { "F1" => font_id },
+        });
+        let content = Content {
+            operations: vec![
+                Operation::new("BT", vec![]),
+                Operation::new("Tf", vec!["F1".into(), 24.into()]),
+                Operation::new("Td", vec![100.into(), 600.into()]),
+                Operation::new("Tj", vec![Object::string_literal("Hello PDF")]),
+                Operation::new("ET", vec![]),
+            ],
+        };
+        let content_id = doc.add_object(Stream::new(dictionary! {}, content.encode().unwrap()));
+        let page_id = doc.add_object(dictionary! {
+            "Type" => "Page",
+            "Parent" => pages_id,
+            "Contents" => content_id,
+        });
+        doc.objects.insert(
+            pages_id,
+            Object::Dictionary(dictionary! {
+                "Type" => "Pages",
+                "Kids" => vec![page_id.into()],
+                "Count" => 1,
+                "Resources" => resources_id,
+                "MediaBox" => vec![0.into(), 0.into(), 595.into(), 842.into()],
+            }),
+        );
+        let catalog_id = doc.add_object(dictionary! {
+            "Type" => "Catalog",
+            "Pages" => pages_id,
+        });
+        doc.trailer.set("Root", catalog_id);
+
+        let mut bytes = Vec::new();
+        doc.save_to(&mut bytes).unwrap();
+
+        let text = extract_pdf(&bytes).unwrap();
+        assert!(text.contains("Hello PDF"), "got: {:?}", text);
+    }
+}
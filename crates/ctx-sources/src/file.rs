@@ -1,9 +1,117 @@
 use async_trait::async_trait;
-use ctx_core::{Artifact, ArtifactMetadata, ArtifactType, Error, Result};
+use ctx_core::{Artifact, ArtifactMetadata, ArtifactType, Error, LineWindow, Result, TextEncoding};
+use std::io::SeekFrom;
+use tokio::io::{AsyncReadExt, AsyncSeekExt};
 
+use crate::anchor;
+use crate::document::{self, DocumentFormat};
 use crate::handler::{SourceHandler, SourceOptions};
+use crate::metadata;
+use crate::tree::format_size;
 
-pub struct FileHandler;
+/// Leading bytes checked for NUL when deciding whether a file is binary (same as git)
+const BINARY_SNIFF_BYTES: usize = 8000;
+/// Read size when scanning a file too large to load whole for `?head=`/`?tail=`
+const WINDOW_CHUNK: usize = 64 * 1024;
+
+/// Size limit and default decoding for file sources
+#[derive(Debug, Clone)]
+pub struct FileSettings {
+    /// Larger files are refused unless read with `?head=N` or `?tail=N`
+    pub max_file_bytes: u64,
+    /// Used when the URI has no `?encoding=`
+    pub encoding: TextEncoding,
+}
+
+impl Default for FileSettings {
+    fn default() -> Self {
+        Self {
+            max_file_bytes: 10 * 1024 * 1024,
+            encoding: TextEncoding::Utf8,
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct FileHandler {
+    settings: FileSettings,
+}
+
+impl FileHandler {
+    pub fn new(settings: FileSettings) -> Self {
+        Self { settings }
+    }
+
+    async fn file_size(&self, path: &str) -> Result<u64> {
+        let metadata = tokio::fs::metadata(path)
+            .await
+            .map_err(|e| Error::Other(anyhow::anyhow!("Failed to read file {}: {}", path, e)))?;
+        Ok(metadata.len())
+    }
+
+    fn too_large(&self, path: &str, size: u64) -> Error {
+        Error::Other(anyhow::anyhow!(
+            "{} is {}, over the {} file limit. Add ?tail=N or ?head=N to include part of it, or raise `[files] max_file_bytes` in ~/.ctx/config.toml",
+            path,
+            format_size(size),
+            format_size(self.settings.max_file_bytes)
+        ))
+    }
+
+    async fn check_document_size(&self, path: &str) -> Result<()> {
+        let size = self.file_size(path).await?;
+        if size > self.settings.max_file_bytes {
+            return Err(self.too_large(path, size));
+        }
+        Ok(())
+    }
+
+    /// Raw bytes of a document, within the size limit
+    async fn read_document(&self, path: &str) -> Result<Vec<u8>> {
+        self.check_document_size(path).await?;
+        tokio::fs::read(path)
+            .await
+            .map_err(|e| Error::Other(anyhow::anyhow!("Failed to read file {}: {}", path, e)))
+    }
+
+    /// Decoded text of a file, or of its first/last lines
+    pub(crate) async fn read_text(
+        &self,
+        path: &str,
+        window: Option<LineWindow>,
+        encoding: Option<TextEncoding>,
+    ) -> Result<String> {
+        let encoding = encoding.unwrap_or(self.settings.encoding);
+        let size = self.file_size(path).await?;
+        let max = self.settings.max_file_bytes;
+
+        let bytes = if size <= max {
+            tokio::fs::read(path)
+                .await
+                .map_err(|e| Error::Other(anyhow::anyhow!("Failed to read file {}: {}", path, e)))?
+        } else {
+            let Some(window) = window else {
+                return Err(self.too_large(path, size));
+            };
+            // Lines are found by scanning for b'\n', which UTF-16 doesn't encode as one byte
+            if matches!(
+                encoding,
+                TextEncoding::Utf16 | TextEncoding::Utf16le | TextEncoding::Utf16be
+            ) {
+                return Err(self.too_large(path, size));
+            }
+            read_window(path, window, size, max as usize)
+                .await
+                .map_err(|e| Error::Other(anyhow::anyhow!("Failed to read file {}: {}", path, e)))?
+        };
+
+        let text = decode(path, &bytes, encoding)?;
+        Ok(match window {
+            Some(window) => apply_window(&text, window),
+            None => text,
+        })
+    }
+}
 
 #[async_trait]
 impl SourceHandler for FileHandler {
@@ -14,6 +122,13 @@ impl SourceHandler for FileHandler {
             uri
         };
 
+        // Options after `?` (e.g., notebook.ipynb?outputs=false, app.log?tail=500)
+        let (path, query) = match path.split_once('?') {
+            Some((path, query)) => (path, Some(query)),
+            None => (path, None),
+        };
+        let query = parse_query(query)?;
+
         // Check if path has line range (e.g., file.txt#L10-L20)
         let (relative_path, range) = if let Some((path, range_str)) = path.split_once("#L") {
             let range = parse_line_range(range_str)?;
@@ -34,33 +149,99 @@ impl SourceHandler for FileHandler {
             .to_string_lossy()
             .to_string();
 
-        // Read file to compute hash and metadata
-        let content = tokio::fs::read_to_string(&file_path).await.map_err(|e| {
-            Error::Other(anyhow::anyhow!("Failed to read file {}: {}", file_path, e))
-        })?;
+        let format = DocumentFormat::from_path(&file_path);
+        if let Some(format) = format {
+            if range.is_some() {
+                return Err(Error::InvalidSourceUri(format!(
+                    "Line ranges are not supported for {} files",
+                    format.mime_type()
+                )));
+            }
+            if query.window.is_some() || query.encoding.is_some() {
+                return Err(Error::InvalidSourceUri(format!(
+                    "head, tail and encoding options are not supported for {} files",
+                    format.mime_type()
+                )));
+            }
+        } else if query.strip_outputs {
+            return Err(Error::InvalidSourceUri(
+                "outputs=false only applies to .ipynb notebooks".to_string(),
+            ));
+        }
+        if range.is_some() && query.window.is_some() {
+            return Err(Error::InvalidSourceUri(
+                "Use either a line range or head/tail, not both".to_string(),
+            ));
+        }
 
-        let content_hash = blake3::hash(content.as_bytes()).to_hex().to_string();
+        // Read file to compute hash and metadata (documents are binary, so hash raw bytes)
+        let bytes = if format.is_some() {
+            self.read_document(&file_path).await?
+        } else {
+            self.read_text(&file_path, query.window, query.encoding)
+                .await?
+                .into_bytes()
+        };
+
+        let content_hash = blake3::hash(&bytes).to_hex().to_string();
+        // Text as the artifact will render it, for line counts and language detection
+        let mut rendered = match format {
+            Some(_) => None,
+            None => Some(String::from_utf8_lossy(&bytes).into_owned()),
+        };
 
-        let artifact_type = if let Some((start, end)) = range {
+        let artifact_type = if let Some(format) = format {
+            match format {
+                DocumentFormat::Pdf => ArtifactType::Pdf {
+                    path: file_path.clone(),
+                },
+                DocumentFormat::Docx => ArtifactType::Docx {
+                    path: file_path.clone(),
+                },
+                DocumentFormat::Notebook => ArtifactType::Notebook {
+                    path: file_path.clone(),
+                    strip_outputs: query.strip_outputs,
+                },
+            }
+        } else if let Some((start, end)) = range {
+            let text = String::from_utf8_lossy(&bytes);
+            let lines: Vec<&str> = text.lines().collect();
+            let anchor = anchor::anchor_range(&lines, start, end);
+            rendered = lines
+                .get(start..=end.min(lines.len().saturating_sub(1)))
+                .map(|range| range.join("\n"));
             ArtifactType::FileRange {
                 path: file_path.clone(),
                 start,
                 end,
+                encoding: query.encoding,
+                anchor: Some(anchor),
             }
-        } else if file_path.ends_with(".md") {
+        } else if file_path.ends_with(".md") && query.window.is_none() && query.encoding.is_none() {
             ArtifactType::Markdown {
                 path: file_path.clone(),
             }
         } else {
             ArtifactType::File {
                 path: file_path.clone(),
+                window: query.window,
+                encoding: query.encoding,
             }
         };
 
+        // Sized like the facts below: what renders, so a range doesn't count the whole file
+        let size_bytes = rendered.as_ref().map_or(bytes.len(), String::len);
+        let file = rendered.map(|text| metadata::file_info(&file_path, &text));
+        let mime_type = match format {
+            Some(format) => format.mime_type().to_string(),
+            None => metadata::mime_type(file.as_ref().and_then(|f| f.language.as_deref())),
+        };
         let metadata = ArtifactMetadata {
-            size_bytes: content.len(),
-            mime_type: None,
+            size_bytes,
+            mime_type: Some(mime_type),
+            file,
             extra: serde_json::json!({}),
+            ..Default::default()
         };
 
         Ok(Artifact::new(artifact_type, uri.to_string())
@@ -70,15 +251,20 @@ impl SourceHandler for FileHandler {
 
     async fn load(&self, artifact: &Artifact) -> Result<String> {
         match &artifact.artifact_type {
-            ArtifactType::File { path } | ArtifactType::Markdown { path } => {
-                tokio::fs::read_to_string(path).await.map_err(|e| {
-                    Error::Other(anyhow::anyhow!("Failed to read file {}: {}", path, e))
-                })
-            }
-            ArtifactType::FileRange { path, start, end } => {
-                let content = tokio::fs::read_to_string(path).await.map_err(|e| {
-                    Error::Other(anyhow::anyhow!("Failed to read file {}: {}", path, e))
-                })?;
+            ArtifactType::File {
+                path,
+                window,
+                encoding,
+            } => self.read_text(path, *window, *encoding).await,
+            ArtifactType::Markdown { path } => self.read_text(path, None, None).await,
+            ArtifactType::FileRange {
+                path,
+                start,
+                end,
+                encoding,
+                ..
+            } => {
+                let content = self.read_text(path, None, *encoding).await?;
 
                 let lines: Vec<_> = content.lines().collect();
                 if *start >= lines.len() || *end >= lines.len() {
@@ -93,6 +279,21 @@ impl SourceHandler for FileHandler {
 
                 Ok(lines[*start..=*end].join("\n"))
             }
+            ArtifactType::Pdf { path } => {
+                self.check_document_size(path).await?;
+                document::load_document(path, DocumentFormat::Pdf, false).await
+            }
+            ArtifactType::Docx { path } => {
+                self.check_document_size(path).await?;
+                document::load_document(path, DocumentFormat::Docx, false).await
+            }
+            ArtifactType::Notebook {
+                path,
+                strip_outputs,
+            } => {
+                self.check_document_size(path).await?;
+                document::load_document(path, DocumentFormat::Notebook, *strip_outputs).await
+            }
             _ => Err(Error::Other(anyhow::anyhow!(
                 "Unsupported artifact type for FileHandler"
             ))),
@@ -104,6 +305,218 @@ impl SourceHandler for FileHandler {
     }
 }
 
+/// Options from a file URI's `?key=value&...` suffix
+#[derive(Debug, Default)]
+struct FileQuery {
+    /// Notebooks only
+    strip_outputs: bool,
+    window: Option<LineWindow>,
+    encoding: Option<TextEncoding>,
+}
+
+fn parse_query(query: Option<&str>) -> Result<FileQuery> {
+    let mut parsed = FileQuery::default();
+    for pair in query.unwrap_or("").split('&').filter(|p| !p.is_empty()) {
+        match pair.split_once('=') {
+            Some(("outputs", "false")) => parsed.strip_outputs = true,
+            Some(("outputs", "true")) => parsed.strip_outputs = false,
+            Some((key @ ("head" | "tail"), n)) => {
+                let n = n.parse::<usize>().ok().filter(|n| *n > 0).ok_or_else(|| {
+                    Error::InvalidSourceUri(format!(
+                        "{} must be a positive number of lines: {}",
+                        key, pair
+                    ))
+                })?;
+                if parsed.window.is_some() {
+                    return Err(Error::InvalidSourceUri(
+                        "Use either head or tail, not both".to_string(),
+                    ));
+                }
+                parsed.window = Some(if key == "head" {
+                    LineWindow::Head(n)
+                } else {
+                    LineWindow::Tail(n)
+                });
+            }
+            Some(("encoding", name)) => {
+                parsed.encoding = Some(name.parse().map_err(Error::InvalidSourceUri)?);
+            }
+            _ => {
+                return Err(Error::InvalidSourceUri(format!(
+                    "Unknown file option: {}",
+                    pair
+                )));
+            }
+        }
+    }
+    Ok(parsed)
+}
+
+/// Keep the first or last `n` lines
+fn apply_window(text: &str, window: LineWindow) -> String {
+    let lines: Vec<&str> = text.lines().collect();
+    match window {
+        LineWindow::Head(n) => lines[..n.min(lines.len())].join("\n"),
+        LineWindow::Tail(n) => lines[lines.len().saturating_sub(n)..].join("\n"),
+    }
+}
+
+/// Read about enough whole lines from the start or end of a large file, at most `max` bytes
+async fn read_window(
+    path: &str,
+    window: LineWindow,
+    size: u64,
+    max: usize,
+) -> std::io::Result<Vec<u8>> {
+    let mut file = tokio::fs::File::open(path).await?;
+    let newlines = |bytes: &[u8]| bytes.iter().filter(|b| **b == b'\n').count();
+
+    match window {
+        LineWindow::Head(n) => {
+            let mut chunk = vec![0u8; WINDOW_CHUNK];
+            let mut buf = Vec::new();
+            let mut lines = 0;
+            while lines < n && buf.len() < max {
+                let read = file.read(&mut chunk).await?;
+                if read == 0 {
+                    break;
+                }
+                lines += newlines(&chunk[..read]);
+                buf.extend_from_slice(&chunk[..read]);
+            }
+            // The file is larger than `max`, so a full buffer ends in a line cut off by it
+            if buf.len() >= max {
+                buf.truncate(max);
+                if let Some(last) = buf.iter().rposition(|b| *b == b'\n') {
+                    buf.truncate(last);
+                }
+            }
+            Ok(buf)
+        }
+        LineWindow::Tail(n) => {
+            // Read backwards a chunk at a time, last chunk first
+            let mut chunks: Vec<Vec<u8>> = Vec::new();
+            let mut pos = size;
+            let mut read = 0;
+            let mut lines = 0;
+            // The file's trailing newline doesn't end a complete line of its own
+            let mut trailing = 0;
+            while pos > 0 && lines - trailing < n && read < max {
+                let len = (pos as usize).min(WINDOW_CHUNK);
+                pos -= len as u64;
+                file.seek(SeekFrom::Start(pos)).await?;
+                let mut chunk = vec![0u8; len];
+                file.read_exact(&mut chunk).await?;
+                if chunks.is_empty() && chunk.ends_with(b"\n") {
+                    trailing = 1;
+                }
+                lines += newlines(&chunk);
+                read += len;
+                chunks.push(chunk);
+            }
+            let mut buf = Vec::with_capacity(read);
+            for chunk in chunks.iter().rev() {
+                buf.extend_from_slice(chunk);
+            }
+
+            let mut start = 0;
+            if buf.len() > max {
+                start = buf.len() - max;
+            }
+            // Drop the partial line in front, unless it starts the file
+            if pos > 0 || start > 0 {
+                start = buf[start..]
+                    .iter()
+                    .position(|b| *b == b'\n')
+                    .map(|i| start + i + 1)
+                    .unwrap_or(buf.len());
+            }
+            buf.drain(..start);
+            Ok(buf)
+        }
+    }
+}
+
+/// Decode file bytes, refusing binary files and (for strict UTF-8) invalid text
+fn decode(path: &str, bytes: &[u8], encoding: TextEncoding) -> Result<String> {
+    let utf16_bom = bytes.starts_with(&[0xFF, 0xFE]) || bytes.starts_with(&[0xFE, 0xFF]);
+
+    match encoding {
+        TextEncoding::Utf16 => Ok(match bytes {
+            [0xFE, 0xFF, rest @ ..] => decode_utf16(rest, true),
+            [0xFF, 0xFE, rest @ ..] => decode_utf16(rest, false),
+            _ => decode_utf16(bytes, false),
+        }),
+        TextEncoding::Utf16le => Ok(decode_utf16(
+            bytes.strip_prefix(&[0xFF, 0xFE]).unwrap_or(bytes),
+            false,
+        )),
+        TextEncoding::Utf16be => Ok(decode_utf16(
+            bytes.strip_prefix(&[0xFE, 0xFF]).unwrap_or(bytes),
+            true,
+        )),
+        TextEncoding::Auto if utf16_bom => decode(path, bytes, TextEncoding::Utf16),
+        TextEncoding::Auto => {
+            let bytes = bytes.strip_prefix(&[0xEF, 0xBB, 0xBF]).unwrap_or(bytes);
+            check_not_binary(path, bytes)?;
+            Ok(match std::str::from_utf8(bytes) {
+                Ok(text) => text.to_string(),
+                Err(_) => decode_latin1(bytes),
+            })
+        }
+        TextEncoding::Utf8 => {
+            if utf16_bom {
+                return Err(Error::Other(anyhow::anyhow!(
+                    "{} looks UTF-16 encoded. Add ?encoding=utf-16 (or auto) to read it",
+                    path
+                )));
+            }
+            check_not_binary(path, bytes)?;
+            String::from_utf8(bytes.to_vec()).map_err(|_| {
+                Error::Other(anyhow::anyhow!(
+                    "{} is not valid UTF-8. Add ?encoding=auto, latin-1 or lossy to read it",
+                    path
+                ))
+            })
+        }
+        TextEncoding::Lossy => {
+            check_not_binary(path, bytes)?;
+            Ok(String::from_utf8_lossy(bytes).into_owned())
+        }
+        TextEncoding::Latin1 => {
+            check_not_binary(path, bytes)?;
+            Ok(decode_latin1(bytes))
+        }
+    }
+}
+
+fn check_not_binary(path: &str, bytes: &[u8]) -> Result<()> {
+    if bytes[..bytes.len().min(BINARY_SNIFF_BYTES)].contains(&0) {
+        return Err(Error::Other(anyhow::anyhow!(
+            "{} looks like a binary file (it contains NUL bytes); only text files can be added",
+            path
+        )));
+    }
+    Ok(())
+}
+
+fn decode_utf16(bytes: &[u8], big_endian: bool) -> String {
+    let units = bytes.chunks_exact(2).map(|pair| {
+        if big_endian {
+            u16::from_be_bytes([pair[0], pair[1]])
+        } else {
+            u16::from_le_bytes([pair[0], pair[1]])
+        }
+    });
+    char::decode_utf16(units)
+        .map(|c| c.unwrap_or(char::REPLACEMENT_CHARACTER))
+        .collect()
+}
+
+fn decode_latin1(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| char::from(*b)).collect()
+}
+
 fn parse_line_range(range_str: &str) -> Result<(usize, usize)> {
     if let Some((start_str, end_str)) = range_str.split_once('-') {
         let start = start_str
@@ -129,3 +542,219 @@ fn parse_line_range(range_str: &str) -> Result<(usize, usize)> {
         )))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_parse_notebook_with_options() {
+        let dir = std::env::temp_dir().join(format!("ctx-file-test-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let nb = dir.join("analysis.ipynb");
+        std::fs::write(
+            &nb,
+            r#"{"cells": [{"cell_type": "code", "source": "1 + 1", "outputs": [
+                {"output_type": "execute_result", "data": {"text/plain": "2"}}
+            ]}]}"#,
+        )
+        .unwrap();
+
+        let uri = format!("file:{}?outputs=false", nb.display());
+        let artifact = FileHandler::default()
+            .parse(&uri, SourceOptions::default())
+            .await
+            .unwrap();
+
+        assert!(matches!(
+            artifact.artifact_type,
+            ArtifactType::Notebook {
+                strip_outputs: true,
+                ..
+            }
+        ));
+        assert_eq!(
+            artifact.metadata.mime_type.as_deref(),
+            Some("application/x-ipynb+json")
+        );
+
+        let content = FileHandler::default().load(&artifact).await.unwrap();
+        assert_eq!(content, "```python\n1 + 1\n```");
+
+        let err = FileHandler::default()
+            .parse(
+                &format!("file:{}?depth=2", nb.display()),
+                SourceOptions::default(),
+            )
+            .await;
+        assert!(err.is_err());
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    fn temp_dir(name: &str) -> std::path::PathBuf {
+        let dir = std::env::temp_dir().join(format!("ctx-file-{}-{}", name, uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    async fn load_uri(handler: &FileHandler, uri: &str) -> Result<String> {
+        let artifact = handler.parse(uri, SourceOptions::default()).await?;
+        handler.load(&artifact).await
+    }
+
+    #[tokio::test]
+    async fn test_binary_and_oversized_files() {
+        let dir = temp_dir("limits");
+        let binary = dir.join("image.bin");
+        std::fs::write(&binary, b"\x89PNG\r\n\x1a\n\0\0\0\rIHDR").unwrap();
+        let log = dir.join("app.log");
+        let lines: Vec<String> = (1..=200).map(|i| format!("line {}", i)).collect();
+        std::fs::write(&log, lines.join("\n") + "\n").unwrap();
+
+        let handler = FileHandler::new(FileSettings {
+            max_file_bytes: 256,
+            ..Default::default()
+        });
+
+        let err = load_uri(&handler, &format!("file:{}", binary.display()))
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("binary"));
+
+        let err = load_uri(&handler, &format!("file:{}", log.display()))
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("over the 256 B file limit"));
+
+        // Larger than the limit, so only the needed end of the file is read
+        let tail = load_uri(&handler, &format!("file:{}?tail=3", log.display()))
+            .await
+            .unwrap();
+        assert_eq!(tail, "line 198\nline 199\nline 200");
+
+        let head = load_uri(&handler, &format!("file:{}?head=2", log.display()))
+            .await
+            .unwrap();
+        assert_eq!(head, "line 1\nline 2");
+
+        // More lines than fit: whole lines up to the limit
+        let tail = load_uri(&handler, &format!("file:{}?tail=150", log.display()))
+            .await
+            .unwrap();
+        assert!(tail.len() <= 256);
+        assert!(tail.starts_with("line ") && tail.ends_with("line 200"));
+
+        let head = load_uri(&handler, &format!("file:{}?head=150", log.display()))
+            .await
+            .unwrap();
+        assert!(head.len() <= 256);
+        assert!(head.starts_with("line 1\n") && !head.ends_with('\n'));
+        assert_eq!(head.lines().last(), Some("line 33"));
+
+        // Small files are windowed after reading
+        let tail = load_uri(
+            &FileHandler::default(),
+            &format!("file:{}?tail=1", log.display()),
+        )
+        .await
+        .unwrap();
+        assert_eq!(tail, "line 200");
+
+        for query in ["head=0", "tail=x", "head=2&tail=2"] {
+            let uri = format!("file:{}?{}", log.display(), query);
+            assert!(handler.parse(&uri, SourceOptions::default()).await.is_err());
+        }
+        let uri = format!("file:{}#L1-L2?tail=2", log.display());
+        assert!(handler.parse(&uri, SourceOptions::default()).await.is_err());
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_windows_over_several_chunks() {
+        let dir = temp_dir("chunks");
+        let log = dir.join("big.log");
+        // 11 bytes a line, so the file spans several read chunks
+        let lines: Vec<String> = (1..=20_000).map(|i| format!("line {:05}", i)).collect();
+        std::fs::write(&log, lines.join("\n") + "\n").unwrap();
+
+        let max = 3 * WINDOW_CHUNK as u64;
+        let handler = FileHandler::new(FileSettings {
+            max_file_bytes: max,
+            ..Default::default()
+        });
+        let uri = |query: &str| format!("file:{}?{}", log.display(), query);
+
+        // Windows spanning chunk boundaries
+        let head = load_uri(&handler, &uri("head=7000")).await.unwrap();
+        assert_eq!(head, lines[..7000].join("\n"));
+        let tail = load_uri(&handler, &uri("tail=7000")).await.unwrap();
+        assert_eq!(tail, lines[13_000..].join("\n"));
+
+        // Windows larger than the limit: whole lines up to it, from the right end
+        let fit = max as usize / 11;
+        let head = load_uri(&handler, &uri("head=20000")).await.unwrap();
+        assert_eq!(head, lines[..fit].join("\n"));
+        let tail = load_uri(&handler, &uri("tail=20000")).await.unwrap();
+        assert_eq!(tail, lines[20_000 - fit..].join("\n"));
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_text_encodings() {
+        let dir = temp_dir("encodings");
+        let utf16 = dir.join("notes-utf16.txt");
+        let mut bytes = vec![0xFF, 0xFE];
+        bytes.extend("héllo\nwörld".encode_utf16().flat_map(u16::to_le_bytes));
+        std::fs::write(&utf16, bytes).unwrap();
+        let latin1 = dir.join("legacy.txt");
+        std::fs::write(&latin1, b"caf\xe9 cr\xe8me").unwrap();
+
+        let handler = FileHandler::default();
+        let load = |path: &std::path::Path, query: &str| {
+            let uri = format!("file:{}{}", path.display(), query);
+            let handler = &handler;
+            async move { load_uri(handler, &uri).await }
+        };
+
+        let err = load(&utf16, "").await.unwrap_err();
+        assert!(err.to_string().contains("?encoding=utf-16"));
+        assert_eq!(
+            load(&utf16, "?encoding=utf-16").await.unwrap(),
+            "héllo\nwörld"
+        );
+        assert_eq!(
+            load(&utf16, "?encoding=auto").await.unwrap(),
+            "héllo\nwörld"
+        );
+        assert_eq!(
+            load(&utf16, "?encoding=utf-16&tail=1").await.unwrap(),
+            "wörld"
+        );
+
+        let err = load(&latin1, "").await.unwrap_err();
+        assert!(err.to_string().contains("not valid UTF-8"));
+        assert_eq!(
+            load(&latin1, "?encoding=latin-1").await.unwrap(),
+            "café crème"
+        );
+        assert_eq!(load(&latin1, "?encoding=auto").await.unwrap(), "café crème");
+        assert_eq!(
+            load(&latin1, "?encoding=lossy").await.unwrap(),
+            "caf\u{FFFD} cr\u{FFFD}me"
+        );
+        assert!(load(&latin1, "?encoding=ebcdic").await.is_err());
+
+        // The configured default applies when the URI doesn't choose
+        let auto = FileHandler::new(FileSettings {
+            encoding: TextEncoding::Auto,
+            ..Default::default()
+        });
+        let uri = format!("file:{}", latin1.display());
+        assert_eq!(load_uri(&auto, &uri).await.unwrap(), "café crème");
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
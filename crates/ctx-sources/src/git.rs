@@ -26,6 +26,7 @@ impl SourceHandler for GitHandler {
                     "base": base,
                     "head": head,
                 }),
+                ..Default::default()
             };
 
             Ok(Artifact::new(artifact_type, uri.to_string()).with_metadata(metadata))
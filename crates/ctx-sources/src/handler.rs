@@ -1,7 +1,15 @@
 use async_trait::async_trait;
-use ctx_core::{Artifact, Error, Result};
+use ctx_core::{Artifact, Error, Result, find_workspace_root};
+use std::collections::BTreeMap;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 
+use crate::command::CommandPolicy;
+use crate::file::FileSettings;
+use crate::issue::IssueSettings;
+use crate::metadata;
+
 #[derive(Debug, Clone, Default)]
 pub struct SourceOptions {
     pub range: Option<(usize, usize)>,
@@ -23,22 +31,72 @@ pub trait SourceHandler: Send + Sync {
     fn can_handle(&self, uri: &str) -> bool;
 }
 
+/// Handlers enabled by user configuration on top of the built-in ones
+#[derive(Debug, Clone, Default)]
+pub struct SourceSettings {
+    /// Size limit and default encoding for `file:` sources
+    pub files: FileSettings,
+    /// Policy for `cmd:` sources; None refuses them
+    pub command_policy: Option<CommandPolicy>,
+    /// Plugin executables by URI scheme, e.g. `jira` -> `/usr/local/bin/ctx-source-jira`
+    pub plugins: BTreeMap<String, PathBuf>,
+    /// API bases, tokens and caching for `issue:` sources
+    pub issues: IssueSettings,
+    /// Workspace that relative artifact paths resolve against first; defaults to the one
+    /// containing the current directory
+    pub workspace: Option<PathBuf>,
+}
+
 pub struct SourceHandlerRegistry {
     handlers: Vec<Arc<dyn SourceHandler>>,
+    workspace: Option<PathBuf>,
 }
 
 impl SourceHandlerRegistry {
+    /// Registry with all built-in handlers; `cmd:` sources are refused
     pub fn new() -> Self {
+        Self::with_settings(&SourceSettings::default())
+    }
+
+    /// Registry with all built-in handlers plus what `settings` enables
+    pub fn with_settings(settings: &SourceSettings) -> Self {
+        let workspace = settings.workspace.clone().or_else(|| {
+            std::env::current_dir()
+                .ok()
+                .and_then(|dir| find_workspace_root(&dir))
+        });
         let mut registry = Self {
             handlers: Vec::new(),
+            workspace,
         };
 
         // Register built-in handlers
-        registry.register(Arc::new(crate::file::FileHandler));
+        registry.register(Arc::new(crate::file::FileHandler::new(
+            settings.files.clone(),
+        )));
         registry.register(Arc::new(crate::text::TextHandler));
         registry.register(Arc::new(crate::collection::CollectionHandler));
         registry.register(Arc::new(crate::git::GitHandler));
         registry.register(Arc::new(crate::url::UrlHandler));
+        registry.register(Arc::new(crate::crawl::CrawlHandler));
+        registry.register(Arc::new(crate::symbol::SymbolHandler::new(
+            settings.files.clone(),
+        )));
+        registry.register(Arc::new(crate::tree::TreeHandler));
+        registry.register(Arc::new(crate::issue::IssueHandler::new(
+            settings.issues.clone(),
+        )));
+        registry.register(Arc::new(crate::command::CommandHandler::new(
+            settings.command_policy.clone(),
+        )));
+
+        // Plugins come last so they can't shadow built-in schemes
+        for (scheme, program) in &settings.plugins {
+            registry.register(Arc::new(crate::plugin::PluginHandler::new(
+                scheme.clone(),
+                program.clone(),
+            )));
+        }
 
         registry
     }
@@ -48,6 +106,41 @@ impl SourceHandlerRegistry {
     }
 
     pub async fn parse(&self, uri: &str, options: SourceOptions) -> Result<Artifact> {
+        let mut artifact = self.parse_transient(uri, options).await?;
+        if let (Some(info), Some(path)) = (
+            artifact.metadata.file.as_mut(),
+            artifact.artifact_type.file_path(),
+        ) {
+            metadata::add_git_history(info, path, None).await;
+        }
+        store_workspace_relative(&mut artifact);
+        Ok(artifact)
+    }
+
+    /// `artifact`'s source URI with its path relative to `root` when it's under it and absolute
+    /// otherwise, the inverse of [`anchor_uri`]
+    pub fn source_relative_to(&self, artifact: &Artifact, root: &Path) -> String {
+        let uri = &artifact.source_uri;
+        let resolved = self.resolve_paths(artifact);
+        let (Some(span), Some(path)) = (
+            uri_path_span(uri),
+            resolved.artifact_type.local_paths().first().copied(),
+        ) else {
+            return uri.clone();
+        };
+
+        let root = std::fs::canonicalize(root).unwrap_or_else(|_| root.to_path_buf());
+        let path = match Path::new(path).strip_prefix(&root) {
+            Ok(rel) if rel.as_os_str().is_empty() => ".".to_string(),
+            Ok(rel) => rel.to_string_lossy().to_string(),
+            Err(_) => path.to_string(),
+        };
+        replace_span(uri, span, &path)
+    }
+
+    /// Parse for a single render, e.g. the files a collection expands to: paths stay
+    /// absolute and git history isn't looked up
+    pub async fn parse_transient(&self, uri: &str, options: SourceOptions) -> Result<Artifact> {
         for handler in &self.handlers {
             if handler.can_handle(uri) {
                 return handler.parse(uri, options).await;
@@ -60,10 +153,16 @@ impl SourceHandlerRegistry {
         )))
     }
 
+    /// Copy of `artifact` with workspace-relative paths made absolute
+    pub fn resolve_paths(&self, artifact: &Artifact) -> Artifact {
+        artifact.resolve_paths(self.workspace.as_deref())
+    }
+
     pub async fn load(&self, artifact: &Artifact) -> Result<String> {
+        let artifact = self.resolve_paths(artifact);
         for handler in &self.handlers {
             if handler.can_handle(&artifact.source_uri) {
-                return handler.load(artifact).await;
+                return handler.load(&artifact).await;
             }
         }
 
@@ -79,3 +178,171 @@ impl Default for SourceHandlerRegistry {
         Self::new()
     }
 }
+
+/// Anchor relative paths at the current directory, then store them relative to the workspace
+/// containing them so the pack keeps working when the checkout moves
+fn store_workspace_relative(artifact: &mut Artifact) {
+    if let Ok(cwd) = std::env::current_dir() {
+        for path in artifact.artifact_type.local_paths_mut() {
+            if Path::new(path.as_str()).is_relative() {
+                *path = cwd.join(path.as_str()).to_string_lossy().to_string();
+            }
+        }
+    }
+
+    let root = artifact
+        .artifact_type
+        .local_paths()
+        .first()
+        .and_then(|path| find_workspace_root(Path::new(path)));
+    if let Some(root) = root {
+        artifact.relativize(&root);
+    }
+}
+
+/// `uri` with a relative local path anchored at `base` instead of the current directory,
+/// e.g. the sources in a project's ctx.toml
+pub fn anchor_uri(uri: &str, base: &Path) -> String {
+    match uri_path_span(uri) {
+        Some(span) if Path::new(&uri[span.clone()]).is_relative() => {
+            let path = &uri[span.clone()];
+            let anchored = if path.is_empty() || path == "." {
+                base.to_path_buf()
+            } else {
+                base.join(path)
+            };
+            replace_span(uri, span, &anchored.to_string_lossy())
+        }
+        _ => uri.to_string(),
+    }
+}
+
+/// Byte range of the local path inside a source URI, for schemes that take one
+fn uri_path_span(uri: &str) -> Option<Range<usize>> {
+    let (scheme, rest) = match uri.split_once(':') {
+        Some((scheme, rest)) => (scheme, rest),
+        None => ("file", uri),
+    };
+    let start = uri.len() - rest.len();
+    let len = match scheme {
+        "file" => rest
+            .find('?')
+            .into_iter()
+            .chain(rest.find("#L"))
+            .min()
+            .unwrap_or(rest.len()),
+        "symbol" => rest.find("::")?,
+        "glob" => rest.len(),
+        "dir" | "md_dir" | "docs_dir" | "tree" => {
+            // Leading words up to the first option; these paths may contain spaces
+            let options = rest
+                .match_indices(char::is_whitespace)
+                .map(|(i, _)| i)
+                .find(|&i| rest[i..].trim_start().starts_with("--"))
+                .unwrap_or(rest.len());
+            let path = rest[..options].trim_end();
+            if path.starts_with("--") {
+                0
+            } else {
+                path.len()
+            }
+        }
+        _ => return None,
+    };
+    let leading = rest[..len].len() - rest[..len].trim_start().len();
+    Some(start + leading..start + len)
+}
+
+fn replace_span(uri: &str, span: Range<usize>, path: &str) -> String {
+    let mut out = String::with_capacity(uri.len() + path.len());
+    out.push_str(&uri[..span.start]);
+    out.push_str(path);
+    // `tree: --depth=2` has an empty path span right before its options
+    if span.is_empty() && !uri[span.end..].is_empty() && !uri[span.end..].starts_with(' ') {
+        out.push(' ');
+    }
+    out.push_str(&uri[span.end..]);
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_anchor_uri() {
+        let base = Path::new("/work/repo");
+        assert_eq!(
+            anchor_uri("file:src/lib.rs#L3-L9", base),
+            "file:/work/repo/src/lib.rs#L3-L9"
+        );
+        assert_eq!(
+            anchor_uri("app.log?tail=50", base),
+            "/work/repo/app.log?tail=50"
+        );
+        assert_eq!(
+            anchor_uri("symbol:src/auth.rs::Auth::login", base),
+            "symbol:/work/repo/src/auth.rs::Auth::login"
+        );
+        assert_eq!(
+            anchor_uri("dir:my docs --recursive", base),
+            "dir:/work/repo/my docs --recursive"
+        );
+        assert_eq!(
+            anchor_uri("glob:src/**/*.rs", base),
+            "glob:/work/repo/src/**/*.rs"
+        );
+        assert_eq!(
+            anchor_uri("tree:--depth=2", base),
+            "tree:/work/repo --depth=2"
+        );
+        assert_eq!(anchor_uri("tree:", base), "tree:/work/repo");
+        assert_eq!(anchor_uri("file:/abs/x.rs", base), "file:/abs/x.rs");
+        assert_eq!(
+            anchor_uri("https://example.com/a", base),
+            "https://example.com/a"
+        );
+    }
+
+    #[test]
+    fn test_source_relative_to() {
+        let root = std::env::temp_dir().join(format!("ctx-relsrc-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(root.join("src")).unwrap();
+        let root = std::fs::canonicalize(&root).unwrap();
+        let registry = SourceHandlerRegistry::new();
+
+        // Added from a subdirectory: the stored URI is relative to it, not to the project
+        let mut artifact = Artifact::new(
+            ctx_core::ArtifactType::File {
+                path: root.join("src/lib.rs").to_string_lossy().to_string(),
+                window: None,
+                encoding: None,
+            },
+            "file:lib.rs#L1-L2".to_string(),
+        );
+        assert!(artifact.relativize(&root));
+        assert_eq!(
+            registry.source_relative_to(&artifact, &root),
+            "file:src/lib.rs#L1-L2"
+        );
+
+        let elsewhere = root.join("src");
+        let outside = Artifact::new(
+            ctx_core::ArtifactType::Tree {
+                path: "/opt/other".to_string(),
+                max_depth: 2,
+                sizes: false,
+                tokens: false,
+                include: Vec::new(),
+                exclude: Vec::new(),
+            },
+            "tree:../other --depth=2".to_string(),
+        );
+        assert_eq!(
+            registry.source_relative_to(&outside, &elsewhere),
+            "tree:/opt/other --depth=2"
+        );
+
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+}
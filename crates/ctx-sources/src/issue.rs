@@ -0,0 +1,961 @@
+//! `issue:` sources: GitHub issues and pull requests, GitLab issues and merge requests.
+//!
+//! ```text
+//! issue:owner/repo#12                  GitHub issue (or PR conversation)
+//! issue:github:owner/repo!34           GitHub pull request with review threads
+//! issue:gitlab:group/project#5         GitLab issue
+//! issue:gitlab:group/project!7         GitLab merge request
+//! issue:https://github.com/owner/repo/pull/34
+//! ```
+//!
+//! Each provider talks to a configurable API base. A base that isn't an http(s) URL is read as a
+//! directory of exported responses (`<base>/repos/owner/repo/issues/12.json`). Rendered Markdown is
+//! cached in `cache_dir` for `cache_ttl`.
+
+use async_trait::async_trait;
+use ctx_core::{Artifact, ArtifactMetadata, ArtifactType, Error, IssueProvider, Result};
+use serde_json::Value;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use crate::handler::{SourceHandler, SourceOptions};
+use crate::url::UrlHandler;
+
+pub const GITHUB_API: &str = "https://api.github.com";
+pub const GITLAB_API: &str = "https://gitlab.com/api/v4";
+
+const PER_PAGE: usize = 100;
+/// Stop paging long threads here rather than hammering the API
+const MAX_PAGES: usize = 10;
+
+/// Where a provider's API lives and which env var holds its token
+#[derive(Debug, Clone)]
+pub struct IssueApi {
+    /// API root URL, or a directory of exported JSON responses
+    pub base: String,
+    /// Environment variable with the access token; unset or empty means anonymous
+    pub token_env: String,
+}
+
+/// Settings for `issue:` sources
+#[derive(Debug, Clone)]
+pub struct IssueSettings {
+    pub github: IssueApi,
+    pub gitlab: IssueApi,
+    /// How long rendered issues are reused before fetching again; zero always fetches
+    pub cache_ttl: Duration,
+    /// Directory rendered issues are cached in; defaults to `issues` in ctx's data directory
+    pub cache_dir: Option<PathBuf>,
+}
+
+impl Default for IssueSettings {
+    fn default() -> Self {
+        Self {
+            github: IssueApi {
+                base: GITHUB_API.to_string(),
+                token_env: "GITHUB_TOKEN".to_string(),
+            },
+            gitlab: IssueApi {
+                base: GITLAB_API.to_string(),
+                token_env: "GITLAB_TOKEN".to_string(),
+            },
+            cache_ttl: Duration::from_secs(300),
+            cache_dir: None,
+        }
+    }
+}
+
+/// Directory issues are cached in unless configured otherwise
+fn default_cache_dir() -> PathBuf {
+    match directories::ProjectDirs::from("com", "ctx", "ctx") {
+        Some(dirs) => dirs.data_dir().join("issues"),
+        None => std::env::temp_dir().join("ctx-issues"),
+    }
+}
+
+/// Issue reference parsed from an `issue:` URI
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IssueRef {
+    pub provider: IssueProvider,
+    pub repo: String,
+    pub number: u64,
+    pub pull_request: bool,
+}
+
+impl IssueRef {
+    /// Short reference, e.g. `owner/repo#12` or `group/project!7`
+    pub fn display(&self) -> String {
+        let sigil = if self.pull_request { '!' } else { '#' };
+        format!("{}{}{}", self.repo, sigil, self.number)
+    }
+}
+
+/// Parse `issue:[provider:]repo#N`, `issue:[provider:]repo!N` or `issue:<web URL>`
+pub fn parse_issue_uri(uri: &str) -> Result<IssueRef> {
+    let invalid = || {
+        Error::InvalidSourceUri(format!(
+            "Invalid issue URI: {} (expected issue:owner/repo#N, issue:gitlab:group/project!N or an issue URL)",
+            uri
+        ))
+    };
+
+    let spec = uri.strip_prefix("issue:").ok_or_else(invalid)?.trim();
+
+    if spec.starts_with("http://") || spec.starts_with("https://") {
+        return parse_web_url(spec).ok_or_else(invalid);
+    }
+
+    let (provider, rest) = match spec.split_once(':') {
+        Some((name, rest)) => (name.parse().map_err(Error::InvalidSourceUri)?, rest),
+        None => (IssueProvider::Github, spec),
+    };
+
+    let (repo, number, pull_request) = if let Some((repo, n)) = rest.rsplit_once('#') {
+        (repo, n, false)
+    } else if let Some((repo, n)) = rest.rsplit_once('!') {
+        (repo, n, true)
+    } else {
+        return Err(invalid());
+    };
+
+    let repo = repo.trim_matches('/');
+    if !repo.contains('/') {
+        return Err(invalid());
+    }
+
+    Ok(IssueRef {
+        provider,
+        repo: repo.to_string(),
+        number: number.parse().map_err(|_| invalid())?,
+        pull_request,
+    })
+}
+
+/// github.com/o/r/issues/N, github.com/o/r/pull/N, gitlab.com/g/p/-/issues/N, .../-/merge_requests/N
+fn parse_web_url(url: &str) -> Option<IssueRef> {
+    let rest = url.split_once("://")?.1;
+    let path = rest.split_once('/')?.1;
+    let path = path.split(['?', '#']).next()?;
+    let segments: Vec<&str> = path.split('/').filter(|s| !s.is_empty()).collect();
+
+    let (provider, repo, kind, number) = match segments.iter().position(|s| *s == "-") {
+        Some(dash) if segments.len() == dash + 3 => (
+            IssueProvider::Gitlab,
+            segments[..dash].join("/"),
+            segments[dash + 1],
+            segments[dash + 2],
+        ),
+        None if segments.len() == 4 => (
+            IssueProvider::Github,
+            segments[..2].join("/"),
+            segments[2],
+            segments[3],
+        ),
+        _ => return None,
+    };
+
+    let pull_request = match kind {
+        "issues" => false,
+        "pull" | "pulls" | "merge_requests" => true,
+        _ => return None,
+    };
+
+    if !repo.contains('/') {
+        return None;
+    }
+
+    Some(IssueRef {
+        provider,
+        repo,
+        number: number.parse().ok()?,
+        pull_request,
+    })
+}
+
+/// Issue thread normalized across providers
+#[derive(Debug, Default)]
+struct Thread {
+    title: String,
+    state: String,
+    author: String,
+    url: String,
+    labels: Vec<String>,
+    /// (source, target) for pull/merge requests
+    branches: Option<(String, String)>,
+    body: String,
+    comments: Vec<Comment>,
+    review_comments: Vec<Comment>,
+    reviews: Vec<Review>,
+}
+
+#[derive(Debug, Default)]
+struct Comment {
+    author: String,
+    created_at: String,
+    body: String,
+    /// `path:line` for comments on a diff
+    location: Option<String>,
+    diff_hunk: Option<String>,
+}
+
+#[derive(Debug)]
+struct Review {
+    author: String,
+    state: String,
+    body: String,
+}
+
+/// Handles `issue:` sources
+pub struct IssueHandler {
+    settings: IssueSettings,
+    cache_dir: PathBuf,
+}
+
+impl IssueHandler {
+    pub fn new(settings: IssueSettings) -> Self {
+        let cache_dir = settings.cache_dir.clone().unwrap_or_else(default_cache_dir);
+        Self {
+            settings,
+            cache_dir,
+        }
+    }
+
+    fn api(&self, provider: IssueProvider) -> &IssueApi {
+        match provider {
+            IssueProvider::Github => &self.settings.github,
+            IssueProvider::Gitlab => &self.settings.gitlab,
+        }
+    }
+
+    fn cache_key(&self, issue: &IssueRef) -> String {
+        let key = format!(
+            "{}|{}|{}",
+            issue.provider.as_str(),
+            self.api(issue.provider).base,
+            issue.display()
+        );
+        format!("issue-{}", blake3::hash(key.as_bytes()).to_hex())
+    }
+
+    async fn cached(&self, key: &str) -> Option<String> {
+        let path = self.cache_dir.join(key);
+        let written = tokio::fs::metadata(&path).await.ok()?.modified().ok()?;
+        if written.elapsed().ok()? >= self.settings.cache_ttl {
+            return None;
+        }
+        tokio::fs::read_to_string(&path).await.ok()
+    }
+
+    async fn cache(&self, key: &str, content: &str) -> std::io::Result<()> {
+        tokio::fs::create_dir_all(&self.cache_dir).await?;
+        // Written aside and renamed, so a concurrent render never reads half an issue
+        let partial = self.cache_dir.join(format!("{}.partial", key));
+        tokio::fs::write(&partial, content).await?;
+        tokio::fs::rename(&partial, self.cache_dir.join(key)).await
+    }
+
+    async fn fetch(&self, issue: &IssueRef) -> Result<Thread> {
+        let api = ApiClient::new(issue.provider, self.api(issue.provider))?;
+        match issue.provider {
+            IssueProvider::Github => fetch_github(&api, issue).await,
+            IssueProvider::Gitlab => fetch_gitlab(&api, issue).await,
+        }
+    }
+}
+
+#[async_trait]
+impl SourceHandler for IssueHandler {
+    async fn parse(&self, uri: &str, _options: SourceOptions) -> Result<Artifact> {
+        let issue = parse_issue_uri(uri)?;
+
+        let artifact_type = ArtifactType::Issue {
+            provider: issue.provider,
+            repo: issue.repo,
+            number: issue.number,
+            pull_request: issue.pull_request,
+        };
+
+        let metadata = ArtifactMetadata {
+            size_bytes: 0,
+            mime_type: Some("text/markdown".to_string()),
+            extra: serde_json::json!({}),
+            ..Default::default()
+        };
+
+        Ok(Artifact::new(artifact_type, uri.to_string()).with_metadata(metadata))
+    }
+
+    async fn load(&self, artifact: &Artifact) -> Result<String> {
+        let issue = match &artifact.artifact_type {
+            ArtifactType::Issue {
+                provider,
+                repo,
+                number,
+                pull_request,
+            } => IssueRef {
+                provider: *provider,
+                repo: repo.clone(),
+                number: *number,
+                pull_request: *pull_request,
+            },
+            _ => {
+                return Err(Error::Other(anyhow::anyhow!(
+                    "Unsupported artifact type for IssueHandler"
+                )));
+            }
+        };
+
+        let key = self.cache_key(&issue);
+        if let Some(content) = self.cached(&key).await {
+            return Ok(content);
+        }
+
+        let content = render_thread(&issue, &self.fetch(&issue).await?);
+
+        // A cache that can't be written only costs a refetch next time
+        let _ = self.cache(&key, &content).await;
+
+        Ok(content)
+    }
+
+    fn can_handle(&self, uri: &str) -> bool {
+        uri.starts_with("issue:")
+    }
+}
+
+/// JSON fetches against an API base or a directory of exports
+struct ApiClient<'a> {
+    provider: IssueProvider,
+    api: &'a IssueApi,
+    http: Option<reqwest::Client>,
+    token: Option<String>,
+}
+
+impl<'a> ApiClient<'a> {
+    fn new(provider: IssueProvider, api: &'a IssueApi) -> Result<Self> {
+        let is_http = api.base.starts_with("http://") || api.base.starts_with("https://");
+        let token = std::env::var(&api.token_env)
+            .ok()
+            .filter(|token| !token.is_empty());
+        Ok(Self {
+            provider,
+            api,
+            http: if is_http {
+                Some(UrlHandler::client()?)
+            } else {
+                None
+            },
+            token,
+        })
+    }
+
+    fn export_path(&self, endpoint: &str) -> PathBuf {
+        let base = self
+            .api
+            .base
+            .strip_prefix("file://")
+            .unwrap_or(&self.api.base);
+        let endpoint = endpoint.split('?').next().unwrap_or(endpoint);
+        Path::new(base).join(format!("{}.json", endpoint.replace("%2F", "/")))
+    }
+
+    async fn get(&self, endpoint: &str) -> Result<Value> {
+        let Some(http) = &self.http else {
+            let path = self.export_path(endpoint);
+            let content = tokio::fs::read_to_string(&path).await.map_err(|e| {
+                Error::Other(anyhow::anyhow!(
+                    "Failed to read issue export {}: {}",
+                    path.display(),
+                    e
+                ))
+            })?;
+            return serde_json::from_str(&content).map_err(|e| {
+                Error::Other(anyhow::anyhow!("Invalid JSON in {}: {}", path.display(), e))
+            });
+        };
+
+        let url = format!("{}/{}", self.api.base.trim_end_matches('/'), endpoint);
+        let mut request = http.get(&url);
+        if let Some(token) = &self.token {
+            request = match self.provider {
+                IssueProvider::Github => request
+                    .bearer_auth(token)
+                    .header("Accept", "application/vnd.github+json"),
+                IssueProvider::Gitlab => request.header("PRIVATE-TOKEN", token),
+            };
+        }
+
+        let response = request
+            .send()
+            .await
+            .map_err(|e| Error::Other(anyhow::anyhow!("Failed to fetch {}: {}", url, e)))?;
+
+        if !response.status().is_success() {
+            return Err(Error::Other(anyhow::anyhow!(
+                "HTTP error {}: {}",
+                response.status().as_u16(),
+                url
+            )));
+        }
+
+        response
+            .json()
+            .await
+            .map_err(|e| Error::Other(anyhow::anyhow!("Invalid JSON from {}: {}", url, e)))
+    }
+
+    /// Fetch a list endpoint, following pages when talking to a live API
+    async fn get_list(&self, endpoint: &str) -> Result<Vec<Value>> {
+        if self.http.is_none() {
+            return Ok(as_list(self.get(endpoint).await?));
+        }
+
+        let separator = if endpoint.contains('?') { '&' } else { '?' };
+        let mut items = Vec::new();
+        for page in 1..=MAX_PAGES {
+            let page_items = as_list(
+                self.get(&format!(
+                    "{}{}per_page={}&page={}",
+                    endpoint, separator, PER_PAGE, page
+                ))
+                .await?,
+            );
+            let done = page_items.len() < PER_PAGE;
+            items.extend(page_items);
+            if done {
+                break;
+            }
+        }
+        Ok(items)
+    }
+}
+
+fn as_list(value: Value) -> Vec<Value> {
+    match value {
+        Value::Array(items) => items,
+        _ => Vec::new(),
+    }
+}
+
+/// String at a JSON pointer, empty if missing or null
+fn text(value: &Value, pointer: &str) -> String {
+    match value.pointer(pointer) {
+        Some(Value::String(s)) => s.clone(),
+        Some(Value::Number(n)) => n.to_string(),
+        _ => String::new(),
+    }
+}
+
+async fn fetch_github(api: &ApiClient<'_>, issue: &IssueRef) -> Result<Thread> {
+    let repo = format!("repos/{}", issue.repo);
+    let n = issue.number;
+
+    let item = if issue.pull_request {
+        api.get(&format!("{}/pulls/{}", repo, n)).await?
+    } else {
+        api.get(&format!("{}/issues/{}", repo, n)).await?
+    };
+
+    let mut thread = Thread {
+        title: text(&item, "/title"),
+        state: match item.get("merged").and_then(Value::as_bool) {
+            Some(true) => "merged".to_string(),
+            _ => text(&item, "/state"),
+        },
+        author: text(&item, "/user/login"),
+        url: text(&item, "/html_url"),
+        labels: item["labels"]
+            .as_array()
+            .map(|labels| labels.iter().map(|l| text(l, "/name")).collect())
+            .unwrap_or_default(),
+        body: text(&item, "/body"),
+        ..Default::default()
+    };
+    if issue.pull_request {
+        thread.branches = Some((text(&item, "/head/ref"), text(&item, "/base/ref")));
+    }
+
+    // Pull request conversation comments live on the issues endpoint too
+    thread.comments = api
+        .get_list(&format!("{}/issues/{}/comments", repo, n))
+        .await?
+        .iter()
+        .map(|c| Comment {
+            author: text(c, "/user/login"),
+            created_at: text(c, "/created_at"),
+            body: text(c, "/body"),
+            ..Default::default()
+        })
+        .collect();
+
+    if issue.pull_request {
+        thread.reviews = api
+            .get_list(&format!("{}/pulls/{}/reviews", repo, n))
+            .await?
+            .iter()
+            .map(|r| Review {
+                author: text(r, "/user/login"),
+                state: text(r, "/state"),
+                body: text(r, "/body"),
+            })
+            .collect();
+
+        thread.review_comments = api
+            .get_list(&format!("{}/pulls/{}/comments", repo, n))
+            .await?
+            .iter()
+            .map(|c| {
+                let line = match text(c, "/line") {
+                    line if line.is_empty() => text(c, "/original_line"),
+                    line => line,
+                };
+                Comment {
+                    author: text(c, "/user/login"),
+                    created_at: text(c, "/created_at"),
+                    body: text(c, "/body"),
+                    location: Some(location(&text(c, "/path"), &line)),
+                    diff_hunk: Some(text(c, "/diff_hunk")).filter(|h| !h.is_empty()),
+                }
+            })
+            .collect();
+    }
+
+    Ok(thread)
+}
+
+async fn fetch_gitlab(api: &ApiClient<'_>, issue: &IssueRef) -> Result<Thread> {
+    let kind = if issue.pull_request {
+        "merge_requests"
+    } else {
+        "issues"
+    };
+    let base = format!(
+        "projects/{}/{}/{}",
+        issue.repo.replace('/', "%2F"),
+        kind,
+        issue.number
+    );
+
+    let item = api.get(&base).await?;
+
+    let mut thread = Thread {
+        title: text(&item, "/title"),
+        state: text(&item, "/state"),
+        author: text(&item, "/author/username"),
+        url: text(&item, "/web_url"),
+        labels: item["labels"]
+            .as_array()
+            .map(|labels| {
+                labels
+                    .iter()
+                    .filter_map(|l| l.as_str().map(str::to_string))
+                    .collect()
+            })
+            .unwrap_or_default(),
+        body: text(&item, "/description"),
+        ..Default::default()
+    };
+    if issue.pull_request {
+        thread.branches = Some((text(&item, "/source_branch"), text(&item, "/target_branch")));
+    }
+
+    let notes = api
+        .get_list(&format!("{}/notes?sort=asc&order_by=created_at", base))
+        .await?;
+    for note in notes {
+        // System notes are "changed the description" style events
+        if note["system"].as_bool().unwrap_or(false) {
+            continue;
+        }
+
+        let mut comment = Comment {
+            author: text(&note, "/author/username"),
+            created_at: text(&note, "/created_at"),
+            body: text(&note, "/body"),
+            ..Default::default()
+        };
+
+        if note.get("position").is_some_and(|p| !p.is_null()) {
+            let line = match text(&note, "/position/new_line") {
+                line if line.is_empty() => text(&note, "/position/old_line"),
+                line => line,
+            };
+            comment.location = Some(location(&text(&note, "/position/new_path"), &line));
+            thread.review_comments.push(comment);
+        } else {
+            thread.comments.push(comment);
+        }
+    }
+
+    Ok(thread)
+}
+
+fn location(path: &str, line: &str) -> String {
+    if line.is_empty() {
+        path.to_string()
+    } else {
+        format!("{}:{}", path, line)
+    }
+}
+
+fn render_thread(issue: &IssueRef, thread: &Thread) -> String {
+    let mut out = format!("# {} ({})\n\n", thread.title, issue.display());
+
+    let mut facts = Vec::new();
+    if !thread.state.is_empty() {
+        facts.push(format!("**State:** {}", thread.state));
+    }
+    if !thread.author.is_empty() {
+        facts.push(format!("**Author:** @{}", thread.author));
+    }
+    if !thread.labels.is_empty() {
+        facts.push(format!("**Labels:** {}", thread.labels.join(", ")));
+    }
+    if let Some((source, target)) = &thread.branches {
+        facts.push(format!("**Branches:** {} → {}", source, target));
+    }
+    if !facts.is_empty() {
+        out.push_str(&facts.join(" · "));
+        out.push('\n');
+    }
+    if !thread.url.is_empty() {
+        out.push_str(&thread.url);
+        out.push('\n');
+    }
+
+    out.push('\n');
+    match thread.body.trim() {
+        "" => out.push_str("_No description._\n"),
+        body => {
+            out.push_str(body);
+            out.push('\n');
+        }
+    }
+
+    if !thread.comments.is_empty() {
+        out.push_str(&format!("\n## Comments ({})\n", thread.comments.len()));
+        for comment in &thread.comments {
+            render_comment(&mut out, comment);
+        }
+    }
+
+    if !thread.reviews.is_empty() {
+        out.push_str("\n## Reviews\n\n");
+        for review in &thread.reviews {
+            out.push_str(&format!("- @{}: {}", review.author, review.state));
+            if !review.body.trim().is_empty() {
+                out.push_str(&format!(" — {}", review.body.trim()));
+            }
+            out.push('\n');
+        }
+    }
+
+    if !thread.review_comments.is_empty() {
+        out.push_str(&format!(
+            "\n## Review comments ({})\n",
+            thread.review_comments.len()
+        ));
+        for comment in &thread.review_comments {
+            render_comment(&mut out, comment);
+        }
+    }
+
+    out
+}
+
+fn render_comment(out: &mut String, comment: &Comment) {
+    out.push_str(&format!("\n### @{}", comment.author));
+    if let Some(location) = &comment.location {
+        out.push_str(&format!(" on `{}`", location));
+    }
+    // Date only; the time of day adds noise
+    if let Some(date) = comment.created_at.get(..10) {
+        out.push_str(&format!(" · {}", date));
+    }
+    out.push_str("\n\n");
+
+    if let Some(hunk) = &comment.diff_hunk {
+        out.push_str("```diff\n");
+        out.push_str(hunk.trim_end());
+        out.push_str("\n```\n\n");
+    }
+
+    out.push_str(comment.body.trim());
+    out.push('\n');
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("ctx-issue-{}-{}", name, uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn write_json(root: &Path, endpoint: &str, value: Value) {
+        let path = root.join(format!("{}.json", endpoint));
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(path, value.to_string()).unwrap();
+    }
+
+    #[test]
+    fn test_parse_issue_uri() {
+        let cases = [
+            (
+                "issue:rust-lang/rust#123",
+                IssueProvider::Github,
+                "rust-lang/rust",
+                123,
+                false,
+            ),
+            (
+                "issue:github:owner/repo!34",
+                IssueProvider::Github,
+                "owner/repo",
+                34,
+                true,
+            ),
+            (
+                "issue:gitlab:group/sub/project#5",
+                IssueProvider::Gitlab,
+                "group/sub/project",
+                5,
+                false,
+            ),
+            (
+                "issue:gl:group/project!7",
+                IssueProvider::Gitlab,
+                "group/project",
+                7,
+                true,
+            ),
+            (
+                "issue:https://github.com/owner/repo/pull/34",
+                IssueProvider::Github,
+                "owner/repo",
+                34,
+                true,
+            ),
+            (
+                "issue:https://github.com/owner/repo/issues/12#issuecomment-1",
+                IssueProvider::Github,
+                "owner/repo",
+                12,
+                false,
+            ),
+            (
+                "issue:https://gitlab.com/group/sub/project/-/merge_requests/7",
+                IssueProvider::Gitlab,
+                "group/sub/project",
+                7,
+                true,
+            ),
+        ];
+
+        for (uri, provider, repo, number, pull_request) in cases {
+            assert_eq!(
+                parse_issue_uri(uri).unwrap(),
+                IssueRef {
+                    provider,
+                    repo: repo.to_string(),
+                    number,
+                    pull_request,
+                },
+                "{}",
+                uri
+            );
+        }
+
+        for uri in [
+            "issue:repo#1",
+            "issue:owner/repo",
+            "issue:owner/repo#abc",
+            "issue:jira:owner/repo#1",
+            "issue:https://github.com/owner/repo/commits/1",
+        ] {
+            assert!(parse_issue_uri(uri).is_err(), "{}", uri);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_github_pull_request_from_export() {
+        let dir = temp_dir("github");
+        let exports = dir.join("export");
+        write_json(
+            &exports,
+            "repos/acme/widgets/pulls/34",
+            serde_json::json!({
+                "title": "Retry failed uploads",
+                "body": "Fixes #12.",
+                "state": "closed",
+                "merged": true,
+                "user": {"login": "alice"},
+                "html_url": "https://github.com/acme/widgets/pull/34",
+                "labels": [{"name": "bug"}],
+                "head": {"ref": "retry-uploads"},
+                "base": {"ref": "main"}
+            }),
+        );
+        write_json(
+            &exports,
+            "repos/acme/widgets/issues/34/comments",
+            serde_json::json!([
+                {"user": {"login": "bob"}, "created_at": "2024-03-01T10:00:00Z", "body": "Needs a test."}
+            ]),
+        );
+        write_json(
+            &exports,
+            "repos/acme/widgets/pulls/34/reviews",
+            serde_json::json!([
+                {"user": {"login": "carol"}, "state": "APPROVED", "body": ""}
+            ]),
+        );
+        write_json(
+            &exports,
+            "repos/acme/widgets/pulls/34/comments",
+            serde_json::json!([{
+                "user": {"login": "carol"},
+                "created_at": "2024-03-02T09:00:00Z",
+                "path": "src/upload.rs",
+                "line": null,
+                "original_line": 42,
+                "diff_hunk": "@@ -40,3 +40,4 @@\n+    retry(3)",
+                "body": "Make the count configurable."
+            }]),
+        );
+
+        let mut settings = IssueSettings::default();
+        settings.github.base = exports.to_string_lossy().to_string();
+        settings.cache_dir = Some(dir.join("cache"));
+        let handler = IssueHandler::new(settings);
+
+        let artifact = handler
+            .parse("issue:github:acme/widgets!34", SourceOptions::default())
+            .await
+            .unwrap();
+        let content = handler.load(&artifact).await.unwrap();
+
+        assert!(content.starts_with("# Retry failed uploads (acme/widgets!34)\n"));
+        assert!(content.contains(
+            "**State:** merged · **Author:** @alice · **Labels:** bug · **Branches:** retry-uploads → main"
+        ));
+        assert!(content.contains("Fixes #12."));
+        assert!(content.contains("## Comments (1)\n\n### @bob · 2024-03-01\n\nNeeds a test."));
+        assert!(content.contains("- @carol: APPROVED\n"));
+        assert!(content.contains("### @carol on `src/upload.rs:42` · 2024-03-02"));
+        assert!(content.contains("```diff\n@@ -40,3 +40,4 @@\n+    retry(3)\n```"));
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    /// Serves `routes` over HTTP, requiring `PRIVATE-TOKEN: secret`; counts requests
+    async fn mock_server(routes: Vec<(&'static str, Value)>) -> (String, Arc<AtomicUsize>) {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let hits = Arc::new(AtomicUsize::new(0));
+        let counter = hits.clone();
+
+        tokio::spawn(async move {
+            while let Ok((mut socket, _)) = listener.accept().await {
+                counter.fetch_add(1, Ordering::SeqCst);
+                let mut buf = vec![0u8; 8192];
+                let n = socket.read(&mut buf).await.unwrap_or(0);
+                let request = String::from_utf8_lossy(&buf[..n]).to_string();
+                let path = request.split_whitespace().nth(1).unwrap_or("/");
+                let path = path.split('?').next().unwrap_or(path);
+
+                let authorized = request
+                    .lines()
+                    .any(|l| l.eq_ignore_ascii_case("private-token: secret"));
+                let body = routes
+                    .iter()
+                    .find(|(route, _)| *route == path)
+                    .map(|(_, body)| body.to_string());
+                let (status, body) = match body {
+                    Some(_) if !authorized => ("401 Unauthorized", "{}".to_string()),
+                    Some(body) => ("200 OK", body),
+                    None => ("404 Not Found", "{}".to_string()),
+                };
+
+                let response = format!(
+                    "HTTP/1.1 {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                    status,
+                    body.len(),
+                    body
+                );
+                let _ = socket.write_all(response.as_bytes()).await;
+            }
+        });
+
+        (format!("http://{}/api/v4", addr), hits)
+    }
+
+    #[tokio::test]
+    async fn test_gitlab_merge_request_from_api_is_cached() {
+        let (base, hits) = mock_server(vec![
+            (
+                "/api/v4/projects/group%2Fproject/merge_requests/7",
+                serde_json::json!({
+                    "title": "Add search",
+                    "description": "",
+                    "state": "opened",
+                    "author": {"username": "dana"},
+                    "web_url": "https://gitlab.example/group/project/-/merge_requests/7",
+                    "labels": ["feature"],
+                    "source_branch": "search",
+                    "target_branch": "main"
+                }),
+            ),
+            (
+                "/api/v4/projects/group%2Fproject/merge_requests/7/notes",
+                serde_json::json!([
+                    {"author": {"username": "dana"}, "body": "added 2 commits", "system": true, "created_at": "2024-05-01T00:00:00Z"},
+                    {"author": {"username": "erin"}, "body": "Looks good overall.", "system": false, "created_at": "2024-05-02T00:00:00Z"},
+                    {"author": {"username": "erin"}, "body": "Off by one?", "system": false, "created_at": "2024-05-02T00:00:00Z",
+                     "position": {"new_path": "src/search.rs", "new_line": 18, "old_line": null}}
+                ]),
+            ),
+        ])
+        .await;
+
+        let dir = temp_dir("gitlab");
+        // SAFETY: no other test reads or writes this variable
+        unsafe { std::env::set_var("CTX_TEST_GITLAB_TOKEN", "secret") };
+        let handler = IssueHandler::new(IssueSettings {
+            gitlab: IssueApi {
+                base,
+                token_env: "CTX_TEST_GITLAB_TOKEN".to_string(),
+            },
+            cache_dir: Some(dir.join("cache")),
+            ..Default::default()
+        });
+
+        let artifact = handler
+            .parse("issue:gitlab:group/project!7", SourceOptions::default())
+            .await
+            .unwrap();
+        let content = handler.load(&artifact).await.unwrap();
+
+        assert!(content.starts_with("# Add search (group/project!7)\n"));
+        assert!(content.contains("**Branches:** search → main"));
+        assert!(content.contains("_No description._"));
+        assert!(!content.contains("added 2 commits"));
+        assert!(
+            content.contains("## Comments (1)\n\n### @erin · 2024-05-02\n\nLooks good overall.")
+        );
+        assert!(content.contains("### @erin on `src/search.rs:18`"));
+
+        // Served from the cache the second time
+        let requests = hits.load(Ordering::SeqCst);
+        assert_eq!(requests, 2);
+        assert_eq!(handler.load(&artifact).await.unwrap(), content);
+        assert_eq!(hits.load(Ordering::SeqCst), requests);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
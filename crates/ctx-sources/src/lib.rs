@@ -1,10 +1,26 @@
+pub mod anchor;
+pub mod capture;
 pub mod collection;
+pub mod command;
+pub mod crawl;
 pub mod denylist;
+pub mod document;
 pub mod file;
 pub mod git;
 pub mod handler;
+pub mod issue;
+pub mod metadata;
+pub mod plugin;
+pub mod symbol;
+pub mod syntax;
 pub mod text;
+pub mod tree;
 pub mod url;
 
+pub use command::CommandPolicy;
 pub use denylist::Denylist;
-pub use handler::{SourceHandler, SourceHandlerRegistry, SourceOptions};
+pub use file::FileSettings;
+pub use handler::{
+    SourceHandler, SourceHandlerRegistry, SourceOptions, SourceSettings, anchor_uri,
+};
+pub use issue::IssueSettings;
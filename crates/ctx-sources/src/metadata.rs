@@ -0,0 +1,323 @@
+use ctx_core::{Artifact, ArtifactType, FileInfo};
+use std::path::Path;
+use time::OffsetDateTime;
+use tokio::process::Command;
+
+/// Language names by extension; names double as Markdown code fence tags
+const EXTENSIONS: &[(&str, &str)] = &[
+    ("rs", "rust"),
+    ("ts", "typescript"),
+    ("mts", "typescript"),
+    ("cts", "typescript"),
+    ("tsx", "tsx"),
+    ("js", "javascript"),
+    ("mjs", "javascript"),
+    ("cjs", "javascript"),
+    ("jsx", "jsx"),
+    ("py", "python"),
+    ("pyi", "python"),
+    ("go", "go"),
+    ("java", "java"),
+    ("kt", "kotlin"),
+    ("kts", "kotlin"),
+    ("scala", "scala"),
+    ("c", "c"),
+    ("h", "c"),
+    ("cc", "cpp"),
+    ("cpp", "cpp"),
+    ("cxx", "cpp"),
+    ("hh", "cpp"),
+    ("hpp", "cpp"),
+    ("hxx", "cpp"),
+    ("cs", "csharp"),
+    ("swift", "swift"),
+    ("m", "objectivec"),
+    ("rb", "ruby"),
+    ("php", "php"),
+    ("pl", "perl"),
+    ("pm", "perl"),
+    ("lua", "lua"),
+    ("r", "r"),
+    ("dart", "dart"),
+    ("ex", "elixir"),
+    ("exs", "elixir"),
+    ("erl", "erlang"),
+    ("hs", "haskell"),
+    ("ml", "ocaml"),
+    ("clj", "clojure"),
+    ("zig", "zig"),
+    ("nim", "nim"),
+    ("sh", "bash"),
+    ("bash", "bash"),
+    ("zsh", "zsh"),
+    ("fish", "fish"),
+    ("ps1", "powershell"),
+    ("sql", "sql"),
+    ("html", "html"),
+    ("htm", "html"),
+    ("css", "css"),
+    ("scss", "scss"),
+    ("less", "less"),
+    ("vue", "vue"),
+    ("svelte", "svelte"),
+    ("json", "json"),
+    ("jsonc", "json"),
+    ("yaml", "yaml"),
+    ("yml", "yaml"),
+    ("toml", "toml"),
+    ("xml", "xml"),
+    ("ini", "ini"),
+    ("proto", "protobuf"),
+    ("graphql", "graphql"),
+    ("gql", "graphql"),
+    ("tf", "hcl"),
+    ("md", "markdown"),
+    ("markdown", "markdown"),
+];
+
+/// Languages of files recognized by name alone
+const FILE_NAMES: &[(&str, &str)] = &[
+    ("Dockerfile", "dockerfile"),
+    ("Makefile", "makefile"),
+    ("GNUmakefile", "makefile"),
+    ("CMakeLists.txt", "cmake"),
+    ("Gemfile", "ruby"),
+    ("Rakefile", "ruby"),
+    ("Cargo.lock", "toml"),
+];
+
+/// Languages of interpreters named in a `#!` line
+const INTERPRETERS: &[(&str, &str)] = &[
+    ("python", "python"),
+    ("bash", "bash"),
+    ("sh", "bash"),
+    ("zsh", "zsh"),
+    ("fish", "fish"),
+    ("node", "javascript"),
+    ("deno", "typescript"),
+    ("ruby", "ruby"),
+    ("perl", "perl"),
+    ("php", "php"),
+    ("lua", "lua"),
+];
+
+/// Language of a file from its extension, its name, or the shebang on its first line
+pub fn detect_language(path: &str, content: &str) -> Option<&'static str> {
+    let path = Path::new(path);
+    let name = path.file_name()?.to_string_lossy();
+    if let Some((_, language)) = FILE_NAMES.iter().find(|(n, _)| *n == name) {
+        return Some(language);
+    }
+    if let Some(ext) = path.extension() {
+        let ext = ext.to_string_lossy().to_ascii_lowercase();
+        if let Some((_, language)) = EXTENSIONS.iter().find(|(e, _)| *e == ext) {
+            return Some(language);
+        }
+    }
+    shebang_language(content.lines().next()?)
+}
+
+fn shebang_language(line: &str) -> Option<&'static str> {
+    let mut words = line.strip_prefix("#!")?.split_whitespace();
+    let mut program = words.next()?.rsplit('/').next()?;
+    // `#!/usr/bin/env -S python3 -u`
+    if program == "env" {
+        program = words.find(|w| !w.starts_with('-'))?;
+    }
+    // python3.12 -> python
+    let program = program.trim_end_matches(|c: char| c.is_ascii_digit() || c == '.');
+    INTERPRETERS
+        .iter()
+        .find(|(name, _)| *name == program)
+        .map(|(_, language)| *language)
+}
+
+/// MIME type for text in `language`
+pub fn mime_type(language: Option<&str>) -> String {
+    match language {
+        None => "text/plain",
+        Some("markdown") => "text/markdown",
+        Some("html") => "text/html",
+        Some("css") => "text/css",
+        Some("json") => "application/json",
+        Some("xml") => "application/xml",
+        Some(language) => return format!("text/x-{}", language),
+    }
+    .to_string()
+}
+
+/// Language, line count and modification time of `path`, whose rendered text is `content`
+pub fn file_info(path: &str, content: &str) -> FileInfo {
+    FileInfo {
+        language: detect_language(path, content).map(str::to_string),
+        rendered_lines: content.lines().count(),
+        modified: std::fs::metadata(path)
+            .and_then(|m| m.modified())
+            .ok()
+            .map(OffsetDateTime::from),
+        ..Default::default()
+    }
+}
+
+/// Fill in the last commit to touch `path`. It's only looked up again when `HEAD` moved
+/// since `previous`, so unchanged checkouts cost one cheap git call per render.
+pub async fn add_git_history(info: &mut FileInfo, path: &str, previous: Option<&FileInfo>) {
+    let Some(head) = head_commit(path).await else {
+        return;
+    };
+    if let Some(previous) = previous
+        && previous.git_head.as_ref() == Some(&head)
+    {
+        info.git_commit = previous.git_commit.clone();
+        info.git_author = previous.git_author.clone();
+    } else if let Some((commit, author)) = last_commit(path).await {
+        info.git_commit = Some(commit);
+        info.git_author = Some(author);
+    }
+    info.git_head = Some(head);
+}
+
+/// Facts about a file-backed artifact's source as of now, or None if it has no file
+/// or the file can't be read
+pub async fn refresh(artifact: &Artifact, content: &str) -> Option<FileInfo> {
+    let path = match &artifact.artifact_type {
+        ArtifactType::File { path, .. }
+        | ArtifactType::FileRange { path, .. }
+        | ArtifactType::Markdown { path }
+        | ArtifactType::Symbol { path, .. } => path,
+        _ => return None,
+    };
+    if !Path::new(path).is_file() {
+        return None;
+    }
+    let mut info = file_info(path, content);
+    add_git_history(&mut info, path, artifact.metadata.file.as_ref()).await;
+    Some(info)
+}
+
+/// Commit checked out in the repository containing `path`; None outside a git repository
+async fn head_commit(path: &str) -> Option<String> {
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(Path::new(path).parent()?)
+        .args(["rev-parse", "HEAD"])
+        .output()
+        .await
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    Some(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+/// Hash and author name of the last commit touching `path`; None outside a git repository
+async fn last_commit(path: &str) -> Option<(String, String)> {
+    let path = Path::new(path);
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(path.parent()?)
+        .args(["log", "-1", "--format=%H%x00%an", "--"])
+        .arg(path.file_name()?)
+        .output()
+        .await
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let (commit, author) = stdout.trim_end().split_once('\0')?;
+    Some((commit.to_string(), author.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_detect_language() {
+        assert_eq!(detect_language("src/main.rs", ""), Some("rust"));
+        assert_eq!(detect_language("web/App.TSX", ""), Some("tsx"));
+        assert_eq!(detect_language("docker/Dockerfile", ""), Some("dockerfile"));
+        assert_eq!(
+            detect_language("bin/deploy", "#!/usr/bin/env -S python3.12 -u\n"),
+            Some("python")
+        );
+        assert_eq!(
+            detect_language("bin/run", "#!/bin/sh\nset -e\n"),
+            Some("bash")
+        );
+        assert_eq!(detect_language("notes.txt", "plain"), None);
+        assert_eq!(mime_type(Some("rust")), "text/x-rust");
+        assert_eq!(mime_type(None), "text/plain");
+    }
+
+    #[tokio::test]
+    async fn test_git_history() {
+        let dir = std::env::temp_dir().join(format!("ctx-metadata-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let file = dir.join("tool.py");
+        std::fs::write(&file, "print('hi')\n").unwrap();
+        let path = file.to_string_lossy().to_string();
+
+        let git = |args: &[&str]| {
+            std::process::Command::new("git")
+                .arg("-C")
+                .arg(&dir)
+                .args(args)
+                .output()
+                .unwrap()
+        };
+        git(&["init", "-q"]);
+        git(&["add", "."]);
+        git(&[
+            "-c",
+            "user.name=Ada Lovelace",
+            "-c",
+            "user.email=ada@example.com",
+            "commit",
+            "-qm",
+            "init",
+        ]);
+
+        let mut info = file_info(&path, "print('hi')\n");
+        assert_eq!(info.language.as_deref(), Some("python"));
+        assert_eq!(info.rendered_lines, 1);
+        assert!(info.modified.is_some());
+
+        add_git_history(&mut info, &path, None).await;
+        assert_eq!(info.git_author.as_deref(), Some("Ada Lovelace"));
+        assert_eq!(info.git_commit.as_ref().map(String::len), Some(40));
+
+        assert_eq!(info.git_head, info.git_commit);
+
+        // Same HEAD as last time: reused without looking through history
+        let previous = FileInfo {
+            git_commit: Some("cached".to_string()),
+            ..info.clone()
+        };
+        let mut again = file_info(&path, "print('hi')\n");
+        add_git_history(&mut again, &path, Some(&previous)).await;
+        assert_eq!(again.git_commit.as_deref(), Some("cached"));
+
+        // An amend leaves the file's mtime alone but moves HEAD
+        git(&[
+            "-c",
+            "user.name=Grace Hopper",
+            "-c",
+            "user.email=grace@example.com",
+            "commit",
+            "-q",
+            "--amend",
+            "--reset-author",
+            "-m",
+            "amended",
+        ]);
+        let mut amended = file_info(&path, "print('hi')\n");
+        assert_eq!(amended.modified, info.modified);
+        add_git_history(&mut amended, &path, Some(&previous)).await;
+        assert_eq!(amended.git_author.as_deref(), Some("Grace Hopper"));
+        assert_ne!(amended.git_commit, info.git_commit);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,369 @@
+//! External source handlers speaking JSON over stdio.
+//!
+//! Each call runs the plugin executable once, writes a single JSON request line to its stdin and
+//! reads a single JSON response from its stdout:
+//!
+//! ```text
+//! {"method":"can_handle","uri":"jira:PROJ-1"}           -> {"can_handle":true}
+//! {"method":"parse","uri":"jira:PROJ-1"}                -> {"data":{...},"mime_type":"text/markdown"}
+//! {"method":"load","uri":"jira:PROJ-1","data":{...}}    -> {"content":"..."}
+//! ```
+//!
+//! `data` from `parse` is stored with the artifact and passed back on every `load`. Any response
+//! may be `{"error":"message"}` instead. Only URIs with the plugin's scheme are offered to it, and
+//! `can_handle` is asked when adding one, just before `parse`.
+
+use async_trait::async_trait;
+use ctx_core::{Artifact, ArtifactMetadata, ArtifactType, Error, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::process::Stdio;
+use std::sync::Mutex;
+use std::time::Duration;
+use tokio::io::AsyncWriteExt;
+
+use crate::command::read_capped;
+use crate::handler::{SourceHandler, SourceOptions};
+
+const CALL_TIMEOUT: Duration = Duration::from_secs(60);
+/// `can_handle` is a quick yes or no, so it gets much less time
+const CAN_HANDLE_TIMEOUT: Duration = Duration::from_secs(5);
+/// Larger responses are refused rather than read into memory
+const MAX_RESPONSE_BYTES: usize = 8 * 1024 * 1024;
+
+/// Source handler backed by an external executable
+pub struct PluginHandler {
+    scheme: String,
+    program: PathBuf,
+    /// Answers to `can_handle`, which is asked once per URI
+    can_handle_cache: Mutex<HashMap<String, bool>>,
+}
+
+#[derive(Serialize)]
+struct PluginRequest<'a> {
+    method: &'a str,
+    uri: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    data: Option<&'a serde_json::Value>,
+}
+
+#[derive(Debug, Deserialize, Default)]
+struct PluginResponse {
+    error: Option<String>,
+    can_handle: Option<bool>,
+    #[serde(default)]
+    data: serde_json::Value,
+    mime_type: Option<String>,
+    content: Option<String>,
+}
+
+impl PluginHandler {
+    /// Handle `<scheme>:` URIs by running `program`
+    pub fn new(scheme: impl Into<String>, program: impl Into<PathBuf>) -> Self {
+        Self {
+            scheme: scheme.into(),
+            program: program.into(),
+            can_handle_cache: Mutex::new(HashMap::new()),
+        }
+    }
+
+    fn request_json(&self, method: &str, uri: &str, data: Option<&serde_json::Value>) -> String {
+        let request = PluginRequest { method, uri, data };
+        let mut json = serde_json::to_string(&request).expect("plugin request serializes");
+        json.push('\n');
+        json
+    }
+
+    async fn call(
+        &self,
+        method: &str,
+        uri: &str,
+        data: Option<&serde_json::Value>,
+        timeout: Duration,
+    ) -> Result<PluginResponse> {
+        let request = self.request_json(method, uri, data);
+
+        let mut child = tokio::process::Command::new(&self.program)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .kill_on_drop(true)
+            .spawn()
+            .map_err(|e| self.error(format!("failed to start: {}", e)))?;
+
+        let stdin = child.stdin.take();
+        let stdout = child.stdout.take();
+        let stderr = child.stderr.take();
+
+        // Write the request while reading, so a plugin that answers before reading can't stall it
+        let run = async {
+            let write = async {
+                if let Some(mut stdin) = stdin {
+                    // A plugin that exits without reading its request is reported by its output
+                    let _ = stdin.write_all(request.as_bytes()).await;
+                }
+            };
+            let ((), out, err) = tokio::join!(
+                write,
+                read_capped(stdout, MAX_RESPONSE_BYTES),
+                read_capped(stderr, MAX_RESPONSE_BYTES)
+            );
+            let (out, err) = (out?, err?);
+            if out.1 {
+                let _ = child.start_kill();
+            }
+            let status = child.wait().await?;
+            Ok::<_, std::io::Error>((status, out, err))
+        };
+
+        let (status, (stdout, truncated), (stderr, _)) =
+            match tokio::time::timeout(timeout, run).await {
+                Ok(result) => result.map_err(|e| self.error(e.to_string()))?,
+                Err(_) => {
+                    let _ = child.kill().await;
+                    return Err(self.error(format!(
+                        "{} timed out after {}s",
+                        method,
+                        timeout.as_secs()
+                    )));
+                }
+            };
+        if truncated {
+            return Err(self.error(format!(
+                "{} response exceeds {} bytes",
+                method, MAX_RESPONSE_BYTES
+            )));
+        }
+
+        self.decode(method, status, &stdout, &stderr)
+    }
+
+    /// Whether the plugin takes `uri`, asked once per URI. Plugins that don't answer take
+    /// every URI with their scheme; `parse` reports real errors.
+    async fn accepts(&self, uri: &str) -> bool {
+        let cached = self
+            .can_handle_cache
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .get(uri)
+            .copied();
+        if let Some(answer) = cached {
+            return answer;
+        }
+
+        let answer = match self.call("can_handle", uri, None, CAN_HANDLE_TIMEOUT).await {
+            Ok(response) => response.can_handle.unwrap_or(true),
+            Err(_) => true,
+        };
+        self.can_handle_cache
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .insert(uri.to_string(), answer);
+        answer
+    }
+
+    fn decode(
+        &self,
+        method: &str,
+        status: std::process::ExitStatus,
+        stdout: &[u8],
+        stderr: &[u8],
+    ) -> Result<PluginResponse> {
+        if !status.success() {
+            let stderr = String::from_utf8_lossy(stderr);
+            return Err(self.error(format!("{} failed ({}): {}", method, status, stderr.trim())));
+        }
+
+        let response: PluginResponse = serde_json::from_slice(stdout)
+            .map_err(|e| self.error(format!("invalid {} response: {}", method, e)))?;
+
+        match response.error {
+            Some(message) => Err(self.error(message)),
+            None => Ok(response),
+        }
+    }
+
+    fn error(&self, message: String) -> Error {
+        Error::Other(anyhow::anyhow!(
+            "Plugin '{}' ({}): {}",
+            self.scheme,
+            self.program.display(),
+            message
+        ))
+    }
+}
+
+#[async_trait]
+impl SourceHandler for PluginHandler {
+    async fn parse(&self, uri: &str, _options: SourceOptions) -> Result<Artifact> {
+        if !self.accepts(uri).await {
+            return Err(Error::InvalidSourceUri(format!(
+                "Plugin '{}' does not handle {}",
+                self.scheme, uri
+            )));
+        }
+        let response = self.call("parse", uri, None, CALL_TIMEOUT).await?;
+
+        let artifact_type = ArtifactType::Plugin {
+            plugin: self.scheme.clone(),
+            data: response.data,
+        };
+
+        let metadata = ArtifactMetadata {
+            size_bytes: 0,
+            mime_type: Some(
+                response
+                    .mime_type
+                    .unwrap_or_else(|| "text/plain".to_string()),
+            ),
+            extra: serde_json::json!({}),
+            ..Default::default()
+        };
+
+        Ok(Artifact::new(artifact_type, uri.to_string()).with_metadata(metadata))
+    }
+
+    async fn load(&self, artifact: &Artifact) -> Result<String> {
+        match &artifact.artifact_type {
+            ArtifactType::Plugin { data, .. } => {
+                let data = (!data.is_null()).then_some(data);
+                let response = self
+                    .call("load", &artifact.source_uri, data, CALL_TIMEOUT)
+                    .await?;
+                response
+                    .content
+                    .ok_or_else(|| self.error("load response has no content".to_string()))
+            }
+            _ => Err(Error::Other(anyhow::anyhow!(
+                "Unsupported artifact type for PluginHandler"
+            ))),
+        }
+    }
+
+    fn can_handle(&self, uri: &str) -> bool {
+        uri.strip_prefix(self.scheme.as_str())
+            .is_some_and(|rest| rest.starts_with(':'))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::os::unix::fs::PermissionsExt;
+
+    /// Echoes each request back as content; `echo:fail` errors and `echo:skip` is declined
+    const ECHO_PLUGIN: &str = r#"#!/bin/sh
+read -r request
+case "$request" in
+  *'"uri":"echo:fail"'*) echo '{"error":"no such item"}' ;;
+  *'"method":"can_handle"'*'"uri":"echo:skip"'*) echo '{"can_handle":false}' ;;
+  *'"method":"can_handle"'*) echo '{"can_handle":true}' ;;
+  *'"method":"parse"'*) echo '{"data":{"id":42},"mime_type":"text/markdown"}' ;;
+  *'"method":"load"'*)
+    escaped=$(printf '%s' "$request" | sed 's/\\/\\\\/g; s/"/\\"/g')
+    printf '{"content":"%s"}\n' "$escaped" ;;
+  *) exit 2 ;;
+esac
+"#;
+
+    fn echo_plugin(name: &str) -> (PathBuf, PluginHandler) {
+        let dir =
+            std::env::temp_dir().join(format!("ctx-plugin-{}-{}", name, uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let program = dir.join("ctx-source-echo");
+        std::fs::write(&program, ECHO_PLUGIN).unwrap();
+        std::fs::set_permissions(&program, std::fs::Permissions::from_mode(0o755)).unwrap();
+        (dir, PluginHandler::new("echo", program))
+    }
+
+    #[tokio::test]
+    async fn test_echo_plugin_round_trip() {
+        let (dir, handler) = echo_plugin("round-trip");
+
+        assert!(handler.can_handle("echo:hello"));
+        assert!(!handler.can_handle("echoes:hello"));
+        assert!(!handler.can_handle("file:echo"));
+
+        let artifact = handler
+            .parse("echo:hello", SourceOptions::default())
+            .await
+            .unwrap();
+        assert_eq!(
+            artifact.metadata.mime_type.as_deref(),
+            Some("text/markdown")
+        );
+        match &artifact.artifact_type {
+            ArtifactType::Plugin { plugin, data } => {
+                assert_eq!(plugin, "echo");
+                assert_eq!(data, &serde_json::json!({"id": 42}));
+            }
+            other => panic!("unexpected type: {:?}", other),
+        }
+
+        let content = handler.load(&artifact).await.unwrap();
+        assert_eq!(
+            content,
+            r#"{"method":"load","uri":"echo:hello","data":{"id":42}}"#
+        );
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_plugin_errors() {
+        let (dir, handler) = echo_plugin("errors");
+
+        let err = handler
+            .parse("echo:fail", SourceOptions::default())
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("Plugin 'echo'"));
+        assert!(err.to_string().contains("no such item"));
+
+        let err = handler
+            .parse("echo:skip", SourceOptions::default())
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("does not handle echo:skip"));
+
+        let missing = PluginHandler::new("echo", dir.join("does-not-exist"));
+        let err = missing
+            .parse("echo:hello", SourceOptions::default())
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("failed to start"));
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_plugin_stalls_and_floods() {
+        let dir = std::env::temp_dir().join(format!("ctx-plugin-limits-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let plugin = |name: &str, script: &str| {
+            let program = dir.join(name);
+            std::fs::write(&program, script).unwrap();
+            std::fs::set_permissions(&program, std::fs::Permissions::from_mode(0o755)).unwrap();
+            PluginHandler::new("slow", program)
+        };
+
+        // Never reads a request bigger than the pipe buffer: the write is under the timeout too
+        let stalled = plugin("ctx-source-stall", "#!/bin/sh\nsleep 10\n");
+        let data = serde_json::json!({ "blob": "x".repeat(1024 * 1024) });
+        let err = stalled
+            .call("load", "slow:1", Some(&data), Duration::from_millis(300))
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("load timed out"));
+
+        let flood = plugin("ctx-source-flood", "#!/bin/sh\nexec yes\n");
+        let err = flood
+            .call("load", "slow:1", None, CALL_TIMEOUT)
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("load response exceeds"));
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,208 @@
+use async_trait::async_trait;
+use ctx_core::{Artifact, ArtifactMetadata, ArtifactType, Error, Result};
+
+use crate::file::{FileHandler, FileSettings};
+use crate::handler::{SourceHandler, SourceOptions};
+use crate::metadata;
+use crate::syntax::{self, Language};
+
+/// Handles `symbol:path::Item::member` sources, resolved with tree-sitter on every render
+#[derive(Default)]
+pub struct SymbolHandler {
+    /// Reads the file, with the same size limit and binary check as `file:` sources
+    files: FileHandler,
+}
+
+impl SymbolHandler {
+    pub fn new(settings: FileSettings) -> Self {
+        Self {
+            files: FileHandler::new(settings),
+        }
+    }
+
+    /// Read `path` and return the source text of `symbol`, including doc comments
+    async fn extract(&self, path: &str, symbol: &str) -> Result<String> {
+        let source = self.files.read_text(path, None, None).await?;
+        Ok(locate_symbol(path, &source, symbol)?.1)
+    }
+}
+
+#[async_trait]
+impl SourceHandler for SymbolHandler {
+    async fn parse(&self, uri: &str, _options: SourceOptions) -> Result<Artifact> {
+        let spec = uri
+            .strip_prefix("symbol:")
+            .ok_or_else(|| Error::InvalidSourceUri(format!("Invalid symbol URI: {}", uri)))?;
+
+        let (relative_path, symbol) = spec.split_once("::").ok_or_else(|| {
+            Error::InvalidSourceUri(format!(
+                "Expected symbol:<path>::<Item>[::<member>], got: {}",
+                uri
+            ))
+        })?;
+
+        if symbol.is_empty() || symbol.split("::").any(str::is_empty) {
+            return Err(Error::InvalidSourceUri(format!(
+                "Invalid symbol path: {}",
+                symbol
+            )));
+        }
+
+        let file_path = std::fs::canonicalize(relative_path)
+            .map_err(|e| {
+                Error::Other(anyhow::anyhow!(
+                    "Failed to resolve absolute path for {}: {}",
+                    relative_path,
+                    e
+                ))
+            })?
+            .to_string_lossy()
+            .to_string();
+
+        // Resolve now so a typo fails at add time rather than at render time
+        let content = self.extract(&file_path, symbol).await?;
+        let content_hash = blake3::hash(content.as_bytes()).to_hex().to_string();
+
+        let artifact_type = ArtifactType::Symbol {
+            path: file_path.clone(),
+            symbol: symbol.to_string(),
+        };
+
+        let file = metadata::file_info(&file_path, &content);
+        let metadata = ArtifactMetadata {
+            size_bytes: content.len(),
+            mime_type: Some(metadata::mime_type(file.language.as_deref())),
+            file: Some(file),
+            extra: serde_json::json!({}),
+            ..Default::default()
+        };
+
+        Ok(Artifact::new(artifact_type, uri.to_string())
+            .with_hash(content_hash)
+            .with_metadata(metadata))
+    }
+
+    async fn load(&self, artifact: &Artifact) -> Result<String> {
+        match &artifact.artifact_type {
+            ArtifactType::Symbol { path, symbol } => self.extract(path, symbol).await,
+            _ => Err(Error::Other(anyhow::anyhow!(
+                "Unsupported artifact type for SymbolHandler"
+            ))),
+        }
+    }
+
+    fn can_handle(&self, uri: &str) -> bool {
+        uri.starts_with("symbol:")
+    }
+}
+
+/// Source text of `symbol` in `source`, the text of `path`, and the 1-based line it starts on
+pub fn locate_symbol(path: &str, source: &str, symbol: &str) -> Result<(usize, String)> {
+    let language = Language::from_path(path).ok_or_else(|| {
+        Error::InvalidSourceUri(format!(
+            "Symbol sources support Rust, TypeScript/JavaScript and Python files, got: {}",
+            path
+        ))
+    })?;
+
+    let segments: Vec<&str> = symbol.split("::").collect();
+    let span = syntax::find_symbol(language, source, &segments)?.ok_or_else(|| {
+        Error::Other(anyhow::anyhow!("Symbol '{}' not found in {}", symbol, path))
+    })?;
+
+    let line = source[..span.start_byte].matches('\n').count() + 1;
+    Ok((line, source[span.start_byte..span.end_byte].to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_symbol_resolved_at_load_time() {
+        let dir = std::env::temp_dir().join(format!("ctx-symbol-test-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let file = dir.join("auth.rs");
+        std::fs::write(
+            &file,
+            "struct AuthService;\n\nimpl AuthService {\n    fn login(&self) {}\n}\n",
+        )
+        .unwrap();
+
+        let uri = format!("symbol:{}::AuthService::login", file.display());
+        let artifact = SymbolHandler::default()
+            .parse(&uri, SourceOptions::default())
+            .await
+            .unwrap();
+        assert_eq!(
+            SymbolHandler::default().load(&artifact).await.unwrap(),
+            "    fn login(&self) {}"
+        );
+
+        // Edits above the item don't break it
+        std::fs::write(
+            &file,
+            "use std::fmt;\n\nstruct AuthService;\n\nimpl AuthService {\n    fn new() -> Self { Self }\n\n    fn login(&self) -> bool { true }\n}\n",
+        )
+        .unwrap();
+        assert_eq!(
+            SymbolHandler::default().load(&artifact).await.unwrap(),
+            "    fn login(&self) -> bool { true }"
+        );
+
+        // Removing it fails with a clear error
+        std::fs::write(&file, "struct AuthService;\n").unwrap();
+        let err = SymbolHandler::default().load(&artifact).await.unwrap_err();
+        assert!(
+            err.to_string()
+                .contains("Symbol 'AuthService::login' not found")
+        );
+
+        let missing = format!("symbol:{}::Nope", file.display());
+        assert!(
+            SymbolHandler::default()
+                .parse(&missing, SourceOptions::default())
+                .await
+                .is_err()
+        );
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_symbol_file_read_like_file_sources() {
+        let dir = std::env::temp_dir().join(format!("ctx-symbol-limit-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let file = dir.join("big.rs");
+        std::fs::write(
+            &file,
+            format!(
+                "fn small() {{}}
+{}",
+                "// filler\n".repeat(100)
+            ),
+        )
+        .unwrap();
+
+        let handler = SymbolHandler::new(FileSettings {
+            max_file_bytes: 256,
+            ..Default::default()
+        });
+        let uri = format!("symbol:{}::small", file.display());
+        let err = handler
+            .parse(&uri, SourceOptions::default())
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("over the 256 B file limit"));
+
+        // Binary files are refused rather than parsed
+        std::fs::write(&file, b"fn small() {}\n\0\0").unwrap();
+        let err = handler
+            .parse(&uri, SourceOptions::default())
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("binary"));
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,589 @@
+use ctx_core::{Error, RenderMode, Result};
+use tree_sitter::{Node, Parser, Tree};
+
+/// Languages with tree-sitter support (the same set ctx-suggest parses imports for)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Language {
+    Rust,
+    TypeScript,
+    Tsx,
+    Python,
+}
+
+impl Language {
+    pub fn from_path(path: &str) -> Option<Self> {
+        let ext = std::path::Path::new(path).extension()?.to_str()?;
+        match ext {
+            "rs" => Some(Self::Rust),
+            "ts" | "mts" | "cts" | "js" | "mjs" | "cjs" => Some(Self::TypeScript),
+            "tsx" | "jsx" => Some(Self::Tsx),
+            "py" => Some(Self::Python),
+            _ => None,
+        }
+    }
+
+    fn grammar(&self) -> tree_sitter::Language {
+        match self {
+            Self::Rust => tree_sitter_rust::LANGUAGE.into(),
+            Self::TypeScript => tree_sitter_typescript::LANGUAGE_TYPESCRIPT.into(),
+            Self::Tsx => tree_sitter_typescript::LANGUAGE_TSX.into(),
+            Self::Python => tree_sitter_python::LANGUAGE.into(),
+        }
+    }
+
+    pub fn parse(&self, source: &str) -> Result<Tree> {
+        let mut parser = Parser::new();
+        parser
+            .set_language(&self.grammar())
+            .map_err(|e| Error::Other(anyhow::anyhow!("Failed to load grammar: {}", e)))?;
+        parser
+            .parse(source, None)
+            .ok_or_else(|| Error::Other(anyhow::anyhow!("Failed to parse {:?} source", self)))
+    }
+
+    /// Name of the item declared by `node`, or None if it isn't a named item
+    fn item_name(&self, node: Node, source: &[u8]) -> Option<String> {
+        let field = match (self, node.kind()) {
+            (
+                Self::Rust,
+                "function_item"
+                | "function_signature_item"
+                | "struct_item"
+                | "enum_item"
+                | "union_item"
+                | "trait_item"
+                | "type_item"
+                | "const_item"
+                | "static_item"
+                | "mod_item"
+                | "macro_definition"
+                | "associated_type",
+            ) => "name",
+            (Self::Rust, "impl_item") => "type",
+            (
+                Self::TypeScript | Self::Tsx,
+                "function_declaration"
+                | "generator_function_declaration"
+                | "function_signature"
+                | "class_declaration"
+                | "abstract_class_declaration"
+                | "interface_declaration"
+                | "type_alias_declaration"
+                | "enum_declaration"
+                | "internal_module"
+                | "method_definition"
+                | "method_signature"
+                | "abstract_method_signature"
+                | "public_field_definition"
+                | "variable_declarator",
+            ) => "name",
+            (Self::Python, "function_definition" | "class_definition") => "name",
+            _ => return None,
+        };
+
+        let name = node.child_by_field_name(field)?.utf8_text(source).ok()?;
+        if node.kind() == "impl_item" {
+            // `impl<T> foo::Bar<T>` is addressed as `Bar`
+            let name = name.split('<').next().unwrap_or(name);
+            return Some(name.rsplit("::").next().unwrap_or(name).trim().to_string());
+        }
+        Some(name.to_string())
+    }
+
+    /// Nodes that wrap a declaration and belong to its span
+    fn is_wrapper(&self, kind: &str) -> bool {
+        matches!(
+            kind,
+            "export_statement"
+                | "lexical_declaration"
+                | "variable_declaration"
+                | "decorated_definition"
+        )
+    }
+
+    /// Siblings directly above an item that document it (doc comments, attributes)
+    fn is_leading_trivia(&self, kind: &str) -> bool {
+        match self {
+            Self::Rust => matches!(kind, "line_comment" | "block_comment" | "attribute_item"),
+            Self::TypeScript | Self::Tsx => kind == "comment",
+            Self::Python => false,
+        }
+    }
+}
+
+/// Location of a resolved item, including its doc comments and attributes
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Span {
+    /// Byte offset of the start of the first line
+    pub start_byte: usize,
+    pub end_byte: usize,
+    /// 1-indexed, inclusive
+    pub start_line: usize,
+    pub end_line: usize,
+}
+
+/// Find an item by its `::`-separated path (e.g. `["AuthService", "login"]`)
+pub fn find_symbol(language: Language, source: &str, path: &[&str]) -> Result<Option<Span>> {
+    let tree = language.parse(source)?;
+    let bytes = source.as_bytes();
+
+    let Some(mut node) = resolve(language, tree.root_node(), bytes, path) else {
+        return Ok(None);
+    };
+
+    while let Some(parent) = node.parent() {
+        if language.is_wrapper(parent.kind()) {
+            node = parent;
+        } else {
+            break;
+        }
+    }
+
+    let mut start = node;
+    while let Some(prev) = start.prev_named_sibling() {
+        if language.is_leading_trivia(prev.kind())
+            && prev.end_position().row + 1 >= start.start_position().row
+        {
+            start = prev;
+        } else {
+            break;
+        }
+    }
+
+    let line_start = source[..start.start_byte()]
+        .rfind('\n')
+        .map(|i| i + 1)
+        .unwrap_or(0);
+
+    Ok(Some(Span {
+        start_byte: line_start,
+        end_byte: node.end_byte(),
+        start_line: start.start_position().row + 1,
+        end_line: node.end_position().row + 1,
+    }))
+}
+
+fn resolve<'a>(
+    language: Language,
+    scope: Node<'a>,
+    source: &[u8],
+    path: &[&str],
+) -> Option<Node<'a>> {
+    let (first, rest) = path.split_first()?;
+    let mut candidates = Vec::new();
+    collect_items(language, scope, source, Some(first), &mut candidates);
+
+    if rest.is_empty() {
+        // A type name should point at its declaration rather than an impl block
+        return candidates
+            .iter()
+            .find(|n| n.kind() != "impl_item")
+            .or(candidates.first())
+            .copied();
+    }
+
+    candidates
+        .into_iter()
+        .find_map(|candidate| resolve(language, candidate, source, rest))
+}
+
+/// Collect items in `scope` (only those called `name`, if given), without
+/// descending into other items
+fn collect_items<'a>(
+    language: Language,
+    scope: Node<'a>,
+    source: &[u8],
+    name: Option<&str>,
+    out: &mut Vec<Node<'a>>,
+) {
+    let mut cursor = scope.walk();
+    for child in scope.named_children(&mut cursor) {
+        match language.item_name(child, source) {
+            Some(item) if name.is_none_or(|name| item == name) => out.push(child),
+            Some(_) => {}
+            None => collect_items(language, child, source, name, out),
+        }
+    }
+}
+
+/// Render `source` in a reduced mode; `RenderMode::Full` returns it unchanged
+pub fn skeleton(language: Language, source: &str, mode: RenderMode) -> Result<String> {
+    match mode {
+        RenderMode::Full => Ok(source.to_string()),
+        RenderMode::Outline => outline(language, source),
+        RenderMode::Signatures => signatures(language, source),
+    }
+}
+
+/// Keep everything except function bodies, which become `{ ... }` (or `...` in Python)
+fn outline(language: Language, source: &str) -> Result<String> {
+    let tree = language.parse(source)?;
+    let mut elisions = Vec::new();
+    collect_elisions(language, tree.root_node(), &mut elisions);
+
+    let mut out = String::with_capacity(source.len());
+    let mut pos = 0;
+    for (start, end, replacement) in elisions {
+        out.push_str(&source[pos..start]);
+        out.push_str(&replacement);
+        pos = end;
+    }
+    out.push_str(&source[pos..]);
+    Ok(out)
+}
+
+/// Byte ranges of function bodies to replace, in source order
+fn collect_elisions(language: Language, node: Node, out: &mut Vec<(usize, usize, String)>) {
+    if let Some(body) = function_body(language, node) {
+        out.push(match language {
+            Language::Python => python_elision(body),
+            _ => (body.start_byte(), body.end_byte(), "{ ... }".to_string()),
+        });
+        return;
+    }
+
+    let mut cursor = node.walk();
+    for child in node.named_children(&mut cursor) {
+        collect_elisions(language, child, out);
+    }
+}
+
+/// Block body of a function-like node
+fn function_body(language: Language, node: Node) -> Option<Node> {
+    let is_function = match language {
+        Language::Rust => node.kind() == "function_item",
+        Language::TypeScript | Language::Tsx => matches!(
+            node.kind(),
+            "function_declaration"
+                | "generator_function_declaration"
+                | "function_expression"
+                | "generator_function"
+                | "method_definition"
+                | "arrow_function"
+        ),
+        Language::Python => node.kind() == "function_definition",
+    };
+    if !is_function {
+        return None;
+    }
+
+    let body = node.child_by_field_name("body")?;
+    // Expression-bodied arrow functions are already signatures
+    matches!(body.kind(), "block" | "statement_block").then_some(body)
+}
+
+/// Keep a Python function's docstring and replace the rest of its body with `...`
+fn python_elision(body: Node) -> (usize, usize, String) {
+    let docstring = body.named_child(0).filter(|stmt| {
+        stmt.kind() == "expression_statement"
+            && stmt.named_child(0).is_some_and(|e| e.kind() == "string")
+    });
+
+    match docstring {
+        Some(doc) => {
+            let indent = " ".repeat(doc.start_position().column);
+            (doc.end_byte(), body.end_byte(), format!("\n{}...", indent))
+        }
+        None => (body.start_byte(), body.end_byte(), "...".to_string()),
+    }
+}
+
+/// One line per item, with members of impls, traits, classes and modules nested
+fn signatures(language: Language, source: &str) -> Result<String> {
+    let tree = language.parse(source)?;
+    let mut lines = Vec::new();
+    collect_signatures(language, tree.root_node(), source, 0, &mut lines);
+    Ok(lines.join("\n"))
+}
+
+fn collect_signatures(
+    language: Language,
+    scope: Node,
+    source: &str,
+    depth: usize,
+    out: &mut Vec<String>,
+) {
+    let mut items = Vec::new();
+    collect_items(language, scope, source.as_bytes(), None, &mut items);
+
+    for item in items {
+        out.push(format!(
+            "{}{}",
+            "    ".repeat(depth),
+            signature_line(language, item, source)
+        ));
+
+        let is_container = matches!(
+            item.kind(),
+            "impl_item"
+                | "trait_item"
+                | "mod_item"
+                | "class_declaration"
+                | "abstract_class_declaration"
+                | "interface_declaration"
+                | "internal_module"
+                | "class_definition"
+        );
+        if is_container && let Some(body) = item.child_by_field_name("body") {
+            collect_signatures(language, body, source, depth + 1, out);
+        }
+    }
+}
+
+/// Item header up to its body, collapsed onto a single line
+fn signature_line(language: Language, item: Node, source: &str) -> String {
+    // Include `export`/`const` but not decorators
+    let mut start = item;
+    while let Some(parent) = start.parent() {
+        if language.is_wrapper(parent.kind()) && parent.kind() != "decorated_definition" {
+            start = parent;
+        } else {
+            break;
+        }
+    }
+
+    let body = if item.kind() == "variable_declarator" {
+        item.child_by_field_name("value")
+            .filter(|v| matches!(v.kind(), "arrow_function" | "function_expression"))
+            .and_then(|v| v.child_by_field_name("body"))
+            .filter(|b| b.kind() == "statement_block")
+    } else {
+        item.child_by_field_name("body")
+    };
+
+    let header = match body {
+        Some(body) => &source[start.start_byte()..body.start_byte()],
+        None => source[start.start_byte()..start.end_byte()]
+            .lines()
+            .next()
+            .unwrap_or(""),
+    };
+
+    header.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn extract(language: Language, source: &str, path: &str) -> Option<String> {
+        let path: Vec<&str> = path.split("::").collect();
+        find_symbol(language, source, &path)
+            .unwrap()
+            .map(|span| source[span.start_byte..span.end_byte].to_string())
+    }
+
+    #[test]
+    fn test_rust_symbols() {
+        let source = r#"use std::fmt;
+
+/// Handles auth
+#[derive(Debug)]
+pub struct AuthService {
+    secret: String,
+}
+
+impl<T> Other<T> {
+    fn login(&self) {}
+}
+
+impl AuthService {
+    /// Log a user in
+    pub fn login(&self, user: &str) -> bool {
+        !user.is_empty()
+    }
+}
+
+mod inner {
+    pub fn helper() {}
+}
+"#;
+
+        let service = extract(Language::Rust, source, "AuthService").unwrap();
+        assert!(service.starts_with("/// Handles auth\n#[derive(Debug)]\npub struct AuthService"));
+        assert!(service.ends_with('}'));
+
+        let login = extract(Language::Rust, source, "AuthService::login").unwrap();
+        assert_eq!(
+            login,
+            "    /// Log a user in\n    pub fn login(&self, user: &str) -> bool {\n        !user.is_empty()\n    }"
+        );
+
+        let other = extract(Language::Rust, source, "Other::login").unwrap();
+        assert_eq!(other, "    fn login(&self) {}");
+
+        assert!(extract(Language::Rust, source, "inner::helper").is_some());
+        assert!(extract(Language::Rust, source, "helper").is_none());
+        assert!(extract(Language::Rust, source, "AuthService::logout").is_none());
+    }
+
+    #[test]
+    fn test_typescript_symbols() {
+        let source = r#"import { x } from "./x";
+
+/** Auth entry point */
+export class AuthService {
+  private token = "";
+
+  login(user: string): boolean {
+    return user.length > 0;
+  }
+}
+
+export const handler = (req: Request) => {
+  return req;
+};
+"#;
+
+        let class = extract(Language::TypeScript, source, "AuthService").unwrap();
+        assert!(class.starts_with("/** Auth entry point */\nexport class AuthService"));
+
+        let login = extract(Language::TypeScript, source, "AuthService::login").unwrap();
+        assert!(login.starts_with("  login(user: string): boolean {"));
+        assert!(login.ends_with("  }"));
+
+        let handler = extract(Language::TypeScript, source, "handler").unwrap();
+        assert!(handler.starts_with("export const handler"));
+        assert!(handler.ends_with("};"));
+    }
+
+    #[test]
+    fn test_python_symbols() {
+        let source = r#"import os
+
+class AuthService:
+    """Handles auth."""
+
+    @staticmethod
+    def login(user):
+        return bool(user)
+
+def main():
+    pass
+"#;
+
+        let login = extract(Language::Python, source, "AuthService::login").unwrap();
+        assert_eq!(
+            login,
+            "    @staticmethod\n    def login(user):\n        return bool(user)"
+        );
+
+        let main = extract(Language::Python, source, "main").unwrap();
+        assert_eq!(main, "def main():\n    pass");
+    }
+
+    #[test]
+    fn test_rust_skeleton() {
+        let source = r#"use std::fmt;
+
+/// Handles auth
+pub struct AuthService {
+    secret: String,
+}
+
+impl AuthService {
+    /// Log a user in
+    pub fn login(
+        &self,
+        user: &str,
+    ) -> bool {
+        !user.is_empty()
+    }
+}
+"#;
+
+        let outline = skeleton(Language::Rust, source, RenderMode::Outline).unwrap();
+        assert_eq!(
+            outline,
+            r#"use std::fmt;
+
+/// Handles auth
+pub struct AuthService {
+    secret: String,
+}
+
+impl AuthService {
+    /// Log a user in
+    pub fn login(
+        &self,
+        user: &str,
+    ) -> bool { ... }
+}
+"#
+        );
+
+        let signatures = skeleton(Language::Rust, source, RenderMode::Signatures).unwrap();
+        assert_eq!(
+            signatures,
+            "pub struct AuthService\nimpl AuthService\n    pub fn login( &self, user: &str, ) -> bool"
+        );
+
+        let full = skeleton(Language::Rust, source, RenderMode::Full).unwrap();
+        assert_eq!(full, source);
+    }
+
+    #[test]
+    fn test_typescript_skeleton() {
+        let source = r#"/** Auth entry point */
+export class AuthService {
+  login(user: string): boolean {
+    return user.length > 0;
+  }
+}
+
+export const handler = (req: Request) => {
+  return req;
+};
+
+const double = (x: number) => x * 2;
+"#;
+
+        let outline = skeleton(Language::TypeScript, source, RenderMode::Outline).unwrap();
+        assert!(outline.contains("  login(user: string): boolean { ... }"));
+        assert!(outline.contains("export const handler = (req: Request) => { ... };"));
+        assert!(outline.contains("const double = (x: number) => x * 2;"));
+        assert!(!outline.contains("return"));
+
+        let signatures = skeleton(Language::TypeScript, source, RenderMode::Signatures).unwrap();
+        assert_eq!(
+            signatures,
+            "export class AuthService\n    login(user: string): boolean\nexport const handler = (req: Request) =>\nconst double = (x: number) => x * 2;"
+        );
+    }
+
+    #[test]
+    fn test_python_skeleton() {
+        let source = r#"class AuthService:
+    """Handles auth."""
+
+    def login(self, user):
+        """Log a user in."""
+        check(user)
+        return True
+
+    def logout(self):
+        return None
+"#;
+
+        let outline = skeleton(Language::Python, source, RenderMode::Outline).unwrap();
+        assert_eq!(
+            outline,
+            r#"class AuthService:
+    """Handles auth."""
+
+    def login(self, user):
+        """Log a user in."""
+        ...
+
+    def logout(self):
+        ...
+"#
+        );
+
+        let signatures = skeleton(Language::Python, source, RenderMode::Signatures).unwrap();
+        assert_eq!(
+            signatures,
+            "class AuthService:\n    def login(self, user):\n    def logout(self):"
+        );
+    }
+}
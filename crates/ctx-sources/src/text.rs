@@ -23,6 +23,7 @@ impl SourceHandler for TextHandler {
             size_bytes: content.len(),
             mime_type: Some("text/plain".to_string()),
             extra: serde_json::json!({}),
+            ..Default::default()
         };
 
         Ok(Artifact::new(
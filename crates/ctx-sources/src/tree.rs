@@ -0,0 +1,440 @@
+use async_trait::async_trait;
+use ctx_core::{Artifact, ArtifactMetadata, ArtifactType, Error, Result, TokenEstimator};
+use ignore::WalkBuilder;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use crate::collection::{compile_patterns, is_excluded, matches_any, path_components};
+use crate::handler::{SourceHandler, SourceOptions};
+
+const DEFAULT_MAX_DEPTH: usize = 3;
+/// Lines rendered before the tree is cut short
+const MAX_ENTRIES: usize = 1000;
+/// Larger files get a size-based token estimate instead of being tokenized
+const MAX_TOKENIZE_BYTES: u64 = 1024 * 1024;
+
+/// Handles `tree:[path] --depth=N --sizes --tokens --include=glob --exclude=glob`
+pub struct TreeHandler;
+
+/// Options for rendering a directory tree
+#[derive(Debug, Clone)]
+pub struct TreeOptions {
+    pub max_depth: usize,
+    pub sizes: bool,
+    pub tokens: bool,
+    pub include: Vec<String>,
+    pub exclude: Vec<String>,
+}
+
+#[async_trait]
+impl SourceHandler for TreeHandler {
+    async fn parse(&self, uri: &str, options: SourceOptions) -> Result<Artifact> {
+        let spec = uri
+            .strip_prefix("tree:")
+            .ok_or_else(|| Error::InvalidSourceUri(format!("Invalid tree URI: {}", uri)))?;
+
+        let mut path_parts = Vec::new();
+        let mut max_depth = DEFAULT_MAX_DEPTH;
+        let mut sizes = false;
+        let mut tokens = false;
+        let mut include = Vec::new();
+        let mut exclude = options.exclude;
+
+        for part in spec.split_whitespace() {
+            if let Some(val) = part.strip_prefix("--depth=") {
+                max_depth = val.parse().ok().filter(|d| *d > 0).ok_or_else(|| {
+                    Error::InvalidSourceUri(format!("Invalid depth '{}': expected 1 or more", val))
+                })?;
+            } else if part == "--sizes" {
+                sizes = true;
+            } else if part == "--tokens" {
+                tokens = true;
+            } else if let Some(val) = part.strip_prefix("--include=") {
+                include.push(val.to_string());
+            } else if let Some(val) = part.strip_prefix("--exclude=") {
+                exclude.push(val.to_string());
+            } else if part.starts_with("--") {
+                return Err(Error::InvalidSourceUri(format!(
+                    "Unknown tree option '{}'. Expected --depth, --sizes, --tokens, --include or --exclude",
+                    part
+                )));
+            } else {
+                path_parts.push(part);
+            }
+        }
+        // Paths may contain spaces, like `dir:` ones
+        let path = match path_parts.join(" ") {
+            path if path.is_empty() => ".".to_string(),
+            path => path,
+        };
+
+        compile_patterns(&include)?;
+        compile_patterns(&exclude)?;
+
+        let abs_path = std::fs::canonicalize(&path).map_err(|e| {
+            Error::Other(anyhow::anyhow!(
+                "Failed to resolve absolute path for {}: {}",
+                path,
+                e
+            ))
+        })?;
+        if !abs_path.is_dir() {
+            return Err(Error::InvalidSourceUri(format!(
+                "Not a directory: {}",
+                abs_path.display()
+            )));
+        }
+
+        let artifact_type = ArtifactType::Tree {
+            path: abs_path.to_string_lossy().to_string(),
+            max_depth,
+            sizes,
+            tokens,
+            include,
+            exclude,
+        };
+
+        let metadata = ArtifactMetadata {
+            size_bytes: 0,
+            mime_type: Some("text/plain".to_string()),
+            extra: serde_json::json!({}),
+            ..Default::default()
+        };
+
+        Ok(Artifact::new(artifact_type, uri.to_string()).with_metadata(metadata))
+    }
+
+    async fn load(&self, artifact: &Artifact) -> Result<String> {
+        match &artifact.artifact_type {
+            ArtifactType::Tree {
+                path,
+                max_depth,
+                sizes,
+                tokens,
+                include,
+                exclude,
+            } => {
+                let root = PathBuf::from(path);
+                let options = TreeOptions {
+                    max_depth: *max_depth,
+                    sizes: *sizes,
+                    tokens: *tokens,
+                    include: include.clone(),
+                    exclude: exclude.clone(),
+                };
+                tokio::task::spawn_blocking(move || render_tree(&root, &options))
+                    .await
+                    .map_err(|e| Error::Other(e.into()))?
+            }
+            _ => Err(Error::Other(anyhow::anyhow!(
+                "Unsupported artifact type for TreeHandler"
+            ))),
+        }
+    }
+
+    fn can_handle(&self, uri: &str) -> bool {
+        uri.starts_with("tree:")
+    }
+}
+
+#[derive(Default)]
+struct DirNode {
+    dirs: BTreeMap<String, DirNode>,
+    files: BTreeMap<String, FileNode>,
+}
+
+struct FileNode {
+    path: PathBuf,
+    size: u64,
+}
+
+impl DirNode {
+    fn insert(&mut self, components: &[String], file: FileNode) {
+        match components {
+            [name] => {
+                self.files.insert(name.clone(), file);
+            }
+            [dir, rest @ ..] => self.dirs.entry(dir.clone()).or_default().insert(rest, file),
+            [] => {}
+        }
+    }
+
+    /// Number of files and total size below this directory
+    fn totals(&self) -> (usize, u64) {
+        let size = self.files.values().map(|f| f.size).sum();
+        self.dirs
+            .values()
+            .fold((self.files.len(), size), |(n, size), dir| {
+                let (dn, dsize) = dir.totals();
+                (n + dn, size + dsize)
+            })
+    }
+}
+
+/// Render a gitignore-aware tree of `root`. Output depends only on the files present, so it's stable
+/// between renders when nothing changed.
+pub fn render_tree(root: &Path, options: &TreeOptions) -> Result<String> {
+    let include = compile_patterns(&options.include)?;
+    let exclude = compile_patterns(&options.exclude)?;
+
+    let walker = WalkBuilder::new(root)
+        .hidden(true)
+        .git_ignore(true)
+        .require_git(false)
+        .build();
+
+    let mut tree = DirNode::default();
+    for entry in walker.filter_map(std::result::Result::ok) {
+        if !entry.file_type().is_some_and(|t| t.is_file()) {
+            continue;
+        }
+        let Ok(rel_path) = entry.path().strip_prefix(root) else {
+            continue;
+        };
+        let components = path_components(rel_path);
+        if is_excluded(&components, &exclude)
+            || !(include.is_empty() || matches_any(&components, &include))
+        {
+            continue;
+        }
+
+        let size = entry.metadata().map(|m| m.len()).unwrap_or(0);
+        tree.insert(
+            &components,
+            FileNode {
+                path: entry.path().to_path_buf(),
+                size,
+            },
+        );
+    }
+
+    let root_name = root
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_else(|| root.display().to_string());
+
+    let mut renderer = TreeRenderer {
+        options,
+        estimator: options.tokens.then(TokenEstimator::new),
+        lines: vec![format!("{}/", root_name)],
+        omitted: 0,
+    };
+    renderer.render_dir(&tree, 1);
+
+    let mut output = renderer.lines.join("\n");
+    if renderer.omitted > 0 {
+        output.push_str(&format!(
+            "\n[{} more entries omitted; narrow with --depth or --include]",
+            renderer.omitted
+        ));
+    }
+    Ok(output)
+}
+
+struct TreeRenderer<'a> {
+    options: &'a TreeOptions,
+    estimator: Option<TokenEstimator>,
+    lines: Vec<String>,
+    omitted: usize,
+}
+
+impl TreeRenderer<'_> {
+    fn push(&mut self, line: String) {
+        if self.lines.len() > MAX_ENTRIES {
+            self.omitted += 1;
+        } else {
+            self.lines.push(line);
+        }
+    }
+
+    fn render_dir(&mut self, dir: &DirNode, depth: usize) {
+        let indent = "  ".repeat(depth);
+
+        for (name, child) in &dir.dirs {
+            if depth < self.options.max_depth {
+                self.push(format!("{}{}/", indent, name));
+                self.render_dir(child, depth + 1);
+            } else {
+                // Past the depth limit, summarize instead of listing
+                let (files, size) = child.totals();
+                let noun = if files == 1 { "file" } else { "files" };
+                let summary = if self.options.sizes {
+                    format!("{} {}, {}", files, noun, format_size(size))
+                } else {
+                    format!("{} {}", files, noun)
+                };
+                self.push(format!("{}{}/ ({})", indent, name, summary));
+            }
+        }
+
+        for (name, file) in &dir.files {
+            let mut notes = Vec::new();
+            if self.options.sizes {
+                notes.push(format_size(file.size));
+            }
+            if let Some(tokens) = self.estimate_tokens(file) {
+                notes.push(format!("~{} tokens", tokens));
+            }
+            if notes.is_empty() {
+                self.push(format!("{}{}", indent, name));
+            } else {
+                self.push(format!("{}{} ({})", indent, name, notes.join(", ")));
+            }
+        }
+    }
+
+    /// Token estimate for text files; None for binary files or when not requested
+    fn estimate_tokens(&self, file: &FileNode) -> Option<usize> {
+        let estimator = self.estimator.as_ref()?;
+        if self.lines.len() > MAX_ENTRIES {
+            return None;
+        }
+        if file.size > MAX_TOKENIZE_BYTES {
+            return Some(file.size as usize / 4);
+        }
+        let bytes = std::fs::read(&file.path).ok()?;
+        let text = std::str::from_utf8(&bytes).ok()?;
+        Some(estimator.estimate(text))
+    }
+}
+
+pub(crate) fn format_size(bytes: u64) -> String {
+    const KB: f64 = 1024.0;
+    let b = bytes as f64;
+    if b < KB {
+        format!("{} B", bytes)
+    } else if b < KB * KB {
+        format!("{:.1} KB", b / KB)
+    } else {
+        format!("{:.1} MB", b / (KB * KB))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fixture(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("ctx-tree-{}-{}", name, uuid::Uuid::new_v4()));
+        for (path, content) in [
+            ("Cargo.toml", "[package]\n"),
+            ("src/main.rs", "fn main() {}\n"),
+            ("src/lib.rs", "pub mod a;\n"),
+            ("src/nested/deep/a.rs", "pub fn a() {}\n"),
+            ("docs/guide.md", "# Guide\n"),
+            ("target/debug/ctx", "binary"),
+            (".gitignore", "target/\n"),
+        ] {
+            let path = dir.join(path);
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(path, content).unwrap();
+        }
+        dir
+    }
+
+    fn options(max_depth: usize) -> TreeOptions {
+        TreeOptions {
+            max_depth,
+            sizes: false,
+            tokens: false,
+            include: vec![],
+            exclude: vec![],
+        }
+    }
+
+    #[test]
+    fn test_render_tree() {
+        let dir = fixture("render");
+        let name = dir.file_name().unwrap().to_string_lossy().to_string();
+
+        let tree = render_tree(&dir, &options(2)).unwrap();
+        assert_eq!(
+            tree,
+            format!(
+                "{}/\n  docs/\n    guide.md\n  src/\n    nested/ (1 file)\n    lib.rs\n    main.rs\n  Cargo.toml",
+                name
+            )
+        );
+
+        // Regenerating gives the same output
+        assert_eq!(render_tree(&dir, &options(2)).unwrap(), tree);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_render_tree_filters_and_annotations() {
+        let dir = fixture("filters");
+
+        let filtered = TreeOptions {
+            sizes: true,
+            include: vec!["*.rs".to_string()],
+            exclude: vec!["src/nested".to_string()],
+            ..options(5)
+        };
+        let tree = render_tree(&dir, &filtered).unwrap();
+        assert!(tree.contains("\n  src/\n    lib.rs (11 B)\n    main.rs (13 B)"));
+        assert!(!tree.contains("nested"));
+        assert!(!tree.contains("docs"));
+        assert!(!tree.contains("Cargo.toml"));
+
+        let with_tokens = TreeOptions {
+            tokens: true,
+            ..options(1)
+        };
+        let tree = render_tree(&dir, &with_tokens).unwrap();
+        assert!(tree.contains("  src/ (3 files)"));
+        assert!(tree.contains("  Cargo.toml (~"));
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_parse_tree_uri() {
+        let dir = fixture("parse");
+        let uri = format!("tree:{} --depth=2 --tokens --include=*.rs", dir.display());
+        let artifact = TreeHandler
+            .parse(&uri, SourceOptions::default())
+            .await
+            .unwrap();
+
+        match &artifact.artifact_type {
+            ArtifactType::Tree {
+                max_depth,
+                tokens,
+                sizes,
+                include,
+                ..
+            } => {
+                assert_eq!(*max_depth, 2);
+                assert!(*tokens);
+                assert!(!*sizes);
+                assert_eq!(include, &vec!["*.rs".to_string()]);
+            }
+            other => panic!("unexpected type: {:?}", other),
+        }
+
+        // A path with a space, as `anchor_uri` writes for a workspace under one
+        let spaced = dir.join("my docs");
+        std::fs::create_dir_all(&spaced).unwrap();
+        let uri = crate::handler::anchor_uri("tree:my docs --depth=2", &dir);
+        let artifact = TreeHandler
+            .parse(&uri, SourceOptions::default())
+            .await
+            .unwrap();
+        assert_eq!(
+            artifact.artifact_type.local_paths(),
+            vec![spaced.canonicalize().unwrap().to_string_lossy().as_ref()]
+        );
+
+        let bad = format!("tree:{} --depth=0", dir.display());
+        assert!(
+            TreeHandler
+                .parse(&bad, SourceOptions::default())
+                .await
+                .is_err()
+        );
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
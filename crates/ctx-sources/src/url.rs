@@ -7,6 +7,30 @@ use crate::handler::{SourceHandler, SourceOptions};
 pub struct UrlHandler;
 
 impl UrlHandler {
+    /// HTTP client shared by URL-based handlers
+    pub(crate) fn client() -> Result<reqwest::Client> {
+        reqwest::Client::builder()
+            .user_agent("ctx/1.0 (context aggregator)")
+            .timeout(std::time::Duration::from_secs(30))
+            .build()
+            .map_err(|e| Error::Other(anyhow::anyhow!("Failed to create HTTP client: {}", e)))
+    }
+
+    /// Convert a response body to text, stripping HTML when needed
+    pub(crate) fn body_to_text(body: String, content_type: &str) -> String {
+        if content_type.contains("text/html") {
+            let title = Self::extract_title(&body);
+            let text = Self::html_to_text(&body);
+            if let Some(title) = title {
+                format!("# {}\n\n{}", title, text)
+            } else {
+                text
+            }
+        } else {
+            body
+        }
+    }
+
     /// Convert HTML to plain text by stripping tags and decoding entities
     fn html_to_text(html: &str) -> String {
         // Remove script and style tags with their content
@@ -37,7 +61,7 @@ impl UrlHandler {
     }
 
     /// Extract title from HTML
-    fn extract_title(html: &str) -> Option<String> {
+    pub(crate) fn extract_title(html: &str) -> Option<String> {
         let title_re = Regex::new(r"(?is)<title[^>]*>([^<]+)</title>").ok()?;
         title_re
             .captures(html)
@@ -73,13 +97,7 @@ impl SourceHandler for UrlHandler {
     async fn load(&self, artifact: &Artifact) -> Result<String> {
         match &artifact.artifact_type {
             ArtifactType::Url { url, .. } => {
-                let client = reqwest::Client::builder()
-                    .user_agent("ctx/1.0 (context aggregator)")
-                    .timeout(std::time::Duration::from_secs(30))
-                    .build()
-                    .map_err(|e| {
-                        Error::Other(anyhow::anyhow!("Failed to create HTTP client: {}", e))
-                    })?;
+                let client = Self::client()?;
 
                 let response = client
                     .get(url)
@@ -108,19 +126,7 @@ impl SourceHandler for UrlHandler {
                     .map_err(|e| Error::Other(anyhow::anyhow!("Failed to read response: {}", e)))?;
 
                 // If HTML, convert to text
-                let text = if content_type.contains("text/html") {
-                    let title = Self::extract_title(&body);
-                    let text = Self::html_to_text(&body);
-                    if let Some(title) = title {
-                        format!("# {}\n\n{}", title, text)
-                    } else {
-                        text
-                    }
-                } else {
-                    body
-                };
-
-                Ok(text)
+                Ok(Self::body_to_text(body, &content_type))
             }
             _ => Err(Error::Other(anyhow::anyhow!(
                 "Unsupported artifact type for UrlHandler"
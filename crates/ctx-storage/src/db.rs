@@ -1,4 +1,4 @@
-use ctx_core::{Artifact, Error, Pack, Result};
+use ctx_core::{Artifact, ArtifactMetadata, Error, Pack, RenderPolicy, Result};
 use sqlx::Row;
 use sqlx::sqlite::{SqliteConnectOptions, SqlitePool, SqlitePoolOptions};
 use std::path::PathBuf;
@@ -84,6 +84,68 @@ impl Storage {
                 })?;
         }
 
+        let applied: Option<i64> =
+            sqlx::query_scalar("SELECT version FROM _migrations WHERE version = 2")
+                .fetch_optional(&self.pool)
+                .await
+                .map_err(|e| Error::Database(format!("Failed to check migration status: {}", e)))?;
+
+        if applied.is_none() {
+            self.migrate_md_dir_excludes().await?;
+        }
+
+        Ok(())
+    }
+
+    /// Migration 2: `md_dir:` rows saved before `dir:` excluded any file whose path
+    /// contained an exclude string. `dir:` excludes are globs, so each string becomes the
+    /// glob matching the same paths. Rows are rewritten and the migration marked applied in
+    /// one transaction, so an interrupted run can't convert some excludes twice.
+    async fn migrate_md_dir_excludes(&self) -> Result<()> {
+        let mut tx = self
+            .pool
+            .begin()
+            .await
+            .map_err(|e| Error::Database(format!("Failed to begin transaction: {}", e)))?;
+
+        let rows = sqlx::query(
+            "SELECT artifact_id, type_json FROM artifacts
+             WHERE json_extract(type_json, '$.type') = 'collection_md_dir'",
+        )
+        .fetch_all(&mut *tx)
+        .await
+        .map_err(|e| Error::Database(format!("Failed to run migration 002: {}", e)))?;
+
+        for row in rows {
+            let id: String = row.get("artifact_id");
+            let type_json: String = row.get("type_json");
+            let mut value: serde_json::Value = serde_json::from_str(&type_json)?;
+            value["type"] = "collection_dir".into();
+            if let Some(exclude) = value["exclude"].as_array_mut() {
+                for pattern in exclude.iter_mut() {
+                    if let Some(substring) = pattern.as_str() {
+                        *pattern = substring_glob(substring).into();
+                    }
+                }
+            }
+
+            sqlx::query("UPDATE artifacts SET type_json = ? WHERE artifact_id = ?")
+                .bind(value.to_string())
+                .bind(&id)
+                .execute(&mut *tx)
+                .await
+                .map_err(|e| Error::Database(format!("Failed to run migration 002: {}", e)))?;
+        }
+
+        sqlx::query("INSERT INTO _migrations (version, applied_at) VALUES (2, ?)")
+            .bind(time::OffsetDateTime::now_utc().unix_timestamp())
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| Error::Database(format!("Failed to mark migration as applied: {}", e)))?;
+
+        tx.commit()
+            .await
+            .map_err(|e| Error::Database(format!("Failed to commit migration 002: {}", e)))?;
         Ok(())
     }
 
@@ -132,6 +194,20 @@ impl Storage {
         Ok(())
     }
 
+    pub async fn update_pack_policies(&self, pack_id: &str, policies: &RenderPolicy) -> Result<()> {
+        let policies_json = serde_json::to_string(policies)?;
+
+        sqlx::query("UPDATE packs SET policies_json = ?, updated_at = ? WHERE pack_id = ?")
+            .bind(&policies_json)
+            .bind(time::OffsetDateTime::now_utc().unix_timestamp())
+            .bind(pack_id)
+            .execute(&self.pool)
+            .await
+            .map_err(|e| Error::Database(format!("Failed to update pack policies: {}", e)))?;
+
+        Ok(())
+    }
+
     pub async fn list_packs(&self) -> Result<Vec<Pack>> {
         let rows = sqlx::query(
             "SELECT pack_id, name, policies_json, created_at, updated_at FROM packs ORDER BY name",
@@ -198,6 +274,62 @@ impl Storage {
             .map_err(|e| Error::Other(anyhow::anyhow!("Invalid UTF-8 in artifact content: {}", e)))
     }
 
+    /// Store new content for an artifact, e.g. a command's latest output, with its metadata.
+    /// Returns the new content hash.
+    pub async fn update_artifact_content(
+        &self,
+        artifact_id: &str,
+        content: &str,
+        metadata: &ArtifactMetadata,
+    ) -> Result<String> {
+        let content_hash = self.blob_store.store(content.as_bytes()).await?;
+        let meta_json = serde_json::to_string(metadata)?;
+
+        sqlx::query("UPDATE artifacts SET content_hash = ?, meta_json = ? WHERE artifact_id = ?")
+            .bind(&content_hash)
+            .bind(&meta_json)
+            .bind(artifact_id)
+            .execute(&self.pool)
+            .await
+            .map_err(|e| Error::Database(format!("Failed to update artifact content: {}", e)))?;
+
+        Ok(content_hash)
+    }
+
+    /// Replace an artifact's metadata, e.g. refreshed file facts
+    pub async fn update_artifact_metadata(
+        &self,
+        artifact_id: &str,
+        metadata: &ArtifactMetadata,
+    ) -> Result<()> {
+        let meta_json = serde_json::to_string(metadata)?;
+
+        sqlx::query("UPDATE artifacts SET meta_json = ? WHERE artifact_id = ?")
+            .bind(&meta_json)
+            .bind(artifact_id)
+            .execute(&self.pool)
+            .await
+            .map_err(|e| Error::Database(format!("Failed to update artifact metadata: {}", e)))?;
+
+        Ok(())
+    }
+
+    /// Rewrite an artifact's type and metadata (e.g. after re-rooting its paths)
+    pub async fn update_artifact(&self, artifact: &Artifact) -> Result<()> {
+        let type_json = serde_json::to_string(&artifact.artifact_type)?;
+        let meta_json = serde_json::to_string(&artifact.metadata)?;
+
+        sqlx::query("UPDATE artifacts SET type_json = ?, meta_json = ? WHERE artifact_id = ?")
+            .bind(&type_json)
+            .bind(&meta_json)
+            .bind(&artifact.id)
+            .execute(&self.pool)
+            .await
+            .map_err(|e| Error::Database(format!("Failed to update artifact: {}", e)))?;
+
+        Ok(())
+    }
+
     pub async fn get_artifact(&self, id: &str) -> Result<Artifact> {
         let row = sqlx::query(
             "SELECT artifact_id, type_json, source_uri, content_hash, meta_json, token_est, created_at
@@ -415,6 +547,19 @@ impl Storage {
     }
 }
 
+/// Glob matching any path containing `substring`, with glob metacharacters escaped
+fn substring_glob(substring: &str) -> String {
+    let mut glob = String::from("*");
+    for c in substring.chars() {
+        match c {
+            '*' | '?' | '[' | ']' => glob.extend(['[', c, ']']),
+            _ => glob.push(c),
+        }
+    }
+    glob.push('*');
+    glob
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -558,6 +703,8 @@ mod tests {
         let artifact = Artifact::new(
             ArtifactType::File {
                 path: "/test/file.txt".to_string(),
+                window: None,
+                encoding: None,
             },
             "file:/test/file.txt".to_string(),
         );
@@ -623,4 +770,49 @@ mod tests {
         let packs = storage2.list_packs().await.unwrap();
         assert_eq!(packs.len(), 1);
     }
+
+    #[tokio::test]
+    async fn test_md_dir_rows_migrated() {
+        let test_dir =
+            std::env::temp_dir().join(format!("ctx-storage-test-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&test_dir).unwrap();
+        let db_path = test_dir.join("test.db");
+
+        // A row as saved before dir: existed, in a database that only has migration 1
+        let storage = Storage::new(Some(db_path.clone())).await.unwrap();
+        sqlx::query("DELETE FROM _migrations WHERE version = 2")
+            .execute(&storage.pool)
+            .await
+            .unwrap();
+        sqlx::query(
+            "INSERT INTO artifacts (artifact_id, type_json, source_uri, content_hash, meta_json, token_est, created_at)
+             VALUES ('legacy', ?, 'md_dir:docs', NULL, '{\"size_bytes\":0}', 0, 0)",
+        )
+        .bind(r#"{"type":"collection_md_dir","path":"docs","max_files":5,"exclude":["drafts","v1.[old]"],"recursive":true}"#)
+        .execute(&storage.pool)
+        .await
+        .unwrap();
+        drop(storage);
+
+        let storage = Storage::new(Some(db_path)).await.unwrap();
+        let artifact = storage.get_artifact("legacy").await.unwrap();
+        let ArtifactType::CollectionDir {
+            path,
+            max_files,
+            exclude,
+            recursive,
+            extensions,
+            include,
+            ..
+        } = artifact.artifact_type
+        else {
+            panic!("expected a directory collection");
+        };
+        assert_eq!(path, "docs");
+        assert_eq!(max_files, Some(5));
+        assert!(recursive);
+        // Still markdown only
+        assert!(extensions.is_empty() && include.is_empty());
+        assert_eq!(exclude, vec!["*drafts*", "*v1.[[]old[]]*"]);
+    }
 }
@@ -1,14 +1,38 @@
 //! Caching utilities for suggestion signals
 
-use std::path::PathBuf;
-use std::time::Instant;
+use std::collections::{BTreeMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
 
 use dashmap::DashMap;
 
+/// Data a signal built for one workspace, reused until it's older than a TTL
+#[derive(Default)]
+pub struct Cached<T> {
+    pub data: T,
+    /// When the cache was last built
+    pub built_at: Option<Instant>,
+    /// Workspace root this cache is for
+    pub workspace: Option<PathBuf>,
+}
+
+impl<T: Default> Cached<T> {
+    /// Whether the cache was built for `workspace` less than `ttl` ago
+    pub fn is_valid(&self, workspace: &Path, ttl: Duration) -> bool {
+        self.workspace.as_deref() == Some(workspace)
+            && self.built_at.is_some_and(|t| t.elapsed() < ttl)
+    }
+
+    /// Drop the data, so the next use rebuilds it
+    pub fn reset(&mut self) {
+        *self = Self::default();
+    }
+}
+
 /// Cache for git co-change data
 pub struct GitCoChangeCache {
-    /// Map from file path -> list of (co-changed file, change count)
-    pub cochanges: DashMap<PathBuf, Vec<(PathBuf, usize)>>,
+    /// Map from file path -> list of (co-changed file, weighted change count), highest first
+    pub cochanges: DashMap<PathBuf, Vec<(PathBuf, f64)>>,
     /// When the cache was last built
     pub built_at: Option<Instant>,
     /// Workspace root this cache is for
@@ -24,12 +48,12 @@ impl GitCoChangeCache {
         }
     }
 
-    pub fn is_valid(&self, workspace: &PathBuf) -> bool {
+    /// Whether the cache was built for `workspace` less than `ttl` ago
+    pub fn is_valid(&self, workspace: &PathBuf, ttl: Duration) -> bool {
         if self.workspace.as_ref() != Some(workspace) {
             return false;
         }
-        // Cache is valid for 5 minutes
-        self.built_at.is_some_and(|t| t.elapsed().as_secs() < 300)
+        self.built_at.is_some_and(|t| t.elapsed() < ttl)
     }
 
     pub fn clear(&self) {
@@ -65,12 +89,12 @@ impl ImportGraphCache {
         }
     }
 
-    pub fn is_valid(&self, workspace: &PathBuf) -> bool {
+    /// Whether the cache was built for `workspace` less than `ttl` ago
+    pub fn is_valid(&self, workspace: &PathBuf, ttl: Duration) -> bool {
         if self.workspace.as_ref() != Some(workspace) {
             return false;
         }
-        // Import cache is valid for 5 minutes
-        self.built_at.is_some_and(|t| t.elapsed().as_secs() < 300)
+        self.built_at.is_some_and(|t| t.elapsed() < ttl)
     }
 
     pub fn clear(&self) {
@@ -84,3 +108,47 @@ impl Default for ImportGraphCache {
         Self::new()
     }
 }
+
+/// Symbol definitions and references
+#[derive(Default)]
+pub struct SymbolIndexCache {
+    /// File -> symbols it defines
+    pub defines: DashMap<PathBuf, Vec<String>>,
+    /// File -> symbols it references without defining them
+    pub references: DashMap<PathBuf, Vec<String>>,
+    /// Symbol -> files that define it
+    pub definers: DashMap<String, Vec<PathBuf>>,
+    /// Symbol -> files that reference it
+    pub referencers: DashMap<String, Vec<PathBuf>>,
+}
+
+/// A test file found in the workspace
+#[derive(Debug, Clone, Default)]
+pub struct TestFile {
+    /// Normalized name of the code it tests, when its file name follows a convention
+    pub subject: Option<String>,
+    /// Normalized words of the modules it imports
+    pub imports: HashSet<String>,
+}
+
+/// Test files and the source files they may cover
+#[derive(Default)]
+pub struct TestPairingCache {
+    /// Test file -> what's known about it
+    pub tests: DashMap<PathBuf, TestFile>,
+    /// Normalized file stem -> non-test files with that stem
+    pub sources: DashMap<String, Vec<PathBuf>>,
+}
+
+/// The identifier words of each file, indexed for BM25 ranking
+#[derive(Default)]
+pub struct LexicalIndexCache {
+    /// Term -> files containing it, with how often it occurs in each
+    pub postings: DashMap<String, Vec<(PathBuf, u32)>>,
+    /// File -> its terms, with how often each occurs
+    pub terms: DashMap<PathBuf, BTreeMap<String, u32>>,
+    /// File -> number of terms in it
+    pub lengths: DashMap<PathBuf, u32>,
+    /// Average number of terms per file
+    pub average_length: f64,
+}
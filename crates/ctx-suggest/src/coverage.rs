@@ -0,0 +1,95 @@
+//! Greedy selection of files that cover the most of a set of files per token
+
+/// Fixed cost of including a file, so tiny files don't win on gain per token alone
+const TOKEN_OVERHEAD: f64 = 200.0;
+
+/// Picks adding less than this to the coverage of the set aren't worth suggesting
+const MIN_GAIN: f64 = 0.05;
+
+/// A file that could be added to the set
+#[derive(Debug, Clone)]
+pub(crate) struct Candidate {
+    pub path: String,
+    /// Relevance (0-1) to each file of the set, by position
+    pub relevance: Vec<f64>,
+    pub tokens: usize,
+}
+
+/// Candidates in the order they'd be added, with what each adds to the coverage of
+/// the set: every file of the set is covered by the chance any pick relates to it, so
+/// a second file related to the same thing adds less than the first.
+pub(crate) fn select(candidates: Vec<Candidate>, max: usize) -> Vec<(Candidate, f64)> {
+    let files = candidates.first().map_or(0, |c| c.relevance.len());
+    let mut covered = vec![0.0_f64; files];
+    let mut remaining = candidates;
+    let mut picked = Vec::new();
+
+    let gain = |candidate: &Candidate, covered: &[f64]| -> f64 {
+        candidate
+            .relevance
+            .iter()
+            .zip(covered)
+            .map(|(r, c)| (1.0 - c) * r)
+            .sum()
+    };
+
+    while picked.len() < max {
+        let best = remaining
+            .iter()
+            .enumerate()
+            .map(|(i, c)| {
+                let gain = gain(c, &covered);
+                (i, gain, gain / (c.tokens as f64 + TOKEN_OVERHEAD))
+            })
+            .filter(|(_, gain, _)| *gain >= MIN_GAIN)
+            .max_by(|a, b| a.2.total_cmp(&b.2));
+        let Some((index, gain, _)) = best else {
+            break;
+        };
+
+        let candidate = remaining.swap_remove(index);
+        for (c, r) in covered.iter_mut().zip(&candidate.relevance) {
+            *c = 1.0 - (1.0 - *c) * (1.0 - r);
+        }
+        picked.push((candidate, gain / files.max(1) as f64));
+    }
+
+    picked
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn candidate(path: &str, relevance: &[f64], tokens: usize) -> Candidate {
+        Candidate {
+            path: path.to_string(),
+            relevance: relevance.to_vec(),
+            tokens,
+        }
+    }
+
+    #[test]
+    fn test_select() {
+        let picked = select(
+            vec![
+                // Related to both files of the set, but huge
+                candidate("schema.sql", &[0.9, 0.9], 20_000),
+                candidate("models.rs", &[0.8, 0.0], 800),
+                // Covers the same file as `models.rs`
+                candidate("types.rs", &[0.8, 0.0], 700),
+                candidate("handlers.rs", &[0.0, 0.7], 900),
+                candidate("misc.rs", &[0.01, 0.01], 10),
+            ],
+            10,
+        );
+        let order: Vec<&str> = picked.iter().map(|(c, _)| c.path.as_str()).collect();
+        assert_eq!(
+            order,
+            vec!["types.rs", "handlers.rs", "models.rs", "schema.sql"]
+        );
+        assert!((picked[0].1 - 0.4).abs() < 1e-9);
+        // Less once the file it relates to is already covered
+        assert!(picked[2].1 < picked[0].1);
+    }
+}
@@ -0,0 +1,424 @@
+//! On-disk suggestion indexes, kept per workspace in the data directory so each run
+//! only catches up on what changed since the last one
+
+use std::collections::{BTreeMap, HashSet};
+use std::future::Future;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use anyhow::{Context, Result};
+use ignore::WalkBuilder;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use tracing::debug;
+
+use crate::parsers;
+use crate::signals::git_cochange::CoChangeIndex;
+use crate::signals::imports::ImportIndex;
+use crate::signals::lexical::TermIndex;
+use crate::signals::symbols::SymbolIndex;
+
+/// Bumped whenever an index file's layout changes, so old files are rebuilt
+pub(crate) const INDEX_VERSION: u32 = 2;
+
+pub(crate) const IMPORTS_FILE: &str = "imports.json";
+pub(crate) const COCHANGE_FILE: &str = "cochange.json";
+pub(crate) const SYMBOLS_FILE: &str = "symbols.json";
+pub(crate) const TERMS_FILE: &str = "terms.json";
+
+/// Directory indexes are kept in unless configured otherwise
+pub fn default_index_dir() -> PathBuf {
+    match directories::ProjectDirs::from("com", "ctx", "ctx") {
+        Some(dirs) => dirs.data_dir().join("index"),
+        None => std::env::temp_dir().join("ctx-index"),
+    }
+}
+
+/// The index files of one workspace
+#[derive(Debug, Clone)]
+pub struct IndexStore {
+    workspace: PathBuf,
+    dir: PathBuf,
+}
+
+/// What's in a workspace's indexes
+#[derive(Debug, Clone, Serialize)]
+pub struct IndexStatus {
+    pub workspace: PathBuf,
+    pub dir: PathBuf,
+    /// Total size of the index files
+    pub size_bytes: u64,
+    pub imports: Option<ImportsStatus>,
+    pub symbols: Option<SymbolsStatus>,
+    pub terms: Option<TermsStatus>,
+    pub cochange: Option<CoChangeStatus>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ImportsStatus {
+    /// Source files indexed
+    pub files: usize,
+    /// Import statements recorded across them
+    pub imports: usize,
+    /// Unix time of the last update
+    pub updated_at: u64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SymbolsStatus {
+    /// Source files indexed
+    pub files: usize,
+    /// Symbol definitions recorded across them
+    pub definitions: usize,
+    /// Unix time of the last update
+    pub updated_at: u64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct TermsStatus {
+    /// Source files indexed
+    pub files: usize,
+    /// Distinct identifier words across them
+    pub terms: usize,
+    /// Unix time of the last update
+    pub updated_at: u64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CoChangeStatus {
+    /// Commit the index is up to date with
+    pub head: String,
+    /// Commits indexed
+    pub commits: usize,
+    /// Unix time of the last update
+    pub updated_at: u64,
+}
+
+impl IndexStore {
+    /// Store for `workspace` under `root`, or under [`default_index_dir`] when `None`
+    pub fn new(root: Option<PathBuf>, workspace: &Path) -> Self {
+        let root = root.unwrap_or_else(default_index_dir);
+        let key = blake3::hash(workspace.to_string_lossy().as_bytes()).to_hex();
+        Self {
+            workspace: workspace.to_owned(),
+            dir: root.join(&key[..16]),
+        }
+    }
+
+    pub fn workspace(&self) -> &Path {
+        &self.workspace
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Read an index file, if there is a readable one
+    pub(crate) fn load<T: DeserializeOwned>(&self, name: &str) -> Option<T> {
+        let content = std::fs::read(self.dir.join(name)).ok()?;
+        serde_json::from_slice(&content).ok()
+    }
+
+    /// Write an index file, replacing the old one only once the new one is complete
+    pub(crate) fn save<T: Serialize>(&self, name: &str, index: &T) -> Result<()> {
+        std::fs::create_dir_all(&self.dir)
+            .with_context(|| format!("Failed to create {}", self.dir.display()))?;
+        let path = self.dir.join(name);
+        let partial = path.with_extension("json.tmp");
+        std::fs::write(&partial, serde_json::to_vec(index)?)?;
+        std::fs::rename(&partial, &path)?;
+        Ok(())
+    }
+
+    /// Delete the workspace's indexes, returning whether there were any
+    pub fn clear(&self) -> Result<bool> {
+        if !self.dir.exists() {
+            return Ok(false);
+        }
+        std::fs::remove_dir_all(&self.dir)
+            .with_context(|| format!("Failed to remove {}", self.dir.display()))?;
+        Ok(true)
+    }
+
+    pub fn status(&self) -> IndexStatus {
+        let size_bytes = std::fs::read_dir(&self.dir)
+            .into_iter()
+            .flatten()
+            .filter_map(|entry| entry.ok()?.metadata().ok())
+            .map(|metadata| metadata.len())
+            .sum();
+        let imports = self
+            .load::<ImportIndex>(IMPORTS_FILE)
+            .filter(|index| index.version == INDEX_VERSION)
+            .map(|index| ImportsStatus {
+                files: index.files.len(),
+                imports: index.files.values().map(|f| f.data.len()).sum(),
+                updated_at: index.updated_at,
+            });
+        let symbols = self
+            .load::<SymbolIndex>(SYMBOLS_FILE)
+            .filter(|index| index.version == INDEX_VERSION)
+            .map(|index| SymbolsStatus {
+                files: index.files.len(),
+                definitions: index.files.values().map(|f| f.data.defines.len()).sum(),
+                updated_at: index.updated_at,
+            });
+        let terms = self
+            .load::<TermIndex>(TERMS_FILE)
+            .filter(|index| index.version == INDEX_VERSION)
+            .map(|index| TermsStatus {
+                files: index.files.len(),
+                terms: index
+                    .files
+                    .values()
+                    .flat_map(|f| f.data.keys())
+                    .collect::<HashSet<_>>()
+                    .len(),
+                updated_at: index.updated_at,
+            });
+        let cochange = self
+            .load::<CoChangeIndex>(COCHANGE_FILE)
+            .filter(|index| index.version == INDEX_VERSION)
+            .map(|index| CoChangeStatus {
+                head: index.head,
+                commits: index.commits.len(),
+                updated_at: index.updated_at,
+            });
+        IndexStatus {
+            workspace: self.workspace.clone(),
+            dir: self.dir.clone(),
+            size_bytes,
+            imports,
+            symbols,
+            terms,
+            cochange,
+        }
+    }
+}
+
+/// Something computed from each source file of a workspace, as stored on disk
+#[derive(Debug, Serialize, Deserialize)]
+pub(crate) struct FileIndex<T> {
+    pub version: u32,
+    pub workspace: PathBuf,
+    pub updated_at: u64,
+    /// Keyed by path relative to the workspace
+    pub files: BTreeMap<PathBuf, IndexedFile<T>>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(crate) struct IndexedFile<T> {
+    /// Modification time in nanoseconds since the Unix epoch
+    pub modified: u128,
+    pub size: u64,
+    /// BLAKE3 hash of the content, for files touched without being changed
+    pub hash: String,
+    pub data: T,
+}
+
+impl<T: Serialize + DeserializeOwned> FileIndex<T> {
+    /// Bring the index stored as `name` up to date with the workspace's source files,
+    /// calling `compute` with the path and content of files that are new or changed
+    pub(crate) async fn update<F, Fut>(
+        store: &IndexStore,
+        name: &str,
+        workspace: &Path,
+        mut compute: F,
+    ) -> Result<Self>
+    where
+        F: FnMut(PathBuf, String) -> Fut,
+        Fut: Future<Output = T>,
+    {
+        let mut stored = store
+            .load::<Self>(name)
+            .filter(|index| index.version == INDEX_VERSION && index.workspace == workspace)
+            .map(|index| index.files)
+            .unwrap_or_default();
+
+        let mut files = BTreeMap::new();
+        let mut changed = false;
+        let mut computed = 0;
+
+        let walker = WalkBuilder::new(workspace)
+            .hidden(true)
+            .git_ignore(true)
+            .build();
+
+        for entry in walker.filter_map(std::result::Result::ok) {
+            let path = entry.path();
+            if !path.is_file() {
+                continue;
+            }
+
+            let ext = path.extension().and_then(|e| e.to_str()).unwrap_or("");
+            if !parsers::is_supported_extension(ext) {
+                continue;
+            }
+
+            let (Ok(relative), Ok(metadata)) = (path.strip_prefix(workspace), path.metadata())
+            else {
+                continue;
+            };
+            let modified = metadata
+                .modified()
+                .ok()
+                .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+                .map_or(0, |d| d.as_nanos());
+            let size = metadata.len();
+
+            let previous = match stored.remove(relative) {
+                Some(previous) if previous.modified == modified && previous.size == size => {
+                    files.insert(relative.to_owned(), previous);
+                    continue;
+                }
+                previous => previous,
+            };
+
+            changed = true;
+            let Ok(content) = tokio::fs::read(path).await else {
+                continue;
+            };
+            let hash = blake3::hash(&content).to_hex().to_string();
+            let data = match previous {
+                Some(previous) if previous.hash == hash => previous.data,
+                _ => {
+                    computed += 1;
+                    let content = String::from_utf8_lossy(&content).into_owned();
+                    compute(path.to_owned(), content).await
+                }
+            };
+            files.insert(
+                relative.to_owned(),
+                IndexedFile {
+                    modified,
+                    size,
+                    hash,
+                    data,
+                },
+            );
+        }
+
+        // Whatever is left was deleted
+        changed |= !stored.is_empty();
+        debug!(
+            "Index {} for {:?}: {} files, {} updated",
+            name,
+            workspace,
+            files.len(),
+            computed
+        );
+
+        let index = Self {
+            version: INDEX_VERSION,
+            workspace: workspace.to_owned(),
+            updated_at: unix_time(SystemTime::now()),
+            files,
+        };
+        if changed {
+            store.save(name, &index)?;
+        }
+        Ok(index)
+    }
+}
+
+/// Seconds since the Unix epoch
+pub(crate) fn unix_time(time: SystemTime) -> u64 {
+    time.duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::BTreeMap;
+    use std::time::Duration;
+
+    #[test]
+    fn test_save_load_clear() {
+        let root = std::env::temp_dir().join(format!("ctx-index-{}", uuid::Uuid::new_v4()));
+        let store = IndexStore::new(Some(root.clone()), Path::new("/work/project"));
+        assert!(store.dir().starts_with(&root));
+        assert!(store.status().imports.is_none());
+
+        let index = ImportIndex {
+            version: INDEX_VERSION,
+            workspace: store.workspace().to_owned(),
+            updated_at: 1,
+            files: BTreeMap::new(),
+        };
+        store.save(IMPORTS_FILE, &index).unwrap();
+        let status = store.status();
+        assert_eq!(status.imports.unwrap().files, 0);
+        assert!(status.size_bytes > 0);
+
+        assert!(store.clear().unwrap());
+        assert!(!store.clear().unwrap());
+        std::fs::remove_dir_all(&root).ok();
+    }
+
+    /// Update an index of content lengths, returning it and the names of the files
+    /// whose length was computed
+    async fn update_lengths(
+        store: &IndexStore,
+        workspace: &Path,
+    ) -> (FileIndex<usize>, Vec<String>) {
+        let mut computed = Vec::new();
+        let index = FileIndex::update(store, "lengths.json", workspace, |path, content| {
+            computed.push(path.file_name().unwrap().to_string_lossy().to_string());
+            async move { content.len() }
+        })
+        .await
+        .unwrap();
+        computed.sort();
+        (index, computed)
+    }
+
+    #[tokio::test]
+    async fn test_update_recomputes_only_changed_files() {
+        let root = std::env::temp_dir().join(format!("ctx-index-{}", uuid::Uuid::new_v4()));
+        let workspace = root.join("workspace");
+        std::fs::create_dir_all(&workspace).unwrap();
+        for name in ["a.rs", "b.rs", "c.rs"] {
+            std::fs::write(workspace.join(name), format!("// {}\n", name)).unwrap();
+        }
+        let store = IndexStore::new(Some(root.join("index")), &workspace);
+
+        let (index, computed) = update_lengths(&store, &workspace).await;
+        assert_eq!(computed, vec!["a.rs", "b.rs", "c.rs"]);
+        assert_eq!(index.files.len(), 3);
+
+        // Same modification time and size: not even read
+        let (_, computed) = update_lengths(&store, &workspace).await;
+        assert!(computed.is_empty());
+
+        // Touched but unchanged: the stored length is reused, under the new time
+        let a = workspace.join("a.rs");
+        let before = index.files[Path::new("a.rs")].modified;
+        std::fs::File::options()
+            .write(true)
+            .open(&a)
+            .unwrap()
+            .set_modified(SystemTime::now() + Duration::from_secs(60))
+            .unwrap();
+        let (index, computed) = update_lengths(&store, &workspace).await;
+        assert!(computed.is_empty());
+        let touched = &index.files[Path::new("a.rs")];
+        assert_eq!(touched.data, 8);
+        assert!(touched.modified > before);
+
+        std::fs::write(workspace.join("b.rs"), "// b.rs, edited\n").unwrap();
+        std::fs::remove_file(workspace.join("c.rs")).unwrap();
+        let (index, computed) = update_lengths(&store, &workspace).await;
+        assert_eq!(computed, vec!["b.rs"]);
+        assert_eq!(index.files[Path::new("b.rs")].data, 16);
+
+        // Deleted files drop out, on disk too
+        let stored: FileIndex<usize> = store.load("lengths.json").unwrap();
+        let files: Vec<&Path> = stored.files.keys().map(PathBuf::as_path).collect();
+        assert_eq!(files, vec![Path::new("a.rs"), Path::new("b.rs")]);
+        assert_eq!(stored.files[Path::new("a.rs")].modified, touched.modified);
+
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+}
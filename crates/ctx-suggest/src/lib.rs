@@ -3,19 +3,31 @@
 //! Provides intelligent file suggestions based on:
 //! - Git co-change history (files frequently modified together)
 //! - Import/dependency graphs (files that import each other)
+//! - Symbol references (files using types and functions another file defines)
+//! - Test pairing (a file's tests, and the code a test exercises)
+//! - Lexical similarity (files whose identifiers share vocabulary)
+//!
+//! Signals are indexed per workspace on disk (see [`index`]) and updated incrementally.
+//! Suggestions can be for one file, or for a whole pack of files at once.
 
 pub mod cache;
+mod coverage;
+pub mod index;
 pub mod parsers;
 pub mod signals;
 
-use std::collections::HashMap;
-use std::path::PathBuf;
-use std::time::Instant;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
 
 use anyhow::Result;
+use ctx_core::TokenEstimator;
 use serde::{Deserialize, Serialize};
 
+use coverage::Candidate;
+use index::IndexStore;
 use signals::Signal;
+use signals::git_cochange::CoChangeOptions;
 
 /// A suggestion for a related file
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -44,12 +56,52 @@ pub struct SuggestionReason {
 pub struct SuggestRequest {
     /// The file to find related files for
     pub file: String,
-    /// Optional pack context (to exclude already-included files)
+    /// Optional pack context; its files are resolved by the caller into `exclude`
     #[serde(default)]
     pub pack_name: Option<String>,
     /// Maximum results to return
     #[serde(default)]
     pub max_results: Option<usize>,
+    /// Files never to suggest, such as those already in the pack
+    #[serde(default)]
+    pub exclude: Vec<String>,
+}
+
+/// Request for suggestions covering a set of files at once, such as a pack's files
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PackSuggestRequest {
+    /// Files to find related files for; they're never suggested themselves
+    pub files: Vec<String>,
+    /// Further files never to suggest
+    #[serde(default)]
+    pub exclude: Vec<String>,
+    /// Maximum results to return
+    #[serde(default)]
+    pub max_results: Option<usize>,
+}
+
+/// A file suggested for a set of files
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PackSuggestion {
+    /// Absolute path to the suggested file
+    pub path: String,
+    /// Share of the set's relatedness this file adds (0.0 - 1.0), given the files
+    /// suggested before it
+    pub coverage_gain: f64,
+    /// Estimated tokens of the file's content
+    pub tokens: usize,
+    /// Files of the set it's related to
+    pub related_to: Vec<String>,
+}
+
+/// Response with suggestions for a set of files, best gain per token first
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PackSuggestResponse {
+    /// The files suggestions are for
+    pub files: Vec<String>,
+    pub suggestions: Vec<PackSuggestion>,
+    /// Time taken in milliseconds
+    pub elapsed_ms: u64,
 }
 
 /// Response with suggestions
@@ -72,10 +124,36 @@ pub struct SuggestConfig {
     pub min_score: f64,
     /// Git history lookback depth (default: 500)
     pub git_history_depth: usize,
+    /// Age at which a commit counts half as much toward co-change as one made today;
+    /// `None` to count all commits the same (default: 180 days)
+    pub git_half_life: Option<Duration>,
+    /// Only count commits by these authors, matched against name and email
+    /// (default: all authors)
+    pub git_authors: Vec<String>,
+    /// Revision range to read history from, such as `main..HEAD` for the current
+    /// branch since its merge-base with `main` (default: all of `HEAD`'s history)
+    pub git_range: Option<String>,
+    /// Commits changing more files than this are left out of co-change, as bulk changes
+    /// like vendoring or reformatting; `None` for no limit (default: 1000)
+    pub git_max_commit_files: Option<usize>,
     /// Weight for git co-change signal (default: 0.5)
     pub git_weight: f64,
     /// Weight for import signal (default: 0.5)
     pub import_weight: f64,
+    /// Weight for symbol reference signal (default: 0.3)
+    pub symbol_weight: f64,
+    /// Weight for test pairing signal (default: 0.5)
+    pub test_weight: f64,
+    /// Weight for lexical similarity signal (default: 0.3)
+    pub lexical_weight: f64,
+    /// Test file name patterns tried for every language on top of the built-in ones,
+    /// such as `{stem}.integration.{ext}` (default: none)
+    pub test_patterns: Vec<String>,
+    /// Directory for on-disk indexes (default: `index/` in the data directory)
+    pub index_dir: Option<PathBuf>,
+    /// How long indexes loaded in memory are used before checking the workspace for
+    /// changes again (default: 30s)
+    pub index_ttl: Duration,
 }
 
 impl Default for SuggestConfig {
@@ -84,12 +162,39 @@ impl Default for SuggestConfig {
             max_results: 10,
             min_score: 0.1,
             git_history_depth: 500,
+            git_half_life: Some(Duration::from_secs(180 * 24 * 60 * 60)),
+            git_authors: Vec::new(),
+            git_range: None,
+            git_max_commit_files: Some(1000),
             git_weight: 0.5,
             import_weight: 0.5,
+            symbol_weight: 0.3,
+            test_weight: 0.5,
+            lexical_weight: 0.3,
+            test_patterns: Vec::new(),
+            index_dir: None,
+            index_ttl: Duration::from_secs(30),
+        }
+    }
+}
+
+impl SuggestConfig {
+    /// Weight of the signal called `signal`
+    fn weight(&self, signal: &str) -> f64 {
+        match signal {
+            "git_cochange" => self.git_weight,
+            "import" => self.import_weight,
+            "symbol" => self.symbol_weight,
+            "test_pairing" => self.test_weight,
+            "lexical" => self.lexical_weight,
+            _ => 0.5,
         }
     }
 }
 
+/// Candidates weighed per suggestion for a pack, out of all files related to its files
+const PACK_CANDIDATES_PER_RESULT: usize = 5;
+
 /// The main suggestion engine
 pub struct SuggestionEngine {
     config: SuggestConfig,
@@ -101,12 +206,38 @@ impl SuggestionEngine {
     /// Create a new suggestion engine for a workspace
     pub fn new(workspace: impl Into<PathBuf>, config: SuggestConfig) -> Self {
         let workspace = workspace.into();
+        let store = IndexStore::new(config.index_dir.clone(), &workspace);
         let signals: Vec<Box<dyn Signal>> = vec![
             Box::new(signals::git_cochange::GitCoChangeSignal::new(
                 workspace.clone(),
-                config.git_history_depth,
+                CoChangeOptions {
+                    depth: config.git_history_depth,
+                    half_life: config.git_half_life,
+                    authors: config.git_authors.clone(),
+                    range: config.git_range.clone(),
+                    max_commit_files: config.git_max_commit_files,
+                },
+                store.clone(),
+                config.index_ttl,
+            )),
+            Box::new(signals::imports::ImportSignal::new(
+                workspace.clone(),
+                store.clone(),
+                config.index_ttl,
+            )),
+            Box::new(signals::symbols::SymbolSignal::new(
+                store.clone(),
+                config.index_ttl,
+            )),
+            Box::new(signals::test_pairing::TestPairingSignal::new(
+                store.clone(),
+                config.index_ttl,
+                config.test_patterns.clone(),
+            )),
+            Box::new(signals::lexical::LexicalSignal::new(
+                store,
+                config.index_ttl,
             )),
-            Box::new(signals::imports::ImportSignal::new(workspace.clone())),
         ];
 
         Self {
@@ -119,13 +250,105 @@ impl SuggestionEngine {
     /// Get suggestions for a file
     pub async fn suggest(&self, request: &SuggestRequest) -> Result<SuggestResponse> {
         let start = Instant::now();
-        let query_path = PathBuf::from(&request.file);
+        let exclude: HashSet<&str> = request.exclude.iter().map(String::as_str).collect();
+
+        let mut suggestions = self.rank(Path::new(&request.file)).await?;
+        suggestions.retain(|s| !exclude.contains(s.path.as_str()));
+
+        // Limit results
+        let max = request.max_results.unwrap_or(self.config.max_results);
+        suggestions.truncate(max);
+
+        Ok(SuggestResponse {
+            file: request.file.clone(),
+            suggestions,
+            elapsed_ms: start.elapsed().as_millis() as u64,
+        })
+    }
+
+    /// Get suggestions for a set of files together, picking files related to as much of
+    /// the set as possible for as few tokens as possible
+    pub async fn suggest_for_pack(
+        &self,
+        request: &PackSuggestRequest,
+    ) -> Result<PackSuggestResponse> {
+        let start = Instant::now();
+        let exclude: HashSet<&str> = request
+            .files
+            .iter()
+            .chain(&request.exclude)
+            .map(String::as_str)
+            .collect();
+
+        // Relevance of every candidate to each file of the set
+        let mut relevance: HashMap<String, Vec<f64>> = HashMap::new();
+        for (i, file) in request.files.iter().enumerate() {
+            for suggestion in self.rank(Path::new(file)).await? {
+                if exclude.contains(suggestion.path.as_str()) {
+                    continue;
+                }
+                relevance
+                    .entry(suggestion.path)
+                    .or_insert_with(|| vec![0.0; request.files.len()])[i] = suggestion.score;
+            }
+        }
+
+        // Only the most related are worth counting tokens for
+        let max = request.max_results.unwrap_or(self.config.max_results);
+        let mut ranked: Vec<(String, Vec<f64>)> = relevance.into_iter().collect();
+        ranked.sort_by(|a, b| {
+            let total = |r: &[f64]| r.iter().sum::<f64>();
+            total(&b.1)
+                .total_cmp(&total(&a.1))
+                .then_with(|| a.0.cmp(&b.0))
+        });
+        ranked.truncate(max * PACK_CANDIDATES_PER_RESULT);
+
+        let estimator = TokenEstimator::new();
+        let mut candidates = Vec::new();
+        for (path, relevance) in ranked {
+            let Ok(content) = tokio::fs::read_to_string(&path).await else {
+                continue;
+            };
+            candidates.push(Candidate {
+                tokens: estimator.estimate(&content),
+                path,
+                relevance,
+            });
+        }
+
+        let suggestions = coverage::select(candidates, max)
+            .into_iter()
+            .map(|(candidate, coverage_gain)| PackSuggestion {
+                related_to: request
+                    .files
+                    .iter()
+                    .zip(&candidate.relevance)
+                    .filter(|(_, r)| **r > 0.0)
+                    .map(|(file, _)| file.clone())
+                    .collect(),
+                path: candidate.path,
+                coverage_gain,
+                tokens: candidate.tokens,
+            })
+            .collect();
+
+        Ok(PackSuggestResponse {
+            files: request.files.clone(),
+            suggestions,
+            elapsed_ms: start.elapsed().as_millis() as u64,
+        })
+    }
+
+    /// Every file related to `query` above the minimum score, best first
+    async fn rank(&self, query: &Path) -> Result<Vec<Suggestion>> {
+        let query_file = query.to_string_lossy();
 
         // Collect scores from all signals
         let mut combined_scores: HashMap<String, Vec<(String, f64)>> = HashMap::new();
 
         for signal in &self.signals {
-            let scores = signal.score(&query_path, &self.workspace).await?;
+            let scores = signal.score(query, &self.workspace).await?;
             for (path, score) in scores {
                 combined_scores
                     .entry(path)
@@ -137,19 +360,17 @@ impl SuggestionEngine {
         // Build suggestions with weighted scores
         let mut suggestions: Vec<Suggestion> = combined_scores
             .into_iter()
-            .filter(|(path, _)| path != &request.file) // Exclude query file
+            .filter(|(path, _)| *path != query_file) // Exclude query file
             .map(|(path, signal_scores)| {
-                let mut total_score = 0.0;
+                // Each signal is separate evidence: a file one signal relates scores that
+                // signal's weighted score, however many other signals are enabled, and every
+                // further signal relating it raises the score towards 1
+                let mut unrelated = 1.0;
                 let mut reasons = Vec::new();
 
                 for (signal_name, score) in signal_scores {
-                    let weight = match signal_name.as_str() {
-                        "git_cochange" => self.config.git_weight,
-                        "import" => self.config.import_weight,
-                        _ => 0.5,
-                    };
-                    let weighted = score * weight;
-                    total_score += weighted;
+                    let weighted = (score * self.config.weight(&signal_name)).clamp(0.0, 1.0);
+                    unrelated *= 1.0 - weighted;
 
                     reasons.push(SuggestionReason {
                         signal: signal_name,
@@ -158,13 +379,9 @@ impl SuggestionEngine {
                     });
                 }
 
-                // Normalize total score to 0-1 range
-                let normalized_score =
-                    (total_score / (self.config.git_weight + self.config.import_weight)).min(1.0);
-
                 Suggestion {
                     path,
-                    score: normalized_score,
+                    score: 1.0 - unrelated,
                     reasons,
                 }
             })
@@ -178,18 +395,15 @@ impl SuggestionEngine {
                 .unwrap_or(std::cmp::Ordering::Equal)
         });
 
-        // Limit results
-        let max = request.max_results.unwrap_or(self.config.max_results);
-        suggestions.truncate(max);
+        Ok(suggestions)
+    }
 
-        Ok(SuggestResponse {
-            file: request.file.clone(),
-            suggestions,
-            elapsed_ms: start.elapsed().as_millis() as u64,
-        })
+    /// Workspace the engine suggests files from
+    pub fn workspace(&self) -> &Path {
+        &self.workspace
     }
 
-    /// Warm up caches for all signals
+    /// Warm up caches for all signals, bringing their on-disk indexes up to date
     pub async fn warm_cache(&self) -> Result<()> {
         for signal in &self.signals {
             signal.warm_cache(&self.workspace).await?;
@@ -197,3 +411,87 @@ impl SuggestionEngine {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use async_trait::async_trait;
+
+    /// Relates the same files to every query
+    struct FixedSignal {
+        name: &'static str,
+        scores: Vec<(&'static str, f64)>,
+    }
+
+    #[async_trait]
+    impl Signal for FixedSignal {
+        fn name(&self) -> &'static str {
+            self.name
+        }
+
+        async fn score(&self, _query: &Path, _workspace: &Path) -> Result<Vec<(String, f64)>> {
+            Ok(self
+                .scores
+                .iter()
+                .map(|(path, score)| (path.to_string(), *score))
+                .collect())
+        }
+
+        async fn warm_cache(&self, _workspace: &Path) -> Result<()> {
+            Ok(())
+        }
+
+        fn clear_cache(&self) {}
+    }
+
+    fn engine(signals: Vec<FixedSignal>) -> SuggestionEngine {
+        let mut boxed: Vec<Box<dyn Signal>> = Vec::new();
+        for signal in signals {
+            boxed.push(Box::new(signal));
+        }
+        SuggestionEngine {
+            // What `ctx pack add -r` asks for
+            config: SuggestConfig {
+                min_score: 0.2,
+                ..Default::default()
+            },
+            signals: boxed,
+            workspace: PathBuf::from("."),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_one_signal_is_enough() {
+        let engine = engine(vec![
+            FixedSignal {
+                name: "git_cochange",
+                scores: vec![("src/both.rs", 1.0)],
+            },
+            FixedSignal {
+                name: "import",
+                scores: vec![("src/imported.rs", 0.8), ("src/both.rs", 0.8)],
+            },
+            FixedSignal {
+                name: "symbol",
+                scores: vec![],
+            },
+            FixedSignal {
+                name: "test_pairing",
+                scores: vec![],
+            },
+            FixedSignal {
+                name: "lexical",
+                scores: vec![("src/lexical.rs", 0.3)],
+            },
+        ]);
+
+        let suggestions = engine.rank(Path::new("src/main.rs")).await.unwrap();
+        let paths: Vec<&str> = suggestions.iter().map(|s| s.path.as_str()).collect();
+        assert_eq!(paths, ["src/both.rs", "src/imported.rs"]);
+
+        // A direct import alone keeps its weighted score however many signals are enabled
+        assert!((suggestions[1].score - 0.4).abs() < 1e-9);
+        // A second signal adds to it without passing 1
+        assert!((suggestions[0].score - 0.7).abs() < 1e-9);
+    }
+}
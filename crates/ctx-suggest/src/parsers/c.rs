@@ -0,0 +1,203 @@
+//! C and C++ `#include` parser
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, LazyLock};
+
+use anyhow::Result;
+use serde::Deserialize;
+use tree_sitter::Node;
+
+use super::{FileCache, node_text, normalize, parse_tree, read_cached, walk_named};
+
+/// Where `compile_commands.json` is looked for, relative to the workspace
+const COMPILE_DATABASES: &[&str] = &["compile_commands.json", "build/compile_commands.json"];
+
+static DATABASES: LazyLock<FileCache<CompileDatabase>> = LazyLock::new(FileCache::new);
+
+/// Parse includes from a C or C++ file
+pub async fn parse_imports(path: &Path) -> Result<Vec<String>> {
+    let content = tokio::fs::read_to_string(path).await?;
+    let cpp = !matches!(path.extension().and_then(|e| e.to_str()), Some("c" | "h"));
+    extract_imports(&content, cpp)
+}
+
+/// Files named by `#include` directives, including conditional ones. Quoted includes
+/// come back bare (`util/log.h`), system includes keep their brackets (`<stdio.h>`).
+pub fn extract_imports(source: &str, cpp: bool) -> Result<Vec<String>> {
+    let language = if cpp {
+        tree_sitter_cpp::LANGUAGE
+    } else {
+        tree_sitter_c::LANGUAGE
+    };
+    let tree = parse_tree(language.into(), source)?;
+    let mut imports = Vec::new();
+    collect(tree.root_node(), source, &mut imports);
+
+    imports.sort();
+    imports.dedup();
+    Ok(imports)
+}
+
+fn collect(node: Node, source: &str, imports: &mut Vec<String>) {
+    walk_named(node, |node| {
+        if node.kind() != "preproc_include" {
+            return true;
+        }
+        if let Some(path) = node.child_by_field_name("path") {
+            let text = node_text(path, source);
+            match path.kind() {
+                "string_literal" => imports.push(text.trim_matches('"').to_string()),
+                "system_lib_string" => imports.push(text.to_string()),
+                _ => {}
+            }
+        }
+        false
+    });
+}
+
+/// Resolve an include the way the compiler searches for it: quoted includes next to the
+/// source file first, then the include paths from `compile_commands.json` (the source
+/// file's own entry when it has one), then the workspace's `include/` directory
+pub fn resolve_import(workspace: &Path, source_file: &Path, import: &str) -> Option<PathBuf> {
+    let (name, quoted) = match import.strip_prefix('<').and_then(|i| i.strip_suffix('>')) {
+        Some(name) => (name, false),
+        None => (import, true),
+    };
+
+    let mut dirs = Vec::new();
+    if quoted {
+        dirs.push(source_file.parent()?.to_owned());
+    }
+    if let Some(database) = compile_database(workspace) {
+        let source = normalize(source_file);
+        dirs.extend(
+            database
+                .by_file
+                .get(&source)
+                .unwrap_or(&database.include_dirs)
+                .iter()
+                .cloned(),
+        );
+    }
+    dirs.push(workspace.join("include"));
+
+    dirs.iter()
+        .map(|dir| normalize(&dir.join(name)))
+        .find(|path| path.is_file())
+}
+
+/// Include directories from a `compile_commands.json`
+#[derive(Debug, Default)]
+struct CompileDatabase {
+    /// Each compiled file's include directories
+    by_file: HashMap<PathBuf, Vec<PathBuf>>,
+    /// Every include directory in the database, for headers and files it doesn't list
+    include_dirs: Vec<PathBuf>,
+}
+
+#[derive(Deserialize)]
+struct CompileCommand {
+    directory: PathBuf,
+    file: PathBuf,
+    #[serde(default)]
+    arguments: Vec<String>,
+    #[serde(default)]
+    command: String,
+}
+
+fn compile_database(workspace: &Path) -> Option<Arc<CompileDatabase>> {
+    let path = COMPILE_DATABASES
+        .iter()
+        .map(|p| workspace.join(p))
+        .find(|p| p.is_file())?;
+    // Relative `directory` entries are relative to the database itself
+    let base = path.parent()?.to_owned();
+    read_cached(&DATABASES, &path, |content| {
+        let commands: Vec<CompileCommand> = serde_json::from_str(content).ok()?;
+        Some(CompileDatabase::new(&base, commands))
+    })
+}
+
+impl CompileDatabase {
+    fn new(base: &Path, commands: Vec<CompileCommand>) -> Self {
+        let mut database = Self::default();
+        for command in commands {
+            let directory = base.join(&command.directory);
+            let arguments = if command.arguments.is_empty() {
+                command
+                    .command
+                    .split_whitespace()
+                    .map(str::to_string)
+                    .collect()
+            } else {
+                command.arguments
+            };
+            let dirs: Vec<PathBuf> = include_flags(&arguments)
+                .into_iter()
+                .map(|dir| normalize(&directory.join(dir)))
+                .collect();
+            for dir in &dirs {
+                if !database.include_dirs.contains(dir) {
+                    database.include_dirs.push(dir.clone());
+                }
+            }
+            database
+                .by_file
+                .insert(normalize(&directory.join(&command.file)), dirs);
+        }
+        database
+    }
+}
+
+/// Directories passed as `-I dir`, `-Idir`, `-isystem dir` or `-iquote dir`
+fn include_flags(arguments: &[String]) -> Vec<&str> {
+    let mut dirs = Vec::new();
+    let mut args = arguments.iter();
+    while let Some(arg) = args.next() {
+        for flag in ["-I", "-isystem", "-iquote"] {
+            if let Some(dir) = arg.strip_prefix(flag) {
+                if !dir.is_empty() {
+                    dirs.push(dir);
+                } else if let Some(dir) = args.next() {
+                    dirs.push(dir.as_str());
+                }
+                break;
+            }
+        }
+    }
+    dirs
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_extract_imports() {
+        let source = r#"
+#include <stdio.h>
+#include "net/socket.h"
+#ifdef USE_TLS
+#  include "net/tls.h"
+#endif
+// #include "commented.h"
+"#;
+        assert_eq!(
+            extract_imports(source, false).unwrap(),
+            vec!["<stdio.h>", "net/socket.h", "net/tls.h"]
+        );
+    }
+
+    #[test]
+    fn test_include_flags() {
+        let args: Vec<String> = "cc -Iinclude -I ../vendor -isystem /opt/sdk -DX=1 -c a.c"
+            .split_whitespace()
+            .map(str::to_string)
+            .collect();
+        assert_eq!(
+            include_flags(&args),
+            vec!["include", "../vendor", "/opt/sdk"]
+        );
+    }
+}
@@ -0,0 +1,231 @@
+//! Crate names a Rust file can use, read from Cargo manifests the way `cargo metadata`
+//! reports them: the package's own library, its path and workspace dependencies (under
+//! their renamed names), and the other members of its workspace
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, LazyLock};
+
+use super::{FileCache, normalize, read_cached};
+
+static MANIFESTS: LazyLock<FileCache<Manifest>> = LazyLock::new(FileCache::new);
+
+/// Dependency tables a package's code can use crates from
+const DEPENDENCY_TABLES: &[&str] = &["dependencies", "dev-dependencies", "build-dependencies"];
+
+/// The parts of a Cargo.toml that map crate names to source files
+#[derive(Debug, Default)]
+struct Manifest {
+    dir: PathBuf,
+    is_package: bool,
+    /// Library target: the name code refers to it by, and its root file
+    lib: Option<(String, PathBuf)>,
+    /// `src/main.rs`, when the package has one
+    main: Option<PathBuf>,
+    dependencies: Vec<Dependency>,
+    /// Whether this manifest has a `[workspace]` table
+    is_workspace: bool,
+    /// Member package directories of a workspace
+    members: Vec<PathBuf>,
+    /// `[workspace.dependencies]` that members inherit with `workspace = true`
+    workspace_dependencies: HashMap<String, Dependency>,
+}
+
+#[derive(Debug, Clone)]
+struct Dependency {
+    /// The dependency's key, which is also its name in code when it's renamed
+    key: String,
+    /// `package = "..."` was given, so code uses the key rather than the library name
+    renamed: bool,
+    path: Option<PathBuf>,
+    /// `workspace = true`: path and rename come from the workspace's entry
+    inherited: bool,
+}
+
+impl Manifest {
+    fn parse(dir: &Path, content: &str) -> Option<Self> {
+        let value: toml::Table = content.parse().ok()?;
+        let package = value.get("package").and_then(|p| p.as_table());
+        let mut manifest = Manifest {
+            dir: dir.to_owned(),
+            is_package: package.is_some(),
+            is_workspace: value.contains_key("workspace"),
+            ..Default::default()
+        };
+
+        if let Some(package) = package {
+            let lib = value.get("lib").and_then(|l| l.as_table());
+            let name = lib
+                .and_then(|l| l.get("name"))
+                .or_else(|| package.get("name"))
+                .and_then(|n| n.as_str())
+                .map(|n| n.replace('-', "_"));
+            let root = match lib.and_then(|l| l.get("path")).and_then(|p| p.as_str()) {
+                Some(path) => Some(normalize(&dir.join(path))),
+                None => Some(dir.join("src/lib.rs")).filter(|p| p.is_file()),
+            };
+            manifest.lib = name.zip(root);
+            manifest.main = Some(dir.join("src/main.rs")).filter(|p| p.is_file());
+        }
+
+        let targets = value
+            .get("target")
+            .and_then(|t| t.as_table())
+            .into_iter()
+            .flat_map(|targets| targets.values().filter_map(|t| t.as_table()));
+        for table in std::iter::once(&value).chain(targets) {
+            for kind in DEPENDENCY_TABLES {
+                if let Some(deps) = table.get(*kind).and_then(|d| d.as_table()) {
+                    manifest.dependencies.extend(
+                        deps.iter()
+                            .filter_map(|(k, v)| Dependency::parse(dir, k, v)),
+                    );
+                }
+            }
+        }
+
+        if let Some(workspace) = value.get("workspace").and_then(|w| w.as_table()) {
+            manifest.members = expand_members(dir, workspace);
+            if let Some(deps) = workspace.get("dependencies").and_then(|d| d.as_table()) {
+                manifest.workspace_dependencies = deps
+                    .iter()
+                    .filter_map(|(k, v)| Some((k.clone(), Dependency::parse(dir, k, v)?)))
+                    .collect();
+            }
+        }
+        Some(manifest)
+    }
+}
+
+impl Dependency {
+    /// A dependency that can point into the workspace; registry-only ones are skipped
+    fn parse(dir: &Path, key: &str, value: &toml::Value) -> Option<Self> {
+        let table = value.as_table()?;
+        let dependency = Dependency {
+            key: key.to_string(),
+            renamed: table.contains_key("package"),
+            path: table
+                .get("path")
+                .and_then(|p| p.as_str())
+                .map(|p| normalize(&dir.join(p))),
+            inherited: table.get("workspace").and_then(|w| w.as_bool()) == Some(true),
+        };
+        (dependency.path.is_some() || dependency.inherited).then_some(dependency)
+    }
+}
+
+/// Member directories listed in `[workspace] members`, with globs expanded and
+/// `exclude`d directories left out
+fn expand_members(dir: &Path, workspace: &toml::Table) -> Vec<PathBuf> {
+    let patterns = |key: &str| -> Vec<PathBuf> {
+        workspace
+            .get(key)
+            .and_then(|m| m.as_array())
+            .into_iter()
+            .flatten()
+            .filter_map(|m| m.as_str())
+            .flat_map(|pattern| {
+                let pattern = dir.join(pattern).to_string_lossy().to_string();
+                glob::glob(&pattern).into_iter().flatten().flatten()
+            })
+            .map(|path| normalize(&path))
+            .collect()
+    };
+    let excluded = patterns("exclude");
+    patterns("members")
+        .into_iter()
+        .filter(|m| m.join("Cargo.toml").is_file() && !excluded.contains(m))
+        .collect()
+}
+
+fn manifest(dir: &Path) -> Option<Arc<Manifest>> {
+    read_cached(&MANIFESTS, &dir.join("Cargo.toml"), |content| {
+        Manifest::parse(dir, content)
+    })
+}
+
+/// The package `file` belongs to
+fn package(file: &Path) -> Option<Arc<Manifest>> {
+    file.ancestors()
+        .skip(1)
+        .filter(|dir| dir.join("Cargo.toml").is_file())
+        .find_map(|dir| manifest(dir).filter(|m| m.is_package))
+}
+
+/// The workspace `package` is a member of, which may be the package's own manifest
+fn workspace(package: &Manifest) -> Option<Arc<Manifest>> {
+    package
+        .dir
+        .ancestors()
+        .filter(|dir| dir.join("Cargo.toml").is_file())
+        .find_map(|dir| manifest(dir).filter(|m| m.is_workspace))
+}
+
+/// Root file of the crate `file` is part of. Binaries, tests, examples and benches are
+/// crates of their own; anything else belongs to the library when there is one.
+pub(crate) fn own_crate_root(file: &Path) -> Option<PathBuf> {
+    let package = package(file)?;
+    for targets in ["src/bin", "tests", "examples", "benches"] {
+        let targets = package.dir.join(targets);
+        if let Ok(rest) = file.strip_prefix(&targets) {
+            let target = targets.join(rest.components().next()?);
+            return Some(if target.is_dir() {
+                target.join("main.rs")
+            } else {
+                target
+            });
+        }
+    }
+    match (&package.lib, &package.main) {
+        (Some(_), Some(main)) if file == main => Some(main.clone()),
+        (Some((_, lib)), _) => Some(lib.clone()),
+        (None, main) => main.clone(),
+    }
+}
+
+/// Root file of the library that code in `file` calls `name`, if it's in the workspace
+pub(crate) fn crate_root(file: &Path, name: &str) -> Option<PathBuf> {
+    let package = package(file)?;
+    if let Some((lib_name, root)) = &package.lib
+        && lib_name == name
+    {
+        return Some(root.clone());
+    }
+
+    let workspace = workspace(&package);
+    for dependency in &package.dependencies {
+        let (path, renamed) = if dependency.inherited {
+            let Some(inherited) = workspace
+                .as_ref()
+                .and_then(|w| w.workspace_dependencies.get(&dependency.key))
+            else {
+                continue;
+            };
+            (
+                inherited.path.clone(),
+                dependency.renamed || inherited.renamed,
+            )
+        } else {
+            (dependency.path.clone(), dependency.renamed)
+        };
+        let Some((lib_name, root)) = path.and_then(|p| manifest(&p)?.lib.clone()) else {
+            continue;
+        };
+        let used_as = if renamed {
+            dependency.key.replace('-', "_")
+        } else {
+            lib_name
+        };
+        if used_as == name {
+            return Some(root);
+        }
+    }
+
+    // Members depended on by version alone (patched, or published and vendored)
+    workspace?
+        .members
+        .iter()
+        .filter_map(|member| manifest(member)?.lib.clone())
+        .find(|(lib_name, _)| lib_name == name)
+        .map(|(_, root)| root)
+}
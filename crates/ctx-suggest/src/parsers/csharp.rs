@@ -0,0 +1,204 @@
+//! C# `using` directive parser
+
+use std::path::{Path, PathBuf};
+
+use anyhow::Result;
+use tree_sitter::Node;
+
+use super::{files_in, node_text, normalize, parse_tree, walk_named};
+
+/// Parse imports from a C# file
+pub async fn parse_imports(path: &Path) -> Result<Vec<String>> {
+    let content = tokio::fs::read_to_string(path).await?;
+    extract_imports(&content)
+}
+
+/// Namespaces and types named by `using` directives (`global`, `static` and aliases
+/// included), wherever they appear
+pub fn extract_imports(source: &str) -> Result<Vec<String>> {
+    let tree = parse_tree(tree_sitter_c_sharp::LANGUAGE.into(), source)?;
+    let mut imports = Vec::new();
+    collect(tree.root_node(), source, &mut imports);
+
+    imports.sort();
+    imports.dedup();
+    Ok(imports)
+}
+
+fn collect(node: Node, source: &str, imports: &mut Vec<String>) {
+    walk_named(node, |node| {
+        if node.kind() != "using_directive" {
+            return true;
+        }
+        // In `using Alias = A.B;` the alias comes first, so the target is the last name
+        let mut cursor = node.walk();
+        let target = node
+            .named_children(&mut cursor)
+            .filter(|n| matches!(n.kind(), "identifier" | "qualified_name"))
+            .last();
+        if let Some(target) = target {
+            imports.push(node_text(target, source).split_whitespace().collect());
+        }
+        false
+    });
+}
+
+/// Resolve a namespace to the files declaring it, by the folder convention: in a project
+/// with root namespace `Shop`, `Shop.Orders.Api` lives in `Orders/Api/`. Only the source
+/// file's project and the projects it references are searched. A type (`using static
+/// Shop.Orders.Totals;`) resolves to its own file.
+pub fn resolve_import(_workspace: &Path, source_file: &Path, import: &str) -> Vec<PathBuf> {
+    for project in visible_projects(source_file) {
+        let rest = if import == project.root_namespace {
+            ""
+        } else if let Some(rest) = import
+            .strip_prefix(project.root_namespace.as_str())
+            .and_then(|r| r.strip_prefix('.'))
+        {
+            rest
+        } else {
+            continue;
+        };
+
+        let dir = project.dir.join(rest.replace('.', "/"));
+        let files: Vec<PathBuf> = files_in(&dir, &["cs"])
+            .into_iter()
+            .filter(|f| f != source_file)
+            .collect();
+        if !files.is_empty() {
+            return files;
+        }
+
+        let (namespace, name) = rest.rsplit_once('.').unwrap_or(("", rest));
+        let file = project
+            .dir
+            .join(namespace.replace('.', "/"))
+            .join(format!("{}.cs", name));
+        if file.is_file() {
+            return vec![file];
+        }
+    }
+    vec![]
+}
+
+/// A `.csproj` and the namespace its folders start from
+struct Project {
+    dir: PathBuf,
+    root_namespace: String,
+}
+
+/// The project containing `file`, then every project it references, transitively
+fn visible_projects(file: &Path) -> Vec<Project> {
+    let Some(first) = file.ancestors().skip(1).find_map(project_file) else {
+        return vec![];
+    };
+
+    let mut seen = vec![first.clone()];
+    let mut pending = vec![first];
+    let mut projects = Vec::new();
+    while let Some(path) = pending.pop() {
+        let Ok(content) = std::fs::read_to_string(&path) else {
+            continue;
+        };
+        let Some(dir) = path.parent() else {
+            continue;
+        };
+        for reference in attribute_values(&content, "ProjectReference", "Include") {
+            let reference = normalize(&dir.join(reference.replace('\\', "/")));
+            if !seen.contains(&reference) {
+                seen.push(reference.clone());
+                pending.push(reference);
+            }
+        }
+        let root_namespace = element_text(&content, "RootNamespace")
+            .or_else(|| path.file_stem().map(|s| s.to_string_lossy().to_string()))
+            .unwrap_or_default();
+        projects.push(Project {
+            dir: dir.to_owned(),
+            root_namespace,
+        });
+    }
+    projects
+}
+
+fn project_file(dir: &Path) -> Option<PathBuf> {
+    let mut projects: Vec<PathBuf> = std::fs::read_dir(dir)
+        .ok()?
+        .filter_map(|e| e.ok().map(|e| e.path()))
+        .filter(|p| p.extension().is_some_and(|e| e == "csproj"))
+        .collect();
+    projects.sort();
+    projects.into_iter().next()
+}
+
+/// Text of the first `<tag>...</tag>` element
+fn element_text(xml: &str, tag: &str) -> Option<String> {
+    let start = xml.find(&format!("<{}>", tag))? + tag.len() + 2;
+    let end = start + xml[start..].find(&format!("</{}>", tag))?;
+    Some(xml[start..end].trim().to_string()).filter(|t| !t.is_empty())
+}
+
+/// Values of `attribute` on every `<tag ...>` element
+fn attribute_values<'a>(xml: &'a str, tag: &str, attribute: &str) -> Vec<&'a str> {
+    let open = format!("<{}", tag);
+    let key = format!("{}=\"", attribute);
+    xml.match_indices(&open)
+        .filter_map(|(i, _)| {
+            let element = &xml[i..i + xml[i..].find('>')?];
+            let value = &element[element.find(&key)? + key.len()..];
+            Some(&value[..value.find('"')?])
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_extract_imports() {
+        let source = r#"
+global using System;
+using static Shop.Orders.Totals;
+using Json = System.Text.Json;
+using Shop.Orders;
+
+namespace Shop.Api
+{
+    using Shop.Payments;
+
+    class Handler {}
+}
+"#;
+        assert_eq!(
+            extract_imports(source).unwrap(),
+            vec![
+                "Shop.Orders",
+                "Shop.Orders.Totals",
+                "Shop.Payments",
+                "System",
+                "System.Text.Json",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_project_xml() {
+        let csproj = r#"<Project Sdk="Microsoft.NET.Sdk">
+  <PropertyGroup>
+    <RootNamespace>Shop.Core</RootNamespace>
+  </PropertyGroup>
+  <ItemGroup>
+    <ProjectReference Include="..\Shared\Shared.csproj" />
+  </ItemGroup>
+</Project>"#;
+        assert_eq!(
+            element_text(csproj, "RootNamespace").as_deref(),
+            Some("Shop.Core")
+        );
+        assert_eq!(
+            attribute_values(csproj, "ProjectReference", "Include"),
+            vec![r"..\Shared\Shared.csproj"]
+        );
+    }
+}
@@ -0,0 +1,149 @@
+//! Go import parser
+
+use std::path::{Path, PathBuf};
+
+use anyhow::Result;
+use tree_sitter::Node;
+
+use super::{files_in, node_text, normalize, parse_tree, walk_named};
+
+/// Parse imports from a Go file
+pub async fn parse_imports(path: &Path) -> Result<Vec<String>> {
+    let content = tokio::fs::read_to_string(path).await?;
+    extract_imports(&content)
+}
+
+/// Package paths named by a Go file's import declarations
+pub fn extract_imports(source: &str) -> Result<Vec<String>> {
+    let tree = parse_tree(tree_sitter_go::LANGUAGE.into(), source)?;
+    let mut imports = Vec::new();
+    collect(tree.root_node(), source, &mut imports);
+
+    imports.sort();
+    imports.dedup();
+    Ok(imports)
+}
+
+fn collect(node: Node, source: &str, imports: &mut Vec<String>) {
+    walk_named(node, |node| {
+        if node.kind() != "import_spec" {
+            return true;
+        }
+        if let Some(path) = node.child_by_field_name("path") {
+            let text = node_text(path, source);
+            imports.push(text.trim_matches(|c| c == '"' || c == '`').to_string());
+        }
+        false
+    });
+}
+
+/// Resolve a Go import to the files of the package it names. Only packages of the
+/// source file's module, or of modules it `replace`s with a local directory, resolve.
+pub fn resolve_import(_workspace: &Path, source_file: &Path, import: &str) -> Vec<PathBuf> {
+    let Some(module) = find_module(source_file) else {
+        return vec![];
+    };
+
+    std::iter::once((module.path.as_str(), module.root.clone()))
+        .chain(
+            module
+                .replace
+                .iter()
+                .map(|(p, dir)| (p.as_str(), dir.clone())),
+        )
+        .find_map(|(prefix, dir)| {
+            let rest = if import == prefix {
+                ""
+            } else {
+                import.strip_prefix(prefix)?.strip_prefix('/')?
+            };
+            Some(dir.join(rest))
+        })
+        .map(|dir| {
+            files_in(&dir, &["go"])
+                .into_iter()
+                .filter(|f| !f.to_string_lossy().ends_with("_test.go"))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// The parts of a `go.mod` that say where packages live
+struct Module {
+    root: PathBuf,
+    path: String,
+    /// `replace` targets that are local directories
+    replace: Vec<(String, PathBuf)>,
+}
+
+/// The module `file` belongs to, from the nearest `go.mod` above it
+fn find_module(file: &Path) -> Option<Module> {
+    let root = file
+        .ancestors()
+        .skip(1)
+        .find(|dir| dir.join("go.mod").is_file())?;
+    let content = std::fs::read_to_string(root.join("go.mod")).ok()?;
+
+    let mut path = None;
+    let mut replace = Vec::new();
+    let mut in_replace_block = false;
+    for line in content.lines() {
+        let line = line.split("//").next().unwrap_or("").trim();
+        if let Some(module) = line.strip_prefix("module ") {
+            path = Some(module.trim().trim_matches('"').to_string());
+        } else if line == "replace (" {
+            in_replace_block = true;
+        } else if in_replace_block && line == ")" {
+            in_replace_block = false;
+        } else {
+            let directive = if in_replace_block {
+                Some(line)
+            } else {
+                line.strip_prefix("replace ")
+            };
+            // `old [version] => ./local/dir`
+            if let Some((old, new)) = directive.and_then(|d| d.split_once("=>")) {
+                let new = new.trim();
+                if new.starts_with("./") || new.starts_with("../") {
+                    let old = old.split_whitespace().next().unwrap_or("");
+                    replace.push((old.to_string(), normalize(&root.join(new))));
+                }
+            }
+        }
+    }
+
+    Some(Module {
+        root: root.to_owned(),
+        path: path?,
+        replace,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_extract_imports() {
+        let source = r#"
+package main
+
+import "fmt"
+
+import (
+    api "example.com/shop/internal/api"
+    _ "example.com/shop/internal/db"
+    `example.com/shop/pkg/log`
+)
+"#;
+        assert_eq!(
+            extract_imports(source).unwrap(),
+            vec![
+                "example.com/shop/internal/api",
+                "example.com/shop/internal/db",
+                "example.com/shop/pkg/log",
+                "fmt",
+            ]
+        );
+    }
+}
@@ -0,0 +1,229 @@
+//! Java and Kotlin import parser
+
+use std::path::{Path, PathBuf};
+
+use anyhow::Result;
+use tree_sitter::Node;
+
+use super::{files_in, node_text, parse_tree};
+
+/// Extensions of JVM sources that import each other by package
+const EXTENSIONS: &[&str] = &["java", "kt"];
+
+/// Parse imports from a Java or Kotlin file
+pub async fn parse_imports(path: &Path) -> Result<Vec<String>> {
+    let content = tokio::fs::read_to_string(path).await?;
+    match path.extension().and_then(|e| e.to_str()) {
+        Some("kt" | "kts") => Ok(extract_kotlin_imports(&content)),
+        _ => extract_imports(&content),
+    }
+}
+
+/// Names imported by a Java file, static imports included; `a.b.*` keeps its wildcard
+pub fn extract_imports(source: &str) -> Result<Vec<String>> {
+    let tree = parse_tree(tree_sitter_java::LANGUAGE.into(), source)?;
+    let mut imports = Vec::new();
+    let root = tree.root_node();
+    let mut cursor = root.walk();
+    for node in root.named_children(&mut cursor) {
+        if node.kind() == "import_declaration" {
+            imports.extend(java_import(node, source));
+        }
+    }
+
+    imports.sort();
+    imports.dedup();
+    Ok(imports)
+}
+
+fn java_import(node: Node, source: &str) -> Option<String> {
+    let mut cursor = node.walk();
+    let children: Vec<Node> = node.named_children(&mut cursor).collect();
+    let name = children
+        .iter()
+        .find(|n| matches!(n.kind(), "scoped_identifier" | "identifier"))?;
+    let name: String = node_text(*name, source).split_whitespace().collect();
+    if children.iter().any(|n| n.kind() == "asterisk") {
+        Some(format!("{}.*", name))
+    } else {
+        Some(name)
+    }
+}
+
+/// Names imported by a Kotlin file. There's no Kotlin grammar to lean on, but imports
+/// can only appear in the file header, one per line, so that's all that is scanned.
+pub fn extract_kotlin_imports(source: &str) -> Vec<String> {
+    let mut imports = Vec::new();
+    let mut in_comment = false;
+    for line in source.lines() {
+        let line = line.trim();
+        if in_comment {
+            in_comment = !line.contains("*/");
+            continue;
+        }
+        if line.starts_with("/*") {
+            in_comment = !line.contains("*/");
+            continue;
+        }
+        if line.is_empty()
+            || line.starts_with("//")
+            || line.starts_with("#!")
+            || line.starts_with("@file:")
+            || line.starts_with("package ")
+        {
+            continue;
+        }
+        let Some(import) = line.strip_prefix("import ") else {
+            break;
+        };
+        // `import a.b.C as D`, `import a.b.*`, with optional backquoted segments
+        let name = import
+            .split(" as ")
+            .next()
+            .unwrap_or("")
+            .split("//")
+            .next()
+            .unwrap_or("")
+            .trim()
+            .trim_end_matches(';')
+            .replace('`', "");
+        if !name.is_empty() {
+            imports.push(name);
+        }
+    }
+
+    imports.sort();
+    imports.dedup();
+    imports
+}
+
+/// Resolve a Java or Kotlin import to the class files it names: `a.b.C` (or a member
+/// of it) to `a/b/C.java` or `a/b/C.kt`, and `a.b.*` to every source in `a/b/`
+pub fn resolve_import(workspace: &Path, source_file: &Path, import: &str) -> Vec<PathBuf> {
+    let parts: Vec<&str> = import.split('.').collect();
+
+    for root in source_roots(workspace, source_file) {
+        if parts.last() == Some(&"*") {
+            let dir: PathBuf = std::iter::once(root.as_path())
+                .chain(parts[..parts.len() - 1].iter().map(Path::new))
+                .collect();
+            let files: Vec<PathBuf> = files_in(&dir, EXTENSIONS)
+                .into_iter()
+                .filter(|f| f != source_file)
+                .collect();
+            if !files.is_empty() {
+                return files;
+            }
+            continue;
+        }
+
+        // Longest prefix first: `a.b.C.method` is defined in `a/b/C.java`
+        for len in (2..=parts.len()).rev() {
+            let mut path: PathBuf = std::iter::once(root.as_path())
+                .chain(parts[..len - 1].iter().map(Path::new))
+                .collect();
+            for ext in EXTENSIONS {
+                path.push(format!("{}.{}", parts[len - 1], ext));
+                if path.is_file() {
+                    return vec![path];
+                }
+                path.pop();
+            }
+        }
+    }
+    vec![]
+}
+
+/// Directories that package paths are relative to, nearest first: the source file's
+/// ancestors, its module's other source sets (`src/test/kotlin` sees `src/main/java`),
+/// and the main source sets of the workspace's top-level modules
+fn source_roots(workspace: &Path, source_file: &Path) -> Vec<PathBuf> {
+    let mut roots: Vec<PathBuf> = Vec::new();
+    let mut add = |dir: PathBuf| {
+        if dir.is_dir() && !roots.contains(&dir) {
+            roots.push(dir);
+        }
+    };
+
+    for dir in source_file.ancestors().skip(1) {
+        add(dir.to_owned());
+        if dir == workspace {
+            break;
+        }
+    }
+
+    if let Some(src) = source_file
+        .ancestors()
+        .find(|dir| dir.file_name().is_some_and(|n| n == "src"))
+        && let Ok(sets) = std::fs::read_dir(src)
+    {
+        for set in sets.filter_map(|e| e.ok()) {
+            add(set.path().join("java"));
+            add(set.path().join("kotlin"));
+        }
+    }
+
+    let modules = std::iter::once(workspace.to_owned()).chain(
+        std::fs::read_dir(workspace)
+            .into_iter()
+            .flatten()
+            .filter_map(|e| e.ok().map(|e| e.path())),
+    );
+    for module in modules {
+        add(module.join("src/main/java"));
+        add(module.join("src/main/kotlin"));
+    }
+    roots
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_extract_imports() {
+        let source = r#"
+package com.shop.api;
+
+import java.util.List;
+import static com.shop.util.Strings.trim;
+import com.shop.model.*;
+
+public class Handler {}
+"#;
+        assert_eq!(
+            extract_imports(source).unwrap(),
+            vec![
+                "com.shop.model.*",
+                "com.shop.util.Strings.trim",
+                "java.util.List",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_extract_kotlin_imports() {
+        let source = r#"
+@file:JvmName("Main")
+/* License
+ * header */
+package com.shop.app
+
+import com.shop.model.Order
+import com.shop.util.`when` as whenever
+import kotlinx.coroutines.*
+
+fun main() {
+    // import com.shop.not.Imported
+}
+"#;
+        assert_eq!(
+            extract_kotlin_imports(source),
+            vec![
+                "com.shop.model.Order",
+                "com.shop.util.when",
+                "kotlinx.coroutines.*",
+            ]
+        );
+    }
+}
@@ -1,29 +1,322 @@
-//! Import parsers for different languages
+//! Import and symbol parsers for different languages, built on tree-sitter syntax trees
 
+pub mod c;
+mod cargo;
+pub mod csharp;
+pub mod go;
+pub mod java;
+mod node;
+pub mod php;
 pub mod python;
+pub mod ruby;
 pub mod rust;
+pub mod symbols;
+pub mod terms;
 pub mod typescript;
 
-use std::path::Path;
+use std::path::{Component, Path, PathBuf};
+use std::sync::Arc;
+use std::time::SystemTime;
 
-use anyhow::Result;
+use anyhow::{Result, anyhow};
+use dashmap::DashMap;
+use tree_sitter::{Node, Parser, Tree};
+
+/// Parsed project files (manifests, compile databases) by path, with the modification
+/// time they were read at
+type FileCache<T> = DashMap<PathBuf, (SystemTime, Arc<T>)>;
+
+/// Languages imports are parsed for
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Language {
+    Rust,
+    TypeScript,
+    Python,
+    Go,
+    Java,
+    Kotlin,
+    C,
+    Cpp,
+    CSharp,
+    Ruby,
+    Php,
+}
+
+impl Language {
+    fn from_extension(ext: &str) -> Option<Self> {
+        match ext {
+            "rs" => Some(Self::Rust),
+            "ts" | "tsx" | "js" | "jsx" | "mts" | "mjs" | "cts" | "cjs" => Some(Self::TypeScript),
+            "py" => Some(Self::Python),
+            "go" => Some(Self::Go),
+            "java" => Some(Self::Java),
+            "kt" | "kts" => Some(Self::Kotlin),
+            "c" | "h" => Some(Self::C),
+            "cc" | "cpp" | "cxx" | "hh" | "hpp" | "hxx" => Some(Self::Cpp),
+            "cs" => Some(Self::CSharp),
+            "rb" => Some(Self::Ruby),
+            "php" => Some(Self::Php),
+            _ => None,
+        }
+    }
+
+    fn from_path(path: &Path) -> Option<Self> {
+        Self::from_extension(path.extension()?.to_str()?)
+    }
+
+    /// Grammar for a file of this language at `path`; Kotlin has none
+    fn grammar(self, path: &Path) -> Option<tree_sitter::Language> {
+        let ext = path.extension().and_then(|e| e.to_str()).unwrap_or("");
+        let grammar = match self {
+            Self::Rust => tree_sitter_rust::LANGUAGE,
+            Self::TypeScript if matches!(ext, "tsx" | "jsx" | "js" | "mjs" | "cjs") => {
+                tree_sitter_typescript::LANGUAGE_TSX
+            }
+            Self::TypeScript => tree_sitter_typescript::LANGUAGE_TYPESCRIPT,
+            Self::Python => tree_sitter_python::LANGUAGE,
+            Self::Go => tree_sitter_go::LANGUAGE,
+            Self::Java => tree_sitter_java::LANGUAGE,
+            Self::Kotlin => return None,
+            Self::C => tree_sitter_c::LANGUAGE,
+            Self::Cpp => tree_sitter_cpp::LANGUAGE,
+            Self::CSharp => tree_sitter_c_sharp::LANGUAGE,
+            Self::Ruby => tree_sitter_ruby::LANGUAGE,
+            Self::Php => tree_sitter_php::LANGUAGE_PHP,
+        };
+        Some(grammar.into())
+    }
+}
 
 /// Parse imports from a file based on its extension
 pub async fn parse_imports(path: &Path) -> Result<Vec<String>> {
-    let ext = path.extension().and_then(|e| e.to_str()).unwrap_or("");
+    match Language::from_path(path) {
+        Some(Language::Rust) => rust::parse_imports(path).await,
+        Some(Language::TypeScript) => typescript::parse_imports(path).await,
+        Some(Language::Python) => python::parse_imports(path).await,
+        Some(Language::Go) => go::parse_imports(path).await,
+        Some(Language::Java | Language::Kotlin) => java::parse_imports(path).await,
+        Some(Language::C | Language::Cpp) => c::parse_imports(path).await,
+        Some(Language::CSharp) => csharp::parse_imports(path).await,
+        Some(Language::Ruby) => ruby::parse_imports(path).await,
+        Some(Language::Php) => php::parse_imports(path).await,
+        None => Ok(vec![]),
+    }
+}
 
-    match ext {
-        "rs" => rust::parse_imports(path).await,
-        "ts" | "tsx" | "js" | "jsx" | "mts" | "mjs" => typescript::parse_imports(path).await,
-        "py" => python::parse_imports(path).await,
-        _ => Ok(vec![]),
+/// Resolve an import found in `source_file` to the files it refers to. Most imports
+/// name one file; Go packages, C# namespaces and wildcard imports name a directory's worth.
+pub fn resolve_import(workspace: &Path, source_file: &Path, import: &str) -> Vec<PathBuf> {
+    let Some(language) = Language::from_path(source_file) else {
+        return vec![];
+    };
+
+    match language {
+        Language::Rust => rust::resolve_import(workspace, source_file, import)
+            .into_iter()
+            .collect(),
+        Language::TypeScript => typescript::resolve_import(workspace, source_file, import)
+            .into_iter()
+            .collect(),
+        Language::Python => python::resolve_import(workspace, source_file, import)
+            .into_iter()
+            .collect(),
+        Language::Go => go::resolve_import(workspace, source_file, import),
+        Language::Java | Language::Kotlin => java::resolve_import(workspace, source_file, import),
+        Language::C | Language::Cpp => c::resolve_import(workspace, source_file, import)
+            .into_iter()
+            .collect(),
+        Language::CSharp => csharp::resolve_import(workspace, source_file, import),
+        Language::Ruby => ruby::resolve_import(workspace, source_file, import)
+            .into_iter()
+            .collect(),
+        Language::Php => php::resolve_import(workspace, source_file, import)
+            .into_iter()
+            .collect(),
     }
 }
 
 /// Check if a file extension is supported for import parsing
 pub fn is_supported_extension(ext: &str) -> bool {
-    matches!(
-        ext,
-        "rs" | "ts" | "tsx" | "js" | "jsx" | "mts" | "mjs" | "py"
-    )
+    Language::from_extension(ext).is_some()
+}
+
+/// Parse `source` with `language`
+fn parse_tree(language: tree_sitter::Language, source: &str) -> Result<Tree> {
+    let mut parser = Parser::new();
+    parser
+        .set_language(&language)
+        .map_err(|e| anyhow!("Failed to load grammar: {}", e))?;
+    parser
+        .parse(source, None)
+        .ok_or_else(|| anyhow!("Failed to parse source"))
+}
+
+fn node_text<'a>(node: Node, source: &'a str) -> &'a str {
+    &source[node.byte_range()]
+}
+
+/// Call `visit` on `root` and the named nodes under it in document order, skipping the
+/// children of nodes it returns false for. Iterative, so deeply nested expressions can't
+/// overflow the stack.
+fn walk_named<'t>(root: Node<'t>, mut visit: impl FnMut(Node<'t>) -> bool) {
+    let mut cursor = root.walk();
+    loop {
+        let node = cursor.node();
+        if node.is_named() && visit(node) && cursor.goto_first_child() {
+            continue;
+        }
+        while !cursor.goto_next_sibling() {
+            if !cursor.goto_parent() {
+                return;
+            }
+        }
+    }
+}
+
+/// `parse` applied to the file at `path`, reused from `cache` while the file is unchanged
+fn read_cached<T>(
+    cache: &FileCache<T>,
+    path: &Path,
+    parse: impl FnOnce(&str) -> Option<T>,
+) -> Option<Arc<T>> {
+    let modified = std::fs::metadata(path).and_then(|m| m.modified()).ok()?;
+    if let Some(cached) = cache.get(path)
+        && cached.0 == modified
+    {
+        return Some(cached.1.clone());
+    }
+
+    let value = Arc::new(parse(&std::fs::read_to_string(path).ok()?)?);
+    cache.insert(path.to_owned(), (modified, value.clone()));
+    Some(value)
+}
+
+/// Files in `dir` (not its subdirectories) with one of `extensions`, sorted
+fn files_in(dir: &Path, extensions: &[&str]) -> Vec<PathBuf> {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return vec![];
+    };
+    let mut files: Vec<PathBuf> = entries
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|path| {
+            path.is_file()
+                && path
+                    .extension()
+                    .and_then(|e| e.to_str())
+                    .is_some_and(|ext| extensions.contains(&ext))
+        })
+        .collect();
+    files.sort();
+    files
+}
+
+/// Drop `.` and fold `..` components so resolved paths match the walked ones
+fn normalize(path: &Path) -> PathBuf {
+    let mut normalized = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir if normalized.file_name().is_some() => {
+                normalized.pop();
+            }
+            other => normalized.push(other),
+        }
+    }
+    normalized
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::BTreeSet;
+
+    /// Every edge found in a fixture project, as `from -> to` relative to its root
+    async fn edges(root: &Path) -> BTreeSet<String> {
+        let mut edges = BTreeSet::new();
+        for entry in walkdir::WalkDir::new(root) {
+            let entry = entry.unwrap();
+            let path = entry.path();
+            let ext = path.extension().and_then(|e| e.to_str()).unwrap_or("");
+            if !path.is_file() || !is_supported_extension(ext) {
+                continue;
+            }
+            for import in parse_imports(path).await.unwrap() {
+                for target in resolve_import(root, path, &import) {
+                    let relative = |p: &Path| p.strip_prefix(root).unwrap().display().to_string();
+                    edges.insert(format!("{} -> {}", relative(path), relative(&target)));
+                }
+            }
+        }
+        edges
+    }
+
+    /// Each fixture project lists the edges it should produce in `edges.txt`
+    async fn check_fixture(name: &str) {
+        let root = Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("tests/fixtures/imports")
+            .join(name);
+        let expected: BTreeSet<String> = std::fs::read_to_string(root.join("edges.txt"))
+            .unwrap()
+            .lines()
+            .map(str::trim)
+            .filter(|l| !l.is_empty() && !l.starts_with('#'))
+            .map(str::to_string)
+            .collect();
+        assert_eq!(edges(&root).await, expected, "fixture '{}'", name);
+    }
+
+    #[tokio::test]
+    async fn test_rust_fixture() {
+        check_fixture("rust").await;
+    }
+
+    #[tokio::test]
+    async fn test_typescript_fixture() {
+        check_fixture("typescript").await;
+    }
+
+    #[tokio::test]
+    async fn test_python_fixture() {
+        check_fixture("python").await;
+    }
+
+    #[tokio::test]
+    async fn test_rust_workspace_fixture() {
+        check_fixture("rust_workspace").await;
+    }
+
+    #[tokio::test]
+    async fn test_typescript_monorepo_fixture() {
+        check_fixture("typescript_monorepo").await;
+    }
+
+    #[tokio::test]
+    async fn test_go_fixture() {
+        check_fixture("go").await;
+    }
+
+    #[tokio::test]
+    async fn test_java_fixture() {
+        check_fixture("java").await;
+    }
+
+    #[tokio::test]
+    async fn test_c_fixture() {
+        check_fixture("c").await;
+    }
+
+    #[tokio::test]
+    async fn test_csharp_fixture() {
+        check_fixture("csharp").await;
+    }
+
+    #[tokio::test]
+    async fn test_ruby_fixture() {
+        check_fixture("ruby").await;
+    }
+
+    #[tokio::test]
+    async fn test_php_fixture() {
+        check_fixture("php").await;
+    }
 }
@@ -0,0 +1,447 @@
+//! Where non-relative TypeScript/JavaScript specifiers point, read from `tsconfig.json`
+//! (following `extends`) and from the `package.json` files of a workspace
+
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, LazyLock};
+
+use serde_json::Value;
+
+use super::{FileCache, normalize, read_cached};
+
+/// Config files holding `compilerOptions`, nearest first
+const CONFIG_FILES: &[&str] = &["tsconfig.json", "jsconfig.json"];
+
+/// `extends` chains followed before giving up
+const MAX_EXTENDS_DEPTH: usize = 8;
+
+static CONFIGS: LazyLock<FileCache<Value>> = LazyLock::new(FileCache::new);
+static PACKAGES: LazyLock<FileCache<Package>> = LazyLock::new(FileCache::new);
+static PNPM_WORKSPACES: LazyLock<FileCache<Vec<PathBuf>>> = LazyLock::new(FileCache::new);
+
+/// The `compilerOptions` that map specifiers to files, after `extends` is applied
+#[derive(Debug, Default)]
+struct PathOptions {
+    base_url: Option<PathBuf>,
+    paths: Option<Paths>,
+}
+
+/// A `paths` option: patterns with their substitutions
+#[derive(Debug)]
+struct Paths {
+    patterns: Vec<(String, Vec<String>)>,
+    /// Directory of the config defining them, which substitutions are relative to when
+    /// there's no `baseUrl`
+    dir: PathBuf,
+}
+
+/// The parts of a `package.json` that say where its modules are
+#[derive(Debug, Default)]
+struct Package {
+    dir: PathBuf,
+    name: Option<String>,
+    exports: Option<Value>,
+    /// `types`, `module` and `main` entry points, in that order
+    entries: Vec<String>,
+    /// Member package directories, when this is a workspace root
+    workspaces: Vec<PathBuf>,
+}
+
+/// Files `specifier` may refer to through the `paths` and `baseUrl` options of the
+/// nearest `tsconfig.json` or `jsconfig.json`, most specific first. Candidates may
+/// still need an extension or `/index` added.
+pub(crate) fn config_candidates(file: &Path, specifier: &str) -> Vec<PathBuf> {
+    let Some(config) = file
+        .ancestors()
+        .skip(1)
+        .flat_map(|dir| CONFIG_FILES.iter().map(move |name| dir.join(name)))
+        .find(|path| path.is_file())
+    else {
+        return vec![];
+    };
+    let options = path_options(&config, 0);
+
+    let mut candidates = Vec::new();
+    if let Some(paths) = &options.paths {
+        let base = options.base_url.as_deref().unwrap_or(&paths.dir);
+        // An exact pattern wins, then the wildcard pattern with the longest prefix
+        let matched = paths
+            .patterns
+            .iter()
+            .filter_map(|(pattern, targets)| {
+                let star = match_pattern(pattern, specifier)?;
+                let specificity = pattern.find('*').unwrap_or(usize::MAX);
+                Some((specificity, star, targets))
+            })
+            .max_by_key(|(specificity, _, _)| *specificity);
+        if let Some((_, star, targets)) = matched {
+            candidates.extend(
+                targets
+                    .iter()
+                    .map(|target| normalize(&base.join(target.replacen('*', star, 1)))),
+            );
+        }
+    }
+    if let Some(base_url) = &options.base_url {
+        candidates.push(normalize(&base_url.join(specifier)));
+    }
+    candidates
+}
+
+/// Files `specifier` may refer to in a package of the source file's workspace: targets
+/// of the package's `exports`, or its entry points and plain subpaths when it has none
+pub(crate) fn package_candidates(file: &Path, specifier: &str) -> Vec<PathBuf> {
+    let (name, subpath) = split_specifier(specifier);
+    let Some(package) = workspace_package(file, name) else {
+        return vec![];
+    };
+
+    let targets = match &package.exports {
+        Some(exports) => export_targets(exports, &subpath),
+        None if subpath == "." => package
+            .entries
+            .iter()
+            .cloned()
+            .chain(std::iter::once("./index".to_string()))
+            .collect(),
+        None => vec![subpath],
+    };
+    targets
+        .iter()
+        .map(|target| normalize(&package.dir.join(target)))
+        .collect()
+}
+
+/// Package name and `./`-prefixed subpath of a bare specifier such as `@app/ui/button`
+fn split_specifier(specifier: &str) -> (&str, String) {
+    let segments = if specifier.starts_with('@') { 2 } else { 1 };
+    let split = specifier
+        .match_indices('/')
+        .nth(segments - 1)
+        .map(|(i, _)| i)
+        .unwrap_or(specifier.len());
+    let (name, rest) = specifier.split_at(split);
+    (name, format!(".{}", rest))
+}
+
+/// What `*` in `pattern` stands for when `specifier` matches it; empty for exact matches
+fn match_pattern<'a>(pattern: &str, specifier: &'a str) -> Option<&'a str> {
+    match pattern.split_once('*') {
+        Some((prefix, suffix)) => specifier.strip_prefix(prefix)?.strip_suffix(suffix),
+        None => (pattern == specifier).then_some(""),
+    }
+}
+
+/// Options from the config at `path`, with the configs it `extends` applied first
+fn path_options(path: &Path, depth: usize) -> PathOptions {
+    let mut options = PathOptions::default();
+    let Some(config) = read_cached(&CONFIGS, path, |content| {
+        serde_json::from_str(&strip_jsonc(content)).ok()
+    }) else {
+        return options;
+    };
+    let Some(dir) = path.parent() else {
+        return options;
+    };
+
+    if depth < MAX_EXTENDS_DEPTH {
+        let extends: Vec<&str> = match &config["extends"] {
+            Value::String(base) => vec![base],
+            Value::Array(bases) => bases.iter().filter_map(|b| b.as_str()).collect(),
+            _ => vec![],
+        };
+        // Later configs override earlier ones
+        for base in extends.into_iter().filter_map(|b| extended_config(path, b)) {
+            let inherited = path_options(&base, depth + 1);
+            options.base_url = inherited.base_url.or(options.base_url);
+            options.paths = inherited.paths.or(options.paths);
+        }
+    }
+
+    let compiler_options = &config["compilerOptions"];
+    if let Some(base_url) = compiler_options["baseUrl"].as_str() {
+        options.base_url = Some(normalize(&dir.join(base_url)));
+    }
+    if let Some(paths) = compiler_options["paths"].as_object() {
+        let patterns = paths
+            .iter()
+            .map(|(pattern, targets)| {
+                let targets = targets
+                    .as_array()
+                    .into_iter()
+                    .flatten()
+                    .filter_map(|t| t.as_str().map(str::to_string))
+                    .collect();
+                (pattern.clone(), targets)
+            })
+            .collect();
+        options.paths = Some(Paths {
+            patterns,
+            dir: dir.to_owned(),
+        });
+    }
+    options
+}
+
+/// The config file an `extends` entry of the config at `path` names: a relative path,
+/// or a file inside a workspace or `node_modules` package
+fn extended_config(path: &Path, base: &str) -> Option<PathBuf> {
+    let with_json = |path: PathBuf| {
+        [
+            path.clone(),
+            PathBuf::from(format!("{}.json", path.display())),
+            path.join("tsconfig.json"),
+        ]
+        .into_iter()
+        .find(|p| p.is_file())
+        .map(|p| normalize(&p))
+    };
+    let dir = path.parent()?;
+    if base.starts_with('.') || Path::new(base).is_absolute() {
+        return with_json(dir.join(base));
+    }
+
+    let (name, subpath) = split_specifier(base);
+    if let Some(package) = workspace_package(path, name) {
+        return with_json(package.dir.join(subpath));
+    }
+    dir.ancestors()
+        .find_map(|dir| with_json(dir.join("node_modules").join(base)))
+}
+
+/// The package called `name` among the members of the workspace `file` is in, or the
+/// package `file` itself belongs to
+fn workspace_package(file: &Path, name: &str) -> Option<Arc<Package>> {
+    for dir in file.ancestors().skip(1) {
+        let own = package(dir);
+        if let Some(own) = &own
+            && own.name.as_deref() == Some(name)
+        {
+            return Some(own.clone());
+        }
+
+        let pnpm = dir.join("pnpm-workspace.yaml");
+        let members = match own {
+            Some(own) if !own.workspaces.is_empty() => own.workspaces.clone(),
+            _ if pnpm.is_file() => read_cached(&PNPM_WORKSPACES, &pnpm, |content| {
+                Some(expand_workspaces(dir, pnpm_packages(content)))
+            })?
+            .to_vec(),
+            _ => continue,
+        };
+        return members
+            .iter()
+            .filter_map(|member| package(member))
+            .find(|member| member.name.as_deref() == Some(name));
+    }
+    None
+}
+
+fn package(dir: &Path) -> Option<Arc<Package>> {
+    let path = dir.join("package.json");
+    if !path.is_file() {
+        return None;
+    }
+    read_cached(&PACKAGES, &path, |content| {
+        let manifest: Value = serde_json::from_str(content).ok()?;
+        let patterns: Vec<&str> = match &manifest["workspaces"] {
+            Value::Array(patterns) => patterns.iter().filter_map(|p| p.as_str()).collect(),
+            // Yarn's `{ "packages": [...] }` form
+            Value::Object(workspaces) => workspaces
+                .get("packages")
+                .and_then(|p| p.as_array())
+                .into_iter()
+                .flatten()
+                .filter_map(|p| p.as_str())
+                .collect(),
+            _ => vec![],
+        };
+        Some(Package {
+            dir: dir.to_owned(),
+            name: manifest["name"].as_str().map(str::to_string),
+            exports: Some(manifest["exports"].clone()).filter(|e| !e.is_null()),
+            entries: ["types", "typings", "module", "main"]
+                .iter()
+                .filter_map(|field| manifest[*field].as_str().map(str::to_string))
+                .collect(),
+            workspaces: expand_workspaces(dir, patterns),
+        })
+    })
+}
+
+/// Package directories matching workspace globs, minus those matching `!` patterns
+fn expand_workspaces(root: &Path, patterns: Vec<&str>) -> Vec<PathBuf> {
+    let expand = |pattern: &str| -> Vec<PathBuf> {
+        let pattern = root.join(pattern).to_string_lossy().to_string();
+        glob::glob(&pattern)
+            .into_iter()
+            .flatten()
+            .flatten()
+            .map(|path| normalize(&path))
+            .collect()
+    };
+    let (excluded, included): (Vec<&str>, Vec<&str>) =
+        patterns.into_iter().partition(|p| p.starts_with('!'));
+    let excluded: Vec<PathBuf> = excluded.iter().flat_map(|p| expand(&p[1..])).collect();
+    included
+        .into_iter()
+        .flat_map(expand)
+        .filter(|dir| dir.join("package.json").is_file() && !excluded.contains(dir))
+        .collect()
+}
+
+/// Globs listed under `packages:` in a `pnpm-workspace.yaml`
+fn pnpm_packages(content: &str) -> Vec<&str> {
+    let mut packages = Vec::new();
+    let mut in_packages = false;
+    for line in content.lines() {
+        let line = line.split(" #").next().unwrap_or("").trim_end();
+        if !line.starts_with([' ', '-']) && !line.is_empty() {
+            in_packages = line.trim() == "packages:";
+        } else if in_packages && let Some(item) = line.trim().strip_prefix('-') {
+            packages.push(item.trim().trim_matches(|c| c == '"' || c == '\''));
+        }
+    }
+    packages
+}
+
+/// Targets `exports` maps `subpath` to, across all conditions in the order listed
+fn export_targets(exports: &Value, subpath: &str) -> Vec<String> {
+    // Sugar for `{ ".": exports }`
+    let is_subpath_map = exports
+        .as_object()
+        .is_some_and(|map| map.keys().any(|key| key.starts_with('.')));
+    if !is_subpath_map {
+        return if subpath == "." {
+            condition_targets(exports, "")
+        } else {
+            vec![]
+        };
+    }
+
+    let map = exports.as_object().into_iter().flatten();
+    let matched = map
+        .filter_map(|(key, value)| {
+            let star = match_pattern(key, subpath)?;
+            Some((key.find('*').unwrap_or(usize::MAX), star, value))
+        })
+        .max_by_key(|(specificity, _, _)| *specificity);
+    matched
+        .map(|(_, star, value)| condition_targets(value, star))
+        .unwrap_or_default()
+}
+
+/// Every path in a (possibly conditional or fallback) export target, `*` substituted
+fn condition_targets(value: &Value, star: &str) -> Vec<String> {
+    match value {
+        Value::String(target) => vec![target.replace('*', star)],
+        Value::Array(targets) => targets
+            .iter()
+            .flat_map(|t| condition_targets(t, star))
+            .collect(),
+        Value::Object(conditions) => conditions
+            .values()
+            .flat_map(|t| condition_targets(t, star))
+            .collect(),
+        _ => vec![],
+    }
+}
+
+/// JSON with the comments and trailing commas `tsconfig.json` allows removed
+fn strip_jsonc(content: &str) -> String {
+    let mut out = String::with_capacity(content.len());
+    let mut chars = content.chars().peekable();
+    let mut in_string = false;
+    while let Some(c) = chars.next() {
+        if in_string {
+            out.push(c);
+            match c {
+                '\\' => out.extend(chars.next()),
+                '"' => in_string = false,
+                _ => {}
+            }
+            continue;
+        }
+        match (c, chars.peek()) {
+            ('"', _) => {
+                in_string = true;
+                out.push(c);
+            }
+            ('/', Some('/')) => while chars.next_if(|c| *c != '\n').is_some() {},
+            ('/', Some('*')) => {
+                chars.next();
+                let mut last = ' ';
+                for c in chars.by_ref() {
+                    if last == '*' && c == '/' {
+                        break;
+                    }
+                    last = c;
+                }
+            }
+            ('}' | ']', _) => {
+                let kept = out.trim_end().len();
+                out.truncate(kept);
+                if out.ends_with(',') {
+                    out.pop();
+                }
+                out.push(c);
+            }
+            _ => out.push(c),
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_strip_jsonc() {
+        let content = r#"{
+  // Paths are relative to baseUrl
+  "compilerOptions": { "baseUrl": "./src", /* inline */ "paths": { "@/*": ["*",], }, },
+  "include": ["src/**/*", "// not a comment"],
+}"#;
+        let value: Value = serde_json::from_str(&strip_jsonc(content)).unwrap();
+        assert_eq!(value["compilerOptions"]["paths"]["@/*"], json!(["*"]));
+        assert_eq!(value["include"][1], "// not a comment");
+    }
+
+    #[test]
+    fn test_export_targets() {
+        let exports = json!({
+            ".": { "types": "./src/index.ts", "default": "./dist/index.js" },
+            "./utils/*": "./src/utils/*.ts",
+            "./utils/internal/*": null,
+        });
+        assert_eq!(
+            export_targets(&exports, "."),
+            vec!["./src/index.ts", "./dist/index.js"]
+        );
+        assert_eq!(
+            export_targets(&exports, "./utils/date"),
+            vec!["./src/utils/date.ts"]
+        );
+        assert!(export_targets(&exports, "./utils/internal/x").is_empty());
+        assert_eq!(export_targets(&json!("./main.js"), "."), vec!["./main.js"]);
+    }
+
+    #[test]
+    fn test_split_specifier() {
+        assert_eq!(
+            split_specifier("@app/ui/button"),
+            ("@app/ui", "./button".into())
+        );
+        assert_eq!(split_specifier("lodash"), ("lodash", ".".into()));
+    }
+
+    #[test]
+    fn test_pnpm_packages() {
+        let content = "packages:\n  - 'packages/*'\n  - \"apps/*\" # apps\n  - '!**/test/**'\ncatalog:\n  - x\n";
+        assert_eq!(
+            pnpm_packages(content),
+            vec!["packages/*", "apps/*", "!**/test/**"]
+        );
+    }
+}
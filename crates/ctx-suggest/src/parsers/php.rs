@@ -0,0 +1,177 @@
+//! PHP `use` and `require` parser
+
+use std::path::{Path, PathBuf};
+
+use anyhow::Result;
+use tree_sitter::Node;
+
+use super::{node_text, normalize, parse_tree, walk_named};
+
+/// Parse imports from a PHP file
+pub async fn parse_imports(path: &Path) -> Result<Vec<String>> {
+    let content = tokio::fs::read_to_string(path).await?;
+    extract_imports(&content)
+}
+
+/// Classes named by `use` declarations (grouped ones expanded) and files loaded by
+/// `require`/`include` with a literal path, optionally prefixed by `__DIR__ .`.
+/// Paths relative to the source file come back starting with `./`.
+pub fn extract_imports(source: &str) -> Result<Vec<String>> {
+    let tree = parse_tree(tree_sitter_php::LANGUAGE_PHP.into(), source)?;
+    let mut imports = Vec::new();
+    collect(tree.root_node(), source, &mut imports);
+
+    imports.sort();
+    imports.dedup();
+    Ok(imports)
+}
+
+fn collect(node: Node, source: &str, imports: &mut Vec<String>) {
+    walk_named(node, |node| visit(node, source, imports));
+}
+
+/// Record a `use` or include; false when `node` is one, as nothing nested in it is
+fn visit(node: Node, source: &str, imports: &mut Vec<String>) -> bool {
+    match node.kind() {
+        "namespace_use_declaration" => {
+            let mut cursor = node.walk();
+            let prefix = node
+                .named_children(&mut cursor)
+                .find(|n| n.kind() == "namespace_name")
+                .map(|n| node_text(n, source));
+            let clauses = node.child_by_field_name("body").unwrap_or(node);
+            let mut cursor = clauses.walk();
+            for clause in clauses.named_children(&mut cursor) {
+                if clause.kind() != "namespace_use_clause" {
+                    continue;
+                }
+                let Some(name) = clause.named_child(0) else {
+                    continue;
+                };
+                let name = node_text(name, source);
+                let name = match prefix {
+                    Some(prefix) => format!("{}\\{}", prefix, name),
+                    None => name.to_string(),
+                };
+                imports.push(name.trim_start_matches('\\').to_string());
+            }
+            false
+        }
+        "require_expression"
+        | "require_once_expression"
+        | "include_expression"
+        | "include_once_expression" => {
+            if let Some(path) = node.named_child(0).and_then(|n| include_path(n, source)) {
+                imports.push(path);
+            }
+            false
+        }
+        _ => true,
+    }
+}
+
+/// `'lib/a.php'` or `__DIR__ . '/lib/a.php'`, as a path relative to the source file
+fn include_path(node: Node, source: &str) -> Option<String> {
+    let path = match node.kind() {
+        "string" | "encapsed_string" => string_value(node, source)?,
+        "binary_expression" => {
+            let left = node.child_by_field_name("left")?;
+            if node_text(left, source) != "__DIR__" {
+                return None;
+            }
+            string_value(node.child_by_field_name("right")?, source)?
+        }
+        _ => return None,
+    };
+    let path = path.trim_start_matches('/');
+    if path.starts_with('.') {
+        Some(path.to_string())
+    } else {
+        Some(format!("./{}", path))
+    }
+}
+
+fn string_value(node: Node, source: &str) -> Option<String> {
+    let mut cursor = node.walk();
+    let parts: Vec<Node> = node.named_children(&mut cursor).collect();
+    match parts.as_slice() {
+        [content] if content.kind() == "string_content" => {
+            Some(node_text(*content, source).to_string())
+        }
+        _ => None,
+    }
+}
+
+/// Resolve a PHP import: file paths relative to the source file, classes through the
+/// PSR-4 `autoload` and `autoload-dev` prefixes of the nearest `composer.json`
+pub fn resolve_import(_workspace: &Path, source_file: &Path, import: &str) -> Option<PathBuf> {
+    if import.starts_with('.') {
+        let path = normalize(&source_file.parent()?.join(import));
+        return path.is_file().then_some(path);
+    }
+
+    let root = source_file
+        .ancestors()
+        .skip(1)
+        .find(|dir| dir.join("composer.json").is_file())?;
+    let composer: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(root.join("composer.json")).ok()?).ok()?;
+
+    let mut prefixes: Vec<(&str, &str)> = Vec::new();
+    for section in ["autoload", "autoload-dev"] {
+        let Some(psr4) = composer[section]["psr-4"].as_object() else {
+            continue;
+        };
+        for (prefix, dirs) in psr4 {
+            match dirs {
+                serde_json::Value::String(dir) => prefixes.push((prefix, dir)),
+                serde_json::Value::Array(dirs) => prefixes.extend(
+                    dirs.iter()
+                        .filter_map(|d| Some((prefix.as_str(), d.as_str()?))),
+                ),
+                _ => {}
+            }
+        }
+    }
+    // Most specific namespace first
+    prefixes.sort_by_key(|(prefix, _)| std::cmp::Reverse(prefix.len()));
+
+    prefixes.into_iter().find_map(|(prefix, dir)| {
+        let rest = import.strip_prefix(prefix)?;
+        let path = root
+            .join(dir)
+            .join(format!("{}.php", rest.replace('\\', "/")));
+        path.is_file().then(|| normalize(&path))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_extract_imports() {
+        let source = r#"<?php
+namespace App\Http;
+
+use App\Models\User;
+use App\Services\{Billing, Mail\Mailer as M};
+use function App\Support\helper;
+
+require_once __DIR__ . '/../bootstrap.php';
+include 'views/header.php';
+require $dynamic;
+"#;
+        assert_eq!(
+            extract_imports(source).unwrap(),
+            vec![
+                "../bootstrap.php",
+                "./views/header.php",
+                "App\\Models\\User",
+                "App\\Services\\Billing",
+                "App\\Services\\Mail\\Mailer",
+                "App\\Support\\helper",
+            ]
+        );
+    }
+}
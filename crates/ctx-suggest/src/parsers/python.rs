@@ -3,57 +3,71 @@
 use std::path::Path;
 
 use anyhow::Result;
-use regex::Regex;
-use std::sync::LazyLock;
+use tree_sitter::Node;
 
-// Matches: import foo, bar, baz
-static IMPORT_REGEX: LazyLock<Regex> =
-    LazyLock::new(|| Regex::new(r"^\s*import\s+([\w.,\s]+)").unwrap());
-
-// Matches: from foo.bar import baz
-static FROM_IMPORT_REGEX: LazyLock<Regex> =
-    LazyLock::new(|| Regex::new(r"^\s*from\s+([\w.]+)\s+import").unwrap());
-
-// Matches: from . import foo (relative import)
-static FROM_RELATIVE_REGEX: LazyLock<Regex> =
-    LazyLock::new(|| Regex::new(r"^\s*from\s+(\.+[\w.]*)\s+import").unwrap());
+use super::{node_text, parse_tree, walk_named};
 
 /// Parse imports from a Python file
 pub async fn parse_imports(path: &Path) -> Result<Vec<String>> {
     let content = tokio::fs::read_to_string(path).await?;
+    extract_imports(&content)
+}
+
+/// Modules a Python file imports, wherever the import statement is (functions,
+/// `try` blocks, ...). `from m import a` yields `m` and `m.a`, since `a` may be a
+/// submodule; relative modules keep their leading dots.
+pub fn extract_imports(source: &str) -> Result<Vec<String>> {
+    let tree = parse_tree(tree_sitter_python::LANGUAGE.into(), source)?;
     let mut imports = Vec::new();
+    collect(tree.root_node(), source, &mut imports);
 
-    for line in content.lines() {
-        // Check for relative imports first
-        if let Some(cap) = FROM_RELATIVE_REGEX.captures(line) {
-            imports.push(cap[1].to_string());
-            continue;
-        }
+    // Deduplicate
+    imports.sort();
+    imports.dedup();
 
-        // Check for from X import
-        if let Some(cap) = FROM_IMPORT_REGEX.captures(line) {
-            imports.push(cap[1].to_string());
-            continue;
-        }
+    Ok(imports)
+}
+
+fn collect(node: Node, source: &str, imports: &mut Vec<String>) {
+    walk_named(node, |node| visit(node, source, imports));
+}
 
-        // Check for import X
-        if let Some(cap) = IMPORT_REGEX.captures(line) {
-            for module in cap[1].split(',') {
-                let module = module.trim();
-                // Handle "import foo as bar" -> just "foo"
-                let module = module.split_whitespace().next().unwrap_or(module);
-                if !module.is_empty() {
-                    imports.push(module.to_string());
+/// Record an import statement; false when `node` is one, as nothing nested in it is
+fn visit(node: Node, source: &str, imports: &mut Vec<String>) -> bool {
+    match node.kind() {
+        "import_statement" => {
+            imports.extend(imported_names(node, source));
+            false
+        }
+        "import_from_statement" => {
+            let Some(module) = node.child_by_field_name("module_name") else {
+                return false;
+            };
+            let module: String = node_text(module, source).split_whitespace().collect();
+            for name in imported_names(node, source) {
+                if module.ends_with('.') {
+                    imports.push(format!("{}{}", module, name));
+                } else {
+                    imports.push(format!("{}.{}", module, name));
                 }
             }
+            imports.push(module);
+            false
         }
+        _ => true,
     }
+}
 
-    // Deduplicate
-    imports.sort();
-    imports.dedup();
-
-    Ok(imports)
+/// Dotted names listed by an import statement, without their `as` aliases
+fn imported_names(node: Node, source: &str) -> Vec<String> {
+    let mut cursor = node.walk();
+    node.children_by_field_name("name", &mut cursor)
+        .filter_map(|name| match name.kind() {
+            "aliased_import" => name.child_by_field_name("name"),
+            _ => Some(name),
+        })
+        .map(|name| node_text(name, source).split_whitespace().collect())
+        .collect()
 }
 
 /// Resolve a Python import to a file path
@@ -148,23 +162,35 @@ mod tests {
     use super::*;
 
     #[test]
-    fn test_import_regex() {
-        let line = "import os, sys, json";
-        let cap = IMPORT_REGEX.captures(line).unwrap();
-        assert_eq!(&cap[1], "os, sys, json");
-    }
-
-    #[test]
-    fn test_from_import_regex() {
-        let line = "from pathlib import Path";
-        let cap = FROM_IMPORT_REGEX.captures(line).unwrap();
-        assert_eq!(&cap[1], "pathlib");
-    }
-
-    #[test]
-    fn test_relative_import() {
-        let line = "from ..utils import helper";
-        let cap = FROM_RELATIVE_REGEX.captures(line).unwrap();
-        assert_eq!(&cap[1], "..utils");
+    fn test_extract_imports() {
+        let source = r#"
+import os, sys as system
+from pathlib import Path
+from ..utils import (
+    helper,
+    other as alias,
+)
+from . import models
+from .core import *
+
+def lazy():
+    import json
+"#;
+        assert_eq!(
+            extract_imports(source).unwrap(),
+            vec![
+                ".",
+                "..utils",
+                "..utils.helper",
+                "..utils.other",
+                ".core",
+                ".models",
+                "json",
+                "os",
+                "pathlib",
+                "pathlib.Path",
+                "sys",
+            ]
+        );
     }
 }
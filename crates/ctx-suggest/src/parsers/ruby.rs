@@ -0,0 +1,114 @@
+//! Ruby `require` parser
+
+use std::path::{Path, PathBuf};
+
+use anyhow::Result;
+use tree_sitter::Node;
+
+use super::{node_text, normalize, parse_tree, walk_named};
+
+/// Parse imports from a Ruby file
+pub async fn parse_imports(path: &Path) -> Result<Vec<String>> {
+    let content = tokio::fs::read_to_string(path).await?;
+    extract_imports(&content)
+}
+
+/// Features loaded by `require`, `require_relative` and `autoload` with literal paths.
+/// `require_relative` paths come back starting with `.` so they can be told apart.
+pub fn extract_imports(source: &str) -> Result<Vec<String>> {
+    let tree = parse_tree(tree_sitter_ruby::LANGUAGE.into(), source)?;
+    let mut imports = Vec::new();
+    collect(tree.root_node(), source, &mut imports);
+
+    imports.sort();
+    imports.dedup();
+    Ok(imports)
+}
+
+fn collect(node: Node, source: &str, imports: &mut Vec<String>) {
+    walk_named(node, |node| {
+        visit(node, source, imports);
+        true
+    });
+}
+
+fn visit(node: Node, source: &str, imports: &mut Vec<String>) {
+    if node.kind() == "call"
+        && node.child_by_field_name("receiver").is_none()
+        && let Some(method) = node.child_by_field_name("method")
+        && let Some(arguments) = node.child_by_field_name("arguments")
+    {
+        let mut cursor = arguments.walk();
+        let path = arguments
+            .named_children(&mut cursor)
+            .find(|n| n.kind() == "string")
+            .and_then(|s| string_value(s, source));
+        match (node_text(method, source), path) {
+            ("require_relative", Some(path)) if path.starts_with('.') => imports.push(path),
+            ("require_relative", Some(path)) => imports.push(format!("./{}", path)),
+            ("require" | "autoload", Some(path)) => imports.push(path),
+            _ => {}
+        }
+    }
+}
+
+/// Contents of a string without interpolation
+fn string_value(node: Node, source: &str) -> Option<String> {
+    let mut cursor = node.walk();
+    let parts: Vec<Node> = node.named_children(&mut cursor).collect();
+    match parts.as_slice() {
+        [content] if content.kind() == "string_content" => {
+            Some(node_text(*content, source).to_string())
+        }
+        _ => None,
+    }
+}
+
+/// Resolve a Ruby feature to a file: relative ones next to the source file, others in
+/// the `lib/` directory of the source file's nearest enclosing project
+pub fn resolve_import(workspace: &Path, source_file: &Path, import: &str) -> Option<PathBuf> {
+    let with_extension = |path: PathBuf| {
+        if path.extension().is_some_and(|e| e == "rb") {
+            path
+        } else {
+            PathBuf::from(format!("{}.rb", path.display()))
+        }
+    };
+
+    if import.starts_with('.') {
+        let path = with_extension(normalize(&source_file.parent()?.join(import)));
+        return path.is_file().then_some(path);
+    }
+
+    for dir in source_file.ancestors().skip(1) {
+        let path = with_extension(dir.join("lib").join(import));
+        if path.is_file() {
+            return Some(path);
+        }
+        if dir == workspace {
+            break;
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_extract_imports() {
+        let source = r#"
+require 'json'
+require_relative '../lib/shop/cart'
+require_relative("order")
+autoload :Billing, 'shop/billing'
+require "plugins/#{name}"
+Kernel.require 'ignored'
+"#;
+        assert_eq!(
+            extract_imports(source).unwrap(),
+            vec!["../lib/shop/cart", "./order", "json", "shop/billing"]
+        );
+    }
+}
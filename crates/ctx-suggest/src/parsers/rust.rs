@@ -1,113 +1,381 @@
 //! Rust import parser
 
-use std::path::Path;
+use std::path::{Path, PathBuf};
+use std::sync::LazyLock;
 
 use anyhow::Result;
-use regex::Regex;
-use std::sync::LazyLock;
+use tree_sitter::Node;
+
+use super::{FileCache, cargo, node_text, normalize, parse_tree, read_cached, walk_named};
+
+/// Prefix for a `#[path = "..."]` module, followed by its path relative to the source file
+pub const PATH_PREFIX: &str = "path:";
+
+/// Crates that are never part of a workspace
+const SYSROOT_CRATES: &[&str] = &["std", "core", "alloc", "proc_macro", "test"];
 
-// Matches: use crate::foo::bar; use super::baz; use self::qux;
-static USE_REGEX: LazyLock<Regex> =
-    LazyLock::new(|| Regex::new(r"^\s*use\s+((?:crate|super|self)(?:::\w+)+)").unwrap());
+/// `pub use` hops followed looking for where an item is defined
+const MAX_REEXPORT_DEPTH: usize = 8;
 
-// Matches: mod foo; (without body - external module)
-static MOD_REGEX: LazyLock<Regex> =
-    LazyLock::new(|| Regex::new(r"^\s*(?:pub\s+)?mod\s+(\w+)\s*;").unwrap());
+static EXPORTS: LazyLock<FileCache<Exports>> = LazyLock::new(FileCache::new);
 
 /// Parse imports from a Rust file
 pub async fn parse_imports(path: &Path) -> Result<Vec<String>> {
     let content = tokio::fs::read_to_string(path).await?;
+    extract_imports(&content)
+}
+
+/// Module paths a Rust file depends on: `use` paths (grouped uses expanded, `pub use`
+/// included, standard library left out) and `mod foo;` declarations as `self::foo`, or
+/// as a [`PATH_PREFIX`] import when they carry a `#[path]` attribute
+pub fn extract_imports(source: &str) -> Result<Vec<String>> {
+    let tree = parse_tree(tree_sitter_rust::LANGUAGE.into(), source)?;
     let mut imports = Vec::new();
+    collect(tree.root_node(), source, &mut imports);
 
-    for line in content.lines() {
-        if let Some(cap) = USE_REGEX.captures(line) {
-            imports.push(cap[1].to_string());
+    imports.sort();
+    imports.dedup();
+    Ok(imports)
+}
+
+/// Record the `use` declarations and `mod` items under `root`
+fn collect(root: Node, source: &str, imports: &mut Vec<String>) {
+    walk_named(root, |node| match node.kind() {
+        "use_declaration" => {
+            if let Some(argument) = node.child_by_field_name("argument") {
+                let depth = inline_modules(node, source).len();
+                let mut paths = Vec::new();
+                expand_use_tree(argument, source, "", &mut paths);
+                imports.extend(
+                    paths
+                        .iter()
+                        .filter_map(|(path, _)| module_path(path, depth)),
+                );
+            }
+            false
+        }
+        "mod_item" if node.child_by_field_name("body").is_none() => {
+            let Some(name) = node.child_by_field_name("name") else {
+                return false;
+            };
+            let inline = inline_modules(node, source);
+            if let Some(file) = path_attribute(node, source) {
+                let mut relative: PathBuf = inline.iter().collect();
+                relative.push(file);
+                imports.push(format!("{}{}", PATH_PREFIX, relative.display()));
+            } else {
+                let mut segments = vec!["self"];
+                segments.extend(inline.iter().map(String::as_str));
+                segments.push(node_text(name, source));
+                imports.push(segments.join("::"));
+            }
+            false
         }
-        if let Some(cap) = MOD_REGEX.captures(line) {
-            imports.push(format!("self::{}", &cap[1]));
+        _ => true,
+    });
+}
+
+/// Names of the `mod x { ... }` blocks enclosing `node`, outermost first
+fn inline_modules(node: Node, source: &str) -> Vec<String> {
+    let mut names = Vec::new();
+    let mut current = node.parent();
+    while let Some(parent) = current {
+        if parent.kind() == "mod_item"
+            && parent.child_by_field_name("body").is_some()
+            && let Some(name) = parent.child_by_field_name("name")
+        {
+            names.push(node_text(name, source).to_string());
         }
+        current = parent.parent();
     }
+    names.reverse();
+    names
+}
 
-    Ok(imports)
+/// Flatten a use tree such as `crate::{a, b::{c, d as e}, f::*}` into full paths with
+/// their `as` aliases. Glob imports end in `::*`.
+fn expand_use_tree(
+    node: Node,
+    source: &str,
+    prefix: &str,
+    paths: &mut Vec<(String, Option<String>)>,
+) {
+    let join = |path: &str| {
+        let path: String = path.split_whitespace().collect();
+        if prefix.is_empty() {
+            path
+        } else {
+            format!("{}::{}", prefix, path)
+        }
+    };
+    match node.kind() {
+        "use_list" => {
+            let mut cursor = node.walk();
+            for item in node.named_children(&mut cursor) {
+                expand_use_tree(item, source, prefix, paths);
+            }
+        }
+        "scoped_use_list" => {
+            let prefix = match node.child_by_field_name("path") {
+                Some(path) => join(node_text(path, source)),
+                None => prefix.to_string(),
+            };
+            if let Some(list) = node.child_by_field_name("list") {
+                expand_use_tree(list, source, &prefix, paths);
+            }
+        }
+        "use_as_clause" => {
+            if let (Some(path), Some(alias)) = (
+                node.child_by_field_name("path"),
+                node.child_by_field_name("alias"),
+            ) {
+                let alias = node_text(alias, source).to_string();
+                paths.push((join(node_text(path, source)), Some(alias)));
+            }
+        }
+        "use_wildcard" => match node.named_child(0) {
+            Some(path) => paths.push((format!("{}::*", join(node_text(path, source))), None)),
+            None => paths.push((format!("{}::*", prefix), None)),
+        },
+        "crate" | "self" | "super" | "identifier" | "scoped_identifier" => {
+            paths.push((join(node_text(node, source)), None));
+        }
+        _ => {}
+    }
 }
 
-/// Resolve a Rust import to a file path
-pub fn resolve_import(
-    _workspace: &Path,
-    source_file: &Path,
-    import: &str,
-) -> Option<std::path::PathBuf> {
-    let parts: Vec<&str> = import.split("::").collect();
-    if parts.is_empty() {
+/// Rewrite a use path seen `depth` inline modules deep as one relative to the file's
+/// own module. Paths into the file's inline modules and the standard library are dropped.
+fn module_path(path: &str, depth: usize) -> Option<String> {
+    let path = path.strip_suffix("::*").unwrap_or(path);
+    let path = path.strip_suffix("::self").unwrap_or(path);
+    let path = path.strip_prefix("::").unwrap_or(path);
+    let segments: Vec<&str> = path.split("::").collect();
+    let (prefix, rest) = match segments[0] {
+        "crate" => ("crate".to_string(), &segments[1..]),
+        "self" if depth == 0 => ("self".to_string(), &segments[1..]),
+        "self" => return None,
+        "super" => {
+            let supers = segments.iter().take_while(|s| **s == "super").count();
+            if supers < depth {
+                return None;
+            }
+            let prefix = if supers == depth {
+                "self".to_string()
+            } else {
+                vec!["super"; supers - depth].join("::")
+            };
+            (prefix, &segments[supers..])
+        }
+        name if SYSROOT_CRATES.contains(&name) => return None,
+        // Another crate, or a module in scope
+        _ => return Some(path.to_string()),
+    };
+    if rest.is_empty() {
         return None;
     }
+    Some(format!("{}::{}", prefix, rest.join("::")))
+}
 
-    let source_dir = source_file.parent()?;
-
-    match parts[0] {
-        "crate" => {
-            // Find crate root (look for Cargo.toml)
-            let mut crate_root = source_dir.to_owned();
-            loop {
-                if crate_root.join("Cargo.toml").exists() {
-                    break;
-                }
-                if !crate_root.pop() {
-                    return None;
+/// Value of a `#[path = "..."]` attribute on `item`
+fn path_attribute(item: Node, source: &str) -> Option<String> {
+    let mut sibling = item.prev_named_sibling();
+    while let Some(node) = sibling {
+        match node.kind() {
+            "attribute_item" => {
+                let text = node_text(node, source);
+                let inner = text.trim_start_matches("#[").trim_end_matches(']');
+                if let Some((key, value)) = inner.split_once('=')
+                    && key.trim() == "path"
+                {
+                    return Some(value.trim().trim_matches('"').to_string());
                 }
             }
+            "line_comment" | "block_comment" => {}
+            _ => return None,
+        }
+        sibling = node.prev_named_sibling();
+    }
+    None
+}
+
+/// Resolve a Rust import to the file defining what it names. Paths into other crates of
+/// the workspace resolve through their Cargo manifests, and items a module only
+/// re-exports with `pub use` resolve to the file that defines them.
+pub fn resolve_import(_workspace: &Path, source_file: &Path, import: &str) -> Option<PathBuf> {
+    if let Some(relative) = import.strip_prefix(PATH_PREFIX) {
+        let path = normalize(&source_file.parent()?.join(relative));
+        return path.is_file().then_some(path);
+    }
+
+    let parts: Vec<&str> = import.split("::").collect();
+    resolve_path(source_file, &parts, 0)
+        .map(|(file, _)| file)
+        .filter(|path| path != source_file)
+}
 
-            // Resolve path from crate root/src
-            let src_dir = crate_root.join("src");
-            resolve_module_path(&src_dir, &parts[1..])
+/// File defining the item `parts` names as seen from `file`, and whether the item was
+/// found there rather than only narrowed down to the module that should hold it
+fn resolve_path(file: &Path, parts: &[&str], depth: usize) -> Option<(PathBuf, bool)> {
+    let (root, dir, rest) = match *parts.first()? {
+        "crate" => {
+            let root = cargo::own_crate_root(file)?;
+            let dir = root.parent()?.to_owned();
+            (root, dir, &parts[1..])
         }
+        "self" => (file.to_owned(), module_dir(file)?, &parts[1..]),
         "super" => {
-            // Go up one directory
-            let parent = source_dir.parent()?;
-            resolve_module_path(parent, &parts[1..])
-        }
-        "self" => {
-            // Same directory
-            resolve_module_path(source_dir, &parts[1..])
+            let supers = parts.iter().take_while(|p| **p == "super").count();
+            let mut dir = module_dir(file)?;
+            for _ in 0..supers {
+                dir = dir.parent()?.to_owned();
+            }
+            (module_file(&dir)?, dir, &parts[supers..])
         }
-        _ => None,
+        name => match cargo::crate_root(file, name) {
+            Some(root) => {
+                let dir = root.parent()?.to_owned();
+                (root, dir, &parts[1..])
+            }
+            // Since the 2018 edition, paths may also start with a module in scope
+            None => (file.to_owned(), module_dir(file)?, parts),
+        },
+    };
+
+    // The deepest module file the path names; what's left are items inside it
+    let (module, consumed) = (1..=rest.len())
+        .rev()
+        .find_map(|len| {
+            let path: PathBuf = std::iter::once(dir.as_path())
+                .chain(rest[..len].iter().map(Path::new))
+                .collect();
+            Some((module_file(&path)?, len))
+        })
+        .unwrap_or((root, 0));
+
+    if consumed == rest.len() {
+        return Some((module, true));
     }
+    Some(find_item(&module, &rest[consumed..], depth))
 }
 
-/// Resolve module path to a file
-fn resolve_module_path(base: &Path, parts: &[&str]) -> Option<std::path::PathBuf> {
-    if parts.is_empty() {
-        return None;
+/// Where `path[0]`, an item of `module`, is defined: `module` itself, or wherever its
+/// `pub use` declarations lead
+fn find_item(module: &Path, path: &[&str], depth: usize) -> (PathBuf, bool) {
+    let not_found = (module.to_owned(), false);
+    if depth >= MAX_REEXPORT_DEPTH {
+        return not_found;
     }
+    let Some(exports) = read_cached(&EXPORTS, module, |source| Some(Exports::parse(source))) else {
+        return not_found;
+    };
 
-    let mut current = base.to_owned();
+    let follow = |target: &str| {
+        let mut target: Vec<&str> = target.split("::").collect();
+        target.extend(&path[1..]);
+        resolve_path(module, &target, depth + 1)
+    };
 
-    for (i, part) in parts.iter().enumerate() {
-        let is_last = i == parts.len() - 1;
+    if let Some((_, target)) = exports.reexports.iter().find(|(name, _)| name == path[0])
+        && let Some(found) = follow(target)
+    {
+        return found;
+    }
+    if exports.items.iter().any(|item| item == path[0]) {
+        return (module.to_owned(), true);
+    }
+    exports
+        .globs
+        .iter()
+        .find_map(|glob| {
+            let found = follow(&format!("{}::{}", glob, path[0]))?;
+            found.1.then_some(found)
+        })
+        .unwrap_or(not_found)
+}
 
-        if is_last {
-            // Try module.rs
-            let file_path = current.join(format!("{part}.rs"));
-            if file_path.exists() {
-                return Some(file_path);
-            }
+/// What a module file makes visible to other modules
+#[derive(Debug, Default)]
+struct Exports {
+    /// Names of items defined in the file
+    items: Vec<String>,
+    /// `pub use` declarations: the name exported, and the path it stands for
+    reexports: Vec<(String, String)>,
+    /// Modules whose items are all re-exported with `pub use path::*`
+    globs: Vec<String>,
+}
 
-            // Try module/mod.rs
-            let mod_path = current.join(part).join("mod.rs");
-            if mod_path.exists() {
-                return Some(mod_path);
+impl Exports {
+    fn parse(source: &str) -> Self {
+        let mut exports = Self::default();
+        let Ok(tree) = parse_tree(tree_sitter_rust::LANGUAGE.into(), source) else {
+            return exports;
+        };
+        let root = tree.root_node();
+        let mut cursor = root.walk();
+        for item in root.named_children(&mut cursor) {
+            match item.kind() {
+                "use_declaration" => {
+                    let mut cursor = item.walk();
+                    let public = item
+                        .named_children(&mut cursor)
+                        .any(|c| c.kind() == "visibility_modifier");
+                    let Some(argument) = item.child_by_field_name("argument") else {
+                        continue;
+                    };
+                    if !public {
+                        continue;
+                    }
+                    let mut paths = Vec::new();
+                    expand_use_tree(argument, source, "", &mut paths);
+                    for (path, alias) in paths {
+                        if let Some(glob) = path.strip_suffix("::*") {
+                            exports.globs.push(glob.to_string());
+                            continue;
+                        }
+                        let path = path.strip_suffix("::self").unwrap_or(&path).to_string();
+                        let name = alias
+                            .or_else(|| path.rsplit("::").next().map(str::to_string))
+                            .unwrap_or_default();
+                        exports.reexports.push((name, path));
+                    }
+                }
+                "function_item" | "struct_item" | "enum_item" | "union_item" | "trait_item"
+                | "type_item" | "const_item" | "static_item" | "mod_item" | "macro_definition" => {
+                    if let Some(name) = item.child_by_field_name("name") {
+                        exports.items.push(node_text(name, source).to_string());
+                    }
+                }
+                _ => {}
             }
-
-            return None;
-        }
-        // Navigate into directory
-        current = current.join(part);
-        if !current.exists() {
-            return None;
         }
+        exports
     }
+}
 
-    None
+/// Directory holding the child modules of the module defined in `file`: crate roots,
+/// `mod.rs` files and `src/lib.rs` use their own directory, `src/net.rs` uses `src/net/`
+fn module_dir(file: &Path) -> Option<PathBuf> {
+    let dir = file.parent()?;
+    let stem = file.file_stem()?.to_str()?;
+    if matches!(stem, "mod" | "lib" | "main")
+        || cargo::own_crate_root(file).is_some_and(|root| root == file)
+    {
+        Some(dir.to_owned())
+    } else {
+        Some(dir.join(stem))
+    }
+}
+
+/// File defining the module whose children live in `dir`
+fn module_file(dir: &Path) -> Option<PathBuf> {
+    let candidates = [
+        dir.with_extension("rs"),
+        dir.join("mod.rs"),
+        dir.join("lib.rs"),
+        dir.join("main.rs"),
+    ];
+    candidates.into_iter().find(|path| path.is_file())
 }
 
 #[cfg(test)]
@@ -115,16 +383,78 @@ mod tests {
     use super::*;
 
     #[test]
-    fn test_use_regex() {
-        let line = "use crate::foo::bar;";
-        let cap = USE_REGEX.captures(line).unwrap();
-        assert_eq!(&cap[1], "crate::foo::bar");
+    fn test_grouped_and_reexported_uses() {
+        let source = r#"
+pub use crate::config::{Config, load as load_config};
+use crate::net::{
+    client::Client,
+    server::{self, Server},
+};
+use ctx_core::{Artifact, Error as CoreError};
+use std::collections::HashMap;
+use super::*;
+"#;
+        assert_eq!(
+            extract_imports(source).unwrap(),
+            vec![
+                "crate::config::Config",
+                "crate::config::load",
+                "crate::net::client::Client",
+                "crate::net::server",
+                "crate::net::server::Server",
+                "ctx_core::Artifact",
+                "ctx_core::Error",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_mod_declarations() {
+        let source = r#"
+pub mod utils;
+#[cfg(unix)]
+#[path = "sys/unix.rs"]
+mod sys;
+mod inline {
+    mod nested;
+    use super::utils::helper;
+    use super::super::parent;
+    use self::nested::Thing;
+}
+"#;
+        assert_eq!(
+            extract_imports(source).unwrap(),
+            vec![
+                "path:sys/unix.rs",
+                "self::inline::nested",
+                "self::utils",
+                "self::utils::helper",
+                "super::parent",
+            ]
+        );
     }
 
     #[test]
-    fn test_mod_regex() {
-        let line = "pub mod utils;";
-        let cap = MOD_REGEX.captures(line).unwrap();
-        assert_eq!(&cap[1], "utils");
+    fn test_exports() {
+        let exports = Exports::parse(
+            r#"
+mod model;
+pub mod util;
+pub use model::{Item as LineItem, Order};
+pub use self::money::*;
+use std::fmt;
+pub struct Db;
+macro_rules! query { () => {} }
+"#,
+        );
+        assert_eq!(exports.items, vec!["model", "util", "Db", "query"]);
+        assert_eq!(
+            exports.reexports,
+            vec![
+                ("LineItem".to_string(), "model::Item".to_string()),
+                ("Order".to_string(), "model::Order".to_string()),
+            ]
+        );
+        assert_eq!(exports.globs, vec!["self::money"]);
     }
 }
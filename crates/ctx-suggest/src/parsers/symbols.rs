@@ -0,0 +1,273 @@
+//! Symbols a source file defines and references, for relating files that share types
+//! and functions without importing each other
+
+use std::collections::BTreeSet;
+use std::path::Path;
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use tree_sitter::Node;
+
+use super::{Language, node_text, parse_tree, walk_named};
+
+/// Shorter names (`i`, `id`, `ok`) relate too many unrelated files to be useful
+const MIN_SYMBOL_LEN: usize = 3;
+
+/// Kotlin declaration keywords, for the word scanner used in place of a grammar
+const KOTLIN_DECLARATIONS: &[&str] = &["class", "interface", "object", "fun", "typealias"];
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Symbols {
+    /// Types, functions, classes and other named items the file declares
+    pub defines: Vec<String>,
+    /// Identifiers the file uses that it doesn't define itself
+    pub references: Vec<String>,
+}
+
+/// Symbols of the file at `path`, whose content is `source`
+pub fn extract_symbols(path: &Path, source: &str) -> Result<Symbols> {
+    let Some(language) = Language::from_path(path) else {
+        return Ok(Symbols::default());
+    };
+    let Some(grammar) = language.grammar(path) else {
+        return Ok(scan_kotlin(source));
+    };
+
+    let tree = parse_tree(grammar, source)?;
+    let mut defines = BTreeSet::new();
+    let mut identifiers = BTreeSet::new();
+    collect(
+        tree.root_node(),
+        source,
+        language,
+        &mut defines,
+        &mut identifiers,
+    );
+    Ok(Symbols::new(defines, identifiers))
+}
+
+impl Symbols {
+    fn new(defines: BTreeSet<String>, identifiers: BTreeSet<String>) -> Self {
+        let long_enough = |name: &String| name.chars().count() >= MIN_SYMBOL_LEN;
+        Self {
+            references: identifiers
+                .difference(&defines)
+                .filter(|name| long_enough(name))
+                .cloned()
+                .collect(),
+            defines: defines.into_iter().filter(long_enough).collect(),
+        }
+    }
+}
+
+fn collect(
+    node: Node,
+    source: &str,
+    language: Language,
+    defines: &mut BTreeSet<String>,
+    identifiers: &mut BTreeSet<String>,
+) {
+    walk_named(node, |node| {
+        if let Some(name) = definition_name(node, source, language) {
+            defines.insert(name);
+        }
+        if node.child_count() == 0 && is_identifier(node.kind()) {
+            identifiers.insert(node_text(node, source).to_string());
+        }
+        true
+    });
+}
+
+/// Leaf node kinds naming something, across grammars
+pub(super) fn is_identifier(kind: &str) -> bool {
+    kind.ends_with("identifier") || matches!(kind, "constant" | "name")
+}
+
+/// Name declared by `node`, when it's a type, function or class declaration
+fn definition_name(node: Node, source: &str, language: Language) -> Option<String> {
+    let kinds: &[&str] = match language {
+        Language::Rust => &[
+            "struct_item",
+            "enum_item",
+            "union_item",
+            "trait_item",
+            "type_item",
+            "function_item",
+            "function_signature_item",
+            "const_item",
+            "static_item",
+            "macro_definition",
+        ],
+        Language::TypeScript => &[
+            "class_declaration",
+            "abstract_class_declaration",
+            "interface_declaration",
+            "type_alias_declaration",
+            "enum_declaration",
+            "function_declaration",
+            "generator_function_declaration",
+        ],
+        Language::Python => &["class_definition", "function_definition"],
+        Language::Go => &["function_declaration", "method_declaration", "type_spec"],
+        Language::Java | Language::Kotlin => &[
+            "class_declaration",
+            "interface_declaration",
+            "enum_declaration",
+            "record_declaration",
+            "annotation_type_declaration",
+            "method_declaration",
+        ],
+        Language::C | Language::Cpp => &[
+            "struct_specifier",
+            "union_specifier",
+            "enum_specifier",
+            "class_specifier",
+        ],
+        Language::CSharp => &[
+            "class_declaration",
+            "struct_declaration",
+            "interface_declaration",
+            "enum_declaration",
+            "record_declaration",
+            "delegate_declaration",
+            "method_declaration",
+        ],
+        Language::Ruby => &["class", "module", "method", "singleton_method"],
+        Language::Php => &[
+            "class_declaration",
+            "interface_declaration",
+            "trait_declaration",
+            "enum_declaration",
+            "function_definition",
+            "method_declaration",
+        ],
+    };
+
+    let name = if kinds.contains(&node.kind()) {
+        // C structs are only declared where they have a body
+        if matches!(language, Language::C | Language::Cpp)
+            && node.child_by_field_name("body").is_none()
+        {
+            return None;
+        }
+        node.child_by_field_name("name")?
+    } else {
+        match (language, node.kind()) {
+            // `const handler = () => ...`
+            (Language::TypeScript, "variable_declarator") => {
+                let value = node.child_by_field_name("value")?;
+                if !matches!(value.kind(), "arrow_function" | "function_expression") {
+                    return None;
+                }
+                node.child_by_field_name("name")?
+            }
+            (Language::C | Language::Cpp, "function_definition" | "type_definition") => {
+                declared_identifier(node.child_by_field_name("declarator")?)?
+            }
+            _ => return None,
+        }
+    };
+
+    // `Admin::User` declares `User`
+    let name = node_text(name, source);
+    let name = name.rsplit("::").next().unwrap_or(name);
+    Some(name.to_string())
+}
+
+/// The identifier inside a C declarator such as `*(*make_handler)(int)`
+fn declared_identifier(declarator: Node) -> Option<Node> {
+    if is_identifier(declarator.kind()) {
+        return Some(declarator);
+    }
+    match declarator.child_by_field_name("declarator") {
+        Some(inner) => declared_identifier(inner),
+        // Qualified C++ names (`Parser::parse`) keep the name last
+        None => declarator.child_by_field_name("name"),
+    }
+}
+
+/// Symbols of a Kotlin file from its words, since there's no grammar to parse it with
+fn scan_kotlin(source: &str) -> Symbols {
+    let words: Vec<&str> = source
+        .split(|c: char| !(c.is_alphanumeric() || c == '_'))
+        .filter(|w| !w.is_empty() && !w.starts_with(|c: char| c.is_ascii_digit()))
+        .collect();
+    let defines = words
+        .windows(2)
+        .filter(|pair| KOTLIN_DECLARATIONS.contains(&pair[0]))
+        .map(|pair| pair[1].to_string())
+        .collect();
+    let identifiers = words.iter().map(|w| w.to_string()).collect();
+    Symbols::new(defines, identifiers)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn symbols(file: &str, source: &str) -> Symbols {
+        extract_symbols(Path::new(file), source).unwrap()
+    }
+
+    #[test]
+    fn test_rust_symbols() {
+        let symbols = symbols(
+            "lib.rs",
+            r#"
+pub struct Order { items: Vec<LineItem> }
+pub trait Priced { fn price(&self) -> Money; }
+impl Priced for Order {
+    fn price(&self) -> Money { total(&self.items) }
+}
+// Mentioned in a comment: Invoice
+"#,
+        );
+        assert_eq!(symbols.defines, vec!["Order", "Priced", "price"]);
+        assert_eq!(
+            symbols.references,
+            vec!["LineItem", "Money", "Vec", "items", "total"]
+        );
+    }
+
+    #[test]
+    fn test_typescript_symbols() {
+        let symbols = symbols(
+            "api.ts",
+            r#"
+export interface Session { user: User }
+export class AuthClient extends BaseClient {}
+export const login = async (s: Session) => fetchToken(s);
+const retries = 3;
+"#,
+        );
+        assert_eq!(symbols.defines, vec!["AuthClient", "Session", "login"]);
+        assert!(symbols.references.contains(&"BaseClient".to_string()));
+        assert!(symbols.references.contains(&"fetchToken".to_string()));
+    }
+
+    #[test]
+    fn test_c_and_python_symbols() {
+        let c = symbols(
+            "net.c",
+            "struct conn { int fd; };\ntypedef struct conn conn_t;\nstruct conn;\nint conn_open(conn_t *c) { return sock(c); }\n",
+        );
+        assert_eq!(c.defines, vec!["conn", "conn_open", "conn_t"]);
+
+        let python = symbols(
+            "auth.py",
+            "class Token(Base):\n    def refresh(self):\n        return renew(self)\n",
+        );
+        assert_eq!(python.defines, vec!["Token", "refresh"]);
+        assert_eq!(python.references, vec!["Base", "renew", "self"]);
+    }
+
+    #[test]
+    fn test_kotlin_symbols() {
+        let symbols = symbols(
+            "Auth.kt",
+            "class TokenStore(val api: Api) {\n    fun refresh() = api.renew()\n}\n",
+        );
+        assert_eq!(symbols.defines, vec!["TokenStore", "refresh"]);
+        assert!(symbols.references.contains(&"renew".to_string()));
+    }
+}
@@ -0,0 +1,125 @@
+//! Words of a source file's identifiers, for relating files by vocabulary
+
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use anyhow::Result;
+use tree_sitter::Node;
+
+use super::symbols::is_identifier;
+use super::{Language, node_text, parse_tree, walk_named};
+
+/// Shorter words (`i`, `id`, `to`) say little about what a file is about
+const MIN_TERM_LEN: usize = 3;
+
+/// How often each word occurs in the identifiers of the file at `path`, whose content
+/// is `source`. `parseHttpRequest` and `parse_http_request` both count as `parse`,
+/// `http` and `request`.
+pub fn extract_terms(path: &Path, source: &str) -> Result<BTreeMap<String, u32>> {
+    let mut terms = BTreeMap::new();
+    let Some(language) = Language::from_path(path) else {
+        return Ok(terms);
+    };
+    let Some(grammar) = language.grammar(path) else {
+        // Kotlin has no grammar, so every word counts
+        for word in source.split(|c: char| !(c.is_alphanumeric() || c == '_')) {
+            add_terms(word, &mut terms);
+        }
+        return Ok(terms);
+    };
+
+    let tree = parse_tree(grammar, source)?;
+    collect(tree.root_node(), source, &mut terms);
+    Ok(terms)
+}
+
+fn collect(node: Node, source: &str, terms: &mut BTreeMap<String, u32>) {
+    walk_named(node, |node| {
+        if node.child_count() == 0 && is_identifier(node.kind()) {
+            add_terms(node_text(node, source), terms);
+        }
+        true
+    });
+}
+
+fn add_terms(identifier: &str, terms: &mut BTreeMap<String, u32>) {
+    for word in split_identifier(identifier) {
+        if word.chars().count() >= MIN_TERM_LEN && !word.starts_with(|c: char| c.is_ascii_digit()) {
+            *terms.entry(word).or_default() += 1;
+        }
+    }
+}
+
+/// Lowercase words of a snake_case, camelCase or PascalCase identifier, keeping
+/// acronyms together (`HTTPRequest` is `http` and `request`)
+fn split_identifier(identifier: &str) -> Vec<String> {
+    let chars: Vec<char> = identifier.chars().collect();
+    let mut words = Vec::new();
+    let mut word = String::new();
+
+    for (i, &c) in chars.iter().enumerate() {
+        if !c.is_alphanumeric() {
+            words.push(std::mem::take(&mut word));
+            continue;
+        }
+        if c.is_uppercase() && i > 0 {
+            let previous = chars[i - 1];
+            let next_lower = chars.get(i + 1).is_some_and(|n| n.is_lowercase());
+            if previous.is_lowercase()
+                || previous.is_ascii_digit()
+                || (previous.is_uppercase() && next_lower)
+            {
+                words.push(std::mem::take(&mut word));
+            }
+        }
+        word.extend(c.to_lowercase());
+    }
+    words.push(word);
+
+    words.retain(|word| !word.is_empty());
+    words
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_split_identifier() {
+        assert_eq!(
+            split_identifier("parseHTTPRequest"),
+            vec!["parse", "http", "request"]
+        );
+        assert_eq!(
+            split_identifier("StripeProvider"),
+            vec!["stripe", "provider"]
+        );
+        assert_eq!(
+            split_identifier("MAX_RETRY_COUNT"),
+            vec!["max", "retry", "count"]
+        );
+        assert_eq!(split_identifier("__init__"), vec!["init"]);
+        assert_eq!(split_identifier("sha256Sum"), vec!["sha256", "sum"]);
+    }
+
+    #[test]
+    fn test_extract_terms() {
+        let terms = extract_terms(
+            Path::new("stripe.rs"),
+            r#"
+// Charges go through the payment gateway
+pub struct StripeProvider { client: StripeClient }
+impl StripeProvider {
+    pub fn charge(&self, amount: Amount) -> Result<ChargeId> { self.client.charge(amount) }
+}
+"#,
+        )
+        .unwrap();
+        assert_eq!(terms["stripe"], 3);
+        assert_eq!(terms["charge"], 3);
+        assert_eq!(terms["amount"], 3);
+        // Comments aren't identifiers
+        assert!(!terms.contains_key("gateway"));
+        assert!(!terms.contains_key("id"));
+    }
+}
@@ -1,44 +1,34 @@
 //! TypeScript/JavaScript import parser
 
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
 use anyhow::Result;
-use regex::Regex;
-use std::sync::LazyLock;
+use tree_sitter::Node;
 
-// Matches: import ... from './foo' or "../../bar" or '@scope/pkg'
-static IMPORT_FROM_REGEX: LazyLock<Regex> =
-    LazyLock::new(|| Regex::new(r#"import\s+.*?\s+from\s+['"]([^'"]+)['"]"#).unwrap());
-
-// Matches: import './styles.css' (side-effect imports)
-static IMPORT_SIDE_EFFECT: LazyLock<Regex> =
-    LazyLock::new(|| Regex::new(r#"^\s*import\s+['"]([^'"]+)['"]"#).unwrap());
-
-// Matches: require('./foo')
-static REQUIRE_REGEX: LazyLock<Regex> =
-    LazyLock::new(|| Regex::new(r#"require\s*\(\s*['"]([^'"]+)['"]\s*\)"#).unwrap());
-
-// Matches: export ... from './foo'
-static EXPORT_FROM_REGEX: LazyLock<Regex> =
-    LazyLock::new(|| Regex::new(r#"export\s+.*?\s+from\s+['"]([^'"]+)['"]"#).unwrap());
+use super::{node, node_text, normalize, parse_tree, walk_named};
 
 /// Parse imports from a TypeScript/JavaScript file
 pub async fn parse_imports(path: &Path) -> Result<Vec<String>> {
     let content = tokio::fs::read_to_string(path).await?;
-    let mut imports = Vec::new();
+    let jsx = matches!(
+        path.extension().and_then(|e| e.to_str()),
+        Some("tsx" | "jsx" | "js" | "mjs" | "cjs")
+    );
+    extract_imports(&content, jsx)
+}
 
-    for cap in IMPORT_FROM_REGEX.captures_iter(&content) {
-        imports.push(cap[1].to_string());
-    }
-    for cap in IMPORT_SIDE_EFFECT.captures_iter(&content) {
-        imports.push(cap[1].to_string());
-    }
-    for cap in REQUIRE_REGEX.captures_iter(&content) {
-        imports.push(cap[1].to_string());
-    }
-    for cap in EXPORT_FROM_REGEX.captures_iter(&content) {
-        imports.push(cap[1].to_string());
-    }
+/// Module specifiers a file depends on: `import` and `export ... from` statements,
+/// `import x = require()`, `require()` calls and dynamic `import()`. JavaScript is
+/// parsed with the TSX grammar when `jsx` is set, since it may contain JSX.
+pub fn extract_imports(source: &str, jsx: bool) -> Result<Vec<String>> {
+    let language = if jsx {
+        tree_sitter_typescript::LANGUAGE_TSX
+    } else {
+        tree_sitter_typescript::LANGUAGE_TYPESCRIPT
+    };
+    let tree = parse_tree(language.into(), source)?;
+    let mut imports = Vec::new();
+    collect(tree.root_node(), source, &mut imports);
 
     // Deduplicate
     imports.sort();
@@ -47,45 +37,95 @@ pub async fn parse_imports(path: &Path) -> Result<Vec<String>> {
     Ok(imports)
 }
 
-/// Resolve a TypeScript/JavaScript import to a file path
-pub fn resolve_import(
-    _workspace: &Path,
-    source_file: &Path,
-    import: &str,
-) -> Option<std::path::PathBuf> {
-    // Only resolve relative imports
-    if !import.starts_with('.') {
+fn collect(node: Node, source: &str, imports: &mut Vec<String>) {
+    walk_named(node, |node| {
+        visit(node, source, imports);
+        true
+    });
+}
+
+fn visit(node: Node, source: &str, imports: &mut Vec<String>) {
+    match node.kind() {
+        "import_statement" | "export_statement" | "import_require_clause" => {
+            if let Some(specifier) = node
+                .child_by_field_name("source")
+                .and_then(|s| string_value(s, source))
+            {
+                imports.push(specifier);
+            }
+        }
+        "call_expression" => {
+            let callee = node.child_by_field_name("function");
+            let is_import = callee.is_some_and(|f| {
+                f.kind() == "import"
+                    || (f.kind() == "identifier" && node_text(f, source) == "require")
+            });
+            if is_import
+                && let Some(specifier) = node
+                    .child_by_field_name("arguments")
+                    .and_then(|args| args.named_child(0))
+                    .and_then(|arg| string_value(arg, source))
+            {
+                imports.push(specifier);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Contents of a string literal node
+fn string_value(node: Node, source: &str) -> Option<String> {
+    if node.kind() != "string" {
         return None;
     }
+    let text = node_text(node, source);
+    text.get(1..text.len().checked_sub(1)?).map(str::to_string)
+}
+
+/// Extensions tried for a specifier without one, in TypeScript's order
+const EXTENSIONS: &[&str] = &["ts", "tsx", "d.ts", "js", "jsx", "mts", "cts", "mjs", "cjs"];
+
+/// Resolve a TypeScript/JavaScript import to a file path. Relative specifiers resolve
+/// next to the source file; bare ones through the `paths` and `baseUrl` of the nearest
+/// `tsconfig.json` (following `extends`), then through the `package.json` of a workspace
+/// package. Directories resolve to their `index` file, as barrel modules do.
+pub fn resolve_import(_workspace: &Path, source_file: &Path, import: &str) -> Option<PathBuf> {
+    if import.starts_with('.') {
+        return resolve_file(&normalize(&source_file.parent()?.join(import)));
+    }
 
-    let source_dir = source_file.parent()?;
-    let import_path = source_dir.join(import);
-
-    // Try various extensions
-    let extensions = [
-        "",
-        ".ts",
-        ".tsx",
-        ".js",
-        ".jsx",
-        "/index.ts",
-        "/index.tsx",
-        "/index.js",
-    ];
-
-    for ext in extensions {
-        let full_path = if let Some(stripped) = ext.strip_prefix('/') {
-            import_path.join(stripped)
-        } else {
-            std::path::PathBuf::from(format!("{}{}", import_path.display(), ext))
-        };
-
-        if full_path.exists() && full_path.is_file() {
-            return Some(full_path);
-        }
+    node::config_candidates(source_file, import)
+        .into_iter()
+        .chain(node::package_candidates(source_file, import))
+        .find_map(|candidate| resolve_file(&candidate))
+}
+
+/// The file `path` names, with an extension or `/index` added as needed. A `.js` path
+/// may name the `.ts` file it compiles from, as ESM imports in TypeScript do.
+fn resolve_file(path: &Path) -> Option<PathBuf> {
+    if path.is_file() {
+        return Some(path.to_owned());
     }
 
-    None
+    let with_extension =
+        |path: &Path, ext: &str| PathBuf::from(format!("{}.{}", path.display(), ext));
+    let compiled_from: &[&str] = match path.extension().and_then(|e| e.to_str()) {
+        Some("js") => &["ts", "tsx"],
+        Some("jsx") => &["tsx"],
+        Some("mjs") => &["mts"],
+        Some("cjs") => &["cts"],
+        _ => &[],
+    };
+    compiled_from
+        .iter()
+        .map(|ext| path.with_extension(ext))
+        .chain(EXTENSIONS.iter().map(|ext| with_extension(path, ext)))
+        .chain(
+            EXTENSIONS
+                .iter()
+                .map(|ext| with_extension(&path.join("index"), ext)),
+        )
+        .find(|candidate| candidate.is_file())
 }
 
 #[cfg(test)]
@@ -93,23 +133,57 @@ mod tests {
     use super::*;
 
     #[test]
-    fn test_import_from_regex() {
-        let line = r#"import { foo } from './utils';"#;
-        let cap = IMPORT_FROM_REGEX.captures(line).unwrap();
-        assert_eq!(&cap[1], "./utils");
+    fn test_extract_imports() {
+        let source = r#"
+import {
+  Button,
+  Card,
+} from './components/button';
+import type { Config } from "./config";
+import './styles.css';
+export * from './types';
+import legacy = require('./legacy');
+const util = require('./util');
+const page = () => import('./page');
+// import { unused } from './commented';
+const text = "import x from './not-an-import'";
+"#;
+        assert_eq!(
+            extract_imports(source, false).unwrap(),
+            vec![
+                "./components/button",
+                "./config",
+                "./legacy",
+                "./page",
+                "./styles.css",
+                "./types",
+                "./util",
+            ]
+        );
     }
 
     #[test]
-    fn test_import_side_effect() {
-        let line = r#"import './styles.css';"#;
-        let cap = IMPORT_SIDE_EFFECT.captures(line).unwrap();
-        assert_eq!(&cap[1], "./styles.css");
+    fn test_extract_imports_jsx() {
+        let source = r#"
+import { Button } from './button';
+export const App = () => <Button label="import x from './nope'" />;
+"#;
+        assert_eq!(extract_imports(source, true).unwrap(), vec!["./button"]);
     }
 
     #[test]
-    fn test_require_regex() {
-        let line = r#"const utils = require('./utils');"#;
-        let cap = REQUIRE_REGEX.captures(line).unwrap();
-        assert_eq!(&cap[1], "./utils");
+    fn test_extract_imports_deep_expression() {
+        // One tree level per `+`: a recursive walk would overflow a 2 MB thread stack
+        let source = format!(
+            "import {{ a }} from './a';\nconst x = {};\n",
+            vec!["a"; 20_000].join(" + ")
+        );
+        let imports = std::thread::Builder::new()
+            .stack_size(2 * 1024 * 1024)
+            .spawn(move || extract_imports(&source, false).unwrap())
+            .unwrap()
+            .join()
+            .unwrap();
+        assert_eq!(imports, vec!["./a"]);
     }
 }
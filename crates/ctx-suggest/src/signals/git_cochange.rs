@@ -4,88 +4,178 @@ use std::collections::HashMap;
 use std::path::{Path, PathBuf};
 use std::process::Command;
 use std::sync::RwLock;
-use std::time::Instant;
+use std::time::{Duration, Instant, SystemTime};
 
 use anyhow::{Context, Result};
 use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
 use tracing::debug;
 
 use super::Signal;
 use crate::cache::GitCoChangeCache;
+use crate::index::{COCHANGE_FILE, INDEX_VERSION, IndexStore, unix_time};
 
 /// Signal based on git co-change history
 pub struct GitCoChangeSignal {
     #[allow(dead_code)]
     workspace: PathBuf,
-    history_depth: usize,
+    options: CoChangeOptions,
+    store: IndexStore,
+    ttl: Duration,
     cache: RwLock<GitCoChangeCache>,
 }
 
+/// Which commits count, and how much
+#[derive(Debug, Clone, Default)]
+pub struct CoChangeOptions {
+    /// Most recent commits read
+    pub depth: usize,
+    /// Age at which a commit counts half as much as one made now; `None` for no decay
+    pub half_life: Option<Duration>,
+    /// Only count commits whose author name or email contains one of these
+    /// (case-insensitive); all commits when empty
+    pub authors: Vec<String>,
+    /// Revision range to read instead of the history of `HEAD`, such as `main..HEAD`
+    /// for the commits of the current branch since it forked
+    pub range: Option<String>,
+    /// Commits touching more files than this (vendoring, mass reformats) are skipped:
+    /// with per-commit normalization they'd add almost nothing, at a quadratic cost in
+    /// pairs. `None` for no limit.
+    pub max_commit_files: Option<usize>,
+}
+
+/// A commit and the files it touched, relative to the workspace
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub(crate) struct Commit {
+    pub hash: String,
+    /// Committer time, in seconds since the Unix epoch
+    pub time: u64,
+    /// `Name <email>`
+    pub author: String,
+    /// Paths after the commit
+    pub files: Vec<String>,
+    /// Files the commit renamed, as `(old, new)`
+    pub renames: Vec<(String, String)>,
+}
+
+/// The commits co-change counts are computed from, as stored on disk
+#[derive(Debug, Serialize, Deserialize)]
+pub(crate) struct CoChangeIndex {
+    pub version: u32,
+    pub workspace: PathBuf,
+    pub updated_at: u64,
+    /// `HEAD` when the index was last updated
+    pub head: String,
+    /// History depth the index was built with
+    pub depth: usize,
+    /// Revision range the index was built from, `None` for the history of `HEAD`
+    pub range: Option<String>,
+    /// Most recent first
+    pub commits: Vec<Commit>,
+}
+
 impl GitCoChangeSignal {
-    pub fn new(workspace: PathBuf, history_depth: usize) -> Self {
+    pub fn new(
+        workspace: PathBuf,
+        options: CoChangeOptions,
+        store: IndexStore,
+        ttl: Duration,
+    ) -> Self {
         Self {
             workspace,
-            history_depth,
+            options,
+            store,
+            ttl,
             cache: RwLock::new(GitCoChangeCache::new()),
         }
     }
 
-    /// Build the co-change index from git history
-    fn build_cochange_index(&self, workspace: &Path) -> Result<()> {
-        debug!("Building git co-change index for {:?}", workspace);
+    /// Bring the stored commit history up to date with `HEAD` (or the configured range),
+    /// reading only commits made since the last update unless history was rewritten
+    fn update_index(&self, workspace: &Path) -> Result<Vec<Commit>> {
+        let depth = self.options.depth;
+        let range = self.options.range.as_deref();
+        // For a range, both of its ends: the index is stale once either moves
+        let head = git(workspace, &["rev-parse", range.unwrap_or("HEAD")])?
+            .split_whitespace()
+            .collect::<Vec<_>>()
+            .join(" ");
+        let stored = self
+            .store
+            .load::<CoChangeIndex>(COCHANGE_FILE)
+            .filter(|index| {
+                index.version == INDEX_VERSION
+                    && index.workspace == workspace
+                    && index.depth == depth
+                    && index.range.as_deref() == range
+            });
+
+        let commits = match stored {
+            Some(index) if index.head == head => return Ok(index.commits),
+            Some(index) if range.is_none() && is_ancestor(workspace, &index.head, &head) => {
+                debug!("Updating git co-change index from {}", index.head);
+                let mut commits = self.read_log(workspace, &format!("{}..{}", index.head, head))?;
+                commits.extend(index.commits);
+                commits.truncate(depth);
+                commits
+            }
+            _ => {
+                debug!("Building git co-change index for {:?}", workspace);
+                self.read_log(workspace, range.unwrap_or(&head))?
+            }
+        };
 
-        let output = Command::new("git")
-            .args([
+        self.store.save(
+            COCHANGE_FILE,
+            &CoChangeIndex {
+                version: INDEX_VERSION,
+                workspace: workspace.to_owned(),
+                updated_at: unix_time(SystemTime::now()),
+                head,
+                depth,
+                range: range.map(str::to_owned),
+                commits: commits.clone(),
+            },
+        )?;
+        Ok(commits)
+    }
+
+    /// Commits in `range`, most recent first, up to the history depth. Paths are
+    /// relative to the workspace, which may be a subdirectory of the repository.
+    fn read_log(&self, workspace: &Path, range: &str) -> Result<Vec<Commit>> {
+        let output = git(
+            workspace,
+            &[
                 "log",
-                "--name-only",
-                "--format=COMMIT:%H",
+                "--name-status",
+                "-M",
+                "--relative",
+                "--format=COMMIT:%H%x00%ct%x00%an <%ae>",
                 "-n",
-                &self.history_depth.to_string(),
-            ])
-            .current_dir(workspace)
-            .output()
-            .context("Failed to run git log")?;
-
-        if !output.status.success() {
-            anyhow::bail!(
-                "git log failed: {}",
-                String::from_utf8_lossy(&output.stderr)
-            );
-        }
-
-        let stdout = String::from_utf8_lossy(&output.stdout);
-        let commits = parse_git_log(&stdout);
-        let mut cochange_counts: HashMap<PathBuf, HashMap<PathBuf, usize>> = HashMap::new();
-
-        for files in commits {
-            // Skip single-file commits (no co-change) and huge commits (likely merges/refactors)
-            if files.len() < 2 || files.len() > 50 {
-                continue;
-            }
+                &self.options.depth.to_string(),
+                range,
+                "--",
+            ],
+        )?;
+        Ok(parse_git_log(&output))
+    }
 
-            for i in 0..files.len() {
-                for j in 0..files.len() {
-                    if i != j {
-                        let file_i = workspace.join(&files[i]);
-                        let file_j = workspace.join(&files[j]);
-
-                        *cochange_counts
-                            .entry(file_i)
-                            .or_default()
-                            .entry(file_j)
-                            .or_default() += 1;
-                    }
-                }
-            }
-        }
+    /// Build the co-change index from git history
+    fn build_cochange_index(&self, workspace: &Path) -> Result<()> {
+        let commits = self.update_index(workspace)?;
+        let cochange_counts =
+            count_cochanges(&commits, &self.options, unix_time(SystemTime::now()));
 
         let mut cache = self.cache.write().unwrap();
         cache.cochanges.clear();
 
         for (file, cochanges) in cochange_counts {
-            let mut sorted: Vec<_> = cochanges.into_iter().collect();
-            sorted.sort_by(|a, b| b.1.cmp(&a.1));
-            cache.cochanges.insert(file, sorted);
+            let mut sorted: Vec<_> = cochanges
+                .into_iter()
+                .map(|(other, weight)| (workspace.join(other), weight))
+                .collect();
+            sorted.sort_by(|a, b| b.1.total_cmp(&a.1));
+            cache.cochanges.insert(workspace.join(file), sorted);
         }
 
         cache.built_at = Some(Instant::now());
@@ -99,7 +189,7 @@ impl GitCoChangeSignal {
     fn ensure_cache(&self, workspace: &Path) -> Result<()> {
         let needs_rebuild = {
             let cache = self.cache.read().unwrap();
-            !cache.is_valid(&workspace.to_owned())
+            !cache.is_valid(&workspace.to_owned(), self.ttl)
         };
 
         if needs_rebuild {
@@ -110,6 +200,33 @@ impl GitCoChangeSignal {
     }
 }
 
+/// Output of a git command run in `workspace`
+fn git(workspace: &Path, args: &[&str]) -> Result<String> {
+    let output = Command::new("git")
+        .args(args)
+        .current_dir(workspace)
+        .output()
+        .with_context(|| format!("Failed to run git {}", args[0]))?;
+
+    if !output.status.success() {
+        anyhow::bail!(
+            "git {} failed: {}",
+            args[0],
+            String::from_utf8_lossy(&output.stderr)
+        );
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+/// Whether `ancestor` is still in the history of `head`, i.e. it wasn't rebased away
+fn is_ancestor(workspace: &Path, ancestor: &str, head: &str) -> bool {
+    Command::new("git")
+        .args(["merge-base", "--is-ancestor", ancestor, head])
+        .current_dir(workspace)
+        .output()
+        .is_ok_and(|output| output.status.success())
+}
+
 #[async_trait]
 impl Signal for GitCoChangeSignal {
     fn name(&self) -> &'static str {
@@ -130,15 +247,12 @@ impl Signal for GitCoChangeSignal {
             return Ok(vec![]);
         }
 
-        let max_count = cochanges.first().map_or(1, |(_, c)| *c) as f64;
+        let max_weight = cochanges.first().map_or(1.0, |(_, w)| *w);
 
         let results = cochanges
             .iter()
             .filter(|(path, _)| path.exists())
-            .map(|(path, count)| {
-                let score = (*count as f64) / max_count;
-                (path.to_string_lossy().to_string(), score)
-            })
+            .map(|(path, weight)| (path.to_string_lossy().to_string(), weight / max_weight))
             .collect();
 
         Ok(results)
@@ -156,10 +270,61 @@ impl Signal for GitCoChangeSignal {
     }
 }
 
-/// Parse git log output into list of files per commit
-fn parse_git_log(output: &str) -> Vec<Vec<String>> {
-    let mut commits = Vec::new();
-    let mut current_files = Vec::new();
+/// How much each pair of files changed together, keyed by their current paths. Each
+/// commit counts 1 per file, shared among the files it changed alongside, and less the
+/// older it is.
+fn count_cochanges(
+    commits: &[Commit],
+    options: &CoChangeOptions,
+    now: u64,
+) -> HashMap<String, HashMap<String, f64>> {
+    let authors: Vec<String> = options.authors.iter().map(|a| a.to_lowercase()).collect();
+    let mut counts: HashMap<String, HashMap<String, f64>> = HashMap::new();
+    // Old path -> the path it has now, from renames seen in later commits
+    let mut renamed: HashMap<String, String> = HashMap::new();
+
+    for commit in commits {
+        let current = |path: &String| renamed.get(path).unwrap_or(path).clone();
+        let files: Vec<String> = commit.files.iter().map(current).collect();
+        let renames: Vec<(String, String)> = commit
+            .renames
+            .iter()
+            .map(|(old, new)| (old.clone(), current(new)))
+            .collect();
+        renamed.extend(renames);
+
+        if files.len() < 2
+            || options
+                .max_commit_files
+                .is_some_and(|max| files.len() > max)
+        {
+            continue;
+        }
+        let author = commit.author.to_lowercase();
+        if !authors.is_empty() && !authors.iter().any(|a| author.contains(a)) {
+            continue;
+        }
+
+        let decay = options.half_life.map_or(1.0, |half_life| {
+            let age = now.saturating_sub(commit.time) as f64;
+            0.5_f64.powf(age / half_life.as_secs_f64().max(1.0))
+        });
+        let weight = decay / (files.len() - 1) as f64;
+
+        for file in &files {
+            let pairs = counts.entry(file.clone()).or_default();
+            for other in files.iter().filter(|other| *other != file) {
+                *pairs.entry(other.clone()).or_default() += weight;
+            }
+        }
+    }
+
+    counts
+}
+
+/// Parse `git log --name-status --format=COMMIT:%H%x00%ct%x00%an <%ae>` output into commits
+fn parse_git_log(output: &str) -> Vec<Commit> {
+    let mut commits: Vec<Commit> = Vec::new();
 
     for line in output.lines() {
         let line = line.trim();
@@ -167,20 +332,32 @@ fn parse_git_log(output: &str) -> Vec<Vec<String>> {
             continue;
         }
 
-        if line.starts_with("COMMIT:") {
-            if !current_files.is_empty() {
-                commits.push(std::mem::take(&mut current_files));
+        if let Some(header) = line.strip_prefix("COMMIT:") {
+            let mut fields = header.split('\0');
+            commits.push(Commit {
+                hash: fields.next().unwrap_or_default().to_string(),
+                time: fields.next().and_then(|t| t.parse().ok()).unwrap_or(0),
+                author: fields.next().unwrap_or_default().to_string(),
+                files: Vec::new(),
+                renames: Vec::new(),
+            });
+        } else if let Some(commit) = commits.last_mut() {
+            // `M\tpath`, or `R097\told\tnew` for renames
+            let mut fields = line.split('\t');
+            let status = fields.next().unwrap_or_default();
+            let paths: Vec<&str> = fields.collect();
+            match (status.chars().next(), paths.as_slice()) {
+                (Some('R'), [old, new]) => {
+                    commit.renames.push((old.to_string(), new.to_string()));
+                    commit.files.push(new.to_string());
+                }
+                (Some('C'), [_, new]) => commit.files.push(new.to_string()),
+                (_, [path]) => commit.files.push(path.to_string()),
+                _ => {}
             }
-        } else {
-            current_files.push(line.to_string());
         }
     }
 
-    // Don't forget the last commit
-    if !current_files.is_empty() {
-        commits.push(current_files);
-    }
-
     commits
 }
 
@@ -190,22 +367,160 @@ mod tests {
 
     #[test]
     fn test_parse_git_log() {
-        let log = r#"COMMIT:abc123
-src/main.rs
-src/lib.rs
-
-COMMIT:def456
-src/lib.rs
-src/utils.rs
-src/config.rs
-"#;
+        let log = "COMMIT:abc123\x001700000000\x00Ada <ada@example.com>\n\
+M\tsrc/main.rs\n\
+R087\tsrc/util.rs\tsrc/utils.rs\n\
+\n\
+COMMIT:def456\x001600000000\x00Bob <bob@example.com>\n\
+A\tsrc/lib.rs\n\
+D\tsrc/config.rs\n";
 
         let commits = parse_git_log(log);
         assert_eq!(commits.len(), 2);
-        assert_eq!(commits[0], vec!["src/main.rs", "src/lib.rs"]);
+        assert_eq!(commits[0].hash, "abc123");
+        assert_eq!(commits[0].time, 1_700_000_000);
+        assert_eq!(commits[0].author, "Ada <ada@example.com>");
+        assert_eq!(commits[0].files, vec!["src/main.rs", "src/utils.rs"]);
         assert_eq!(
-            commits[1],
-            vec!["src/lib.rs", "src/utils.rs", "src/config.rs"]
+            commits[0].renames,
+            vec![("src/util.rs".to_string(), "src/utils.rs".to_string())]
+        );
+        assert_eq!(commits[1].hash, "def456");
+        assert_eq!(commits[1].files, vec!["src/lib.rs", "src/config.rs"]);
+    }
+
+    fn commit(time: u64, author: &str, files: &[&str], renames: &[(&str, &str)]) -> Commit {
+        Commit {
+            hash: String::new(),
+            time,
+            author: author.to_string(),
+            files: files.iter().map(|f| f.to_string()).collect(),
+            renames: renames
+                .iter()
+                .map(|(old, new)| (old.to_string(), new.to_string()))
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn test_count_cochanges() {
+        const DAY: u64 = 86_400;
+        let now = 1_000 * DAY;
+        // Most recent first
+        let commits = [
+            commit(now, "Ada <ada@x>", &["a.rs", "b.rs"], &[]),
+            commit(
+                now - DAY,
+                "Bob <bob@x>",
+                &["b.rs", "new.rs"],
+                &[("old.rs", "new.rs")],
+            ),
+            commit(now - 100 * DAY, "Ada <ada@x>", &["a.rs", "c.rs"], &[]),
+            commit(now - 100 * DAY, "Ada <ada@x>", &["a.rs", "old.rs"], &[]),
+            commit(
+                now,
+                "Ada <ada@x>",
+                &["a.rs", "d.rs", "e.rs", "f.rs", "g.rs"],
+                &[],
+            ),
+        ];
+        let options = CoChangeOptions {
+            depth: 10,
+            half_life: Some(Duration::from_secs(100 * DAY)),
+            ..Default::default()
+        };
+        let counts = count_cochanges(&commits, &options, now);
+        let a = &counts["a.rs"];
+        assert_eq!(a["b.rs"], 1.0);
+        // A hundred days old, so half as much
+        assert_eq!(a["c.rs"], 0.5);
+        // Counted under the file's name after the rename
+        assert_eq!(a["new.rs"], 0.5);
+        assert!(!a.contains_key("old.rs"));
+        // Shared among the four other files of the commit
+        assert_eq!(a["d.rs"], 0.25);
+
+        // Commits over the size limit don't count at all
+        let limited = CoChangeOptions {
+            max_commit_files: Some(4),
+            ..options.clone()
+        };
+        let counts = count_cochanges(&commits, &limited, now);
+        assert!(!counts["a.rs"].contains_key("d.rs"));
+        assert_eq!(counts["a.rs"]["b.rs"], 1.0);
+
+        let options = CoChangeOptions {
+            authors: vec!["BOB".to_string()],
+            ..options
+        };
+        let counts = count_cochanges(&commits, &options, now);
+        assert!(!counts.contains_key("a.rs"));
+        assert!(counts["b.rs"]["new.rs"] > 0.0);
+    }
+
+    #[test]
+    fn test_update_index_reads_only_new_commits() {
+        let root = std::env::temp_dir().join(format!("ctx-cochange-{}", uuid::Uuid::new_v4()));
+        let repo = root.join("repo");
+        std::fs::create_dir_all(&repo).unwrap();
+        let git = |args: &[&str]| {
+            let output = Command::new("git")
+                .arg("-C")
+                .arg(&repo)
+                .args(["-c", "user.name=Ada", "-c", "user.email=ada@example.com"])
+                .args(args)
+                .output()
+                .unwrap();
+            assert!(output.status.success(), "git {:?} failed", args);
+            String::from_utf8_lossy(&output.stdout).trim().to_string()
+        };
+        let commit = |files: &[&str], message: &str| {
+            for file in files {
+                std::fs::write(repo.join(file), message).unwrap();
+            }
+            git(&["add", "."]);
+            git(&["commit", "-qm", message]);
+        };
+        git(&["init", "-q"]);
+        commit(&["a.rs", "b.rs"], "first");
+        commit(&["a.rs", "c.rs"], "second");
+
+        let store = IndexStore::new(Some(root.join("index")), &repo);
+        let options = CoChangeOptions {
+            depth: 100,
+            ..Default::default()
+        };
+        let signal = GitCoChangeSignal::new(
+            repo.clone(),
+            options,
+            store.clone(),
+            Duration::from_secs(60),
         );
+        assert_eq!(signal.update_index(&repo).unwrap().len(), 2);
+
+        // Mark the stored commits, to tell them from commits read from git again
+        let mut index: CoChangeIndex = store.load(COCHANGE_FILE).unwrap();
+        for commit in &mut index.commits {
+            commit.author = "stored".to_string();
+        }
+        store.save(COCHANGE_FILE, &index).unwrap();
+
+        commit(&["b.rs", "c.rs"], "third");
+        let commits = signal.update_index(&repo).unwrap();
+        let authors: Vec<&str> = commits.iter().map(|c| c.author.as_str()).collect();
+        assert_eq!(authors, vec!["Ada <ada@example.com>", "stored", "stored"]);
+        assert_eq!(commits[0].files, vec!["b.rs", "c.rs"]);
+        let index: CoChangeIndex = store.load(COCHANGE_FILE).unwrap();
+        assert_eq!(index.head, git(&["rev-parse", "HEAD"]));
+        assert_eq!(index.commits, commits);
+
+        // Rewritten history: the old head isn't an ancestor, so everything is read again
+        git(&["commit", "--amend", "-qm", "third, reworded"]);
+        let commits = signal.update_index(&repo).unwrap();
+        assert_eq!(commits.len(), 3);
+        assert!(commits.iter().all(|c| c.author == "Ada <ada@example.com>"));
+        assert_eq!(commits[0].hash, git(&["rev-parse", "HEAD"]));
+
+        std::fs::remove_dir_all(&root).unwrap();
     }
 }
@@ -3,28 +3,48 @@
 use std::collections::HashMap;
 use std::path::{Path, PathBuf};
 use std::sync::RwLock;
-use std::time::Instant;
+use std::time::{Duration, Instant};
 
 use anyhow::Result;
 use async_trait::async_trait;
-use ignore::WalkBuilder;
 use tracing::debug;
 
 use super::Signal;
 use crate::cache::ImportGraphCache;
+use crate::index::{FileIndex, IMPORTS_FILE, IndexStore};
 use crate::parsers;
 
 /// Signal based on import/dependency relationships
 pub struct ImportSignal {
     #[allow(dead_code)]
     workspace: PathBuf,
+    store: IndexStore,
+    ttl: Duration,
     cache: RwLock<ImportGraphCache>,
 }
 
+/// Imports parsed from each source file of a workspace, as stored on disk. Imports are
+/// kept unresolved, since what they resolve to depends on the rest of the workspace.
+pub(crate) type ImportIndex = FileIndex<Vec<String>>;
+
+/// Bring the workspace's import index up to date
+pub(crate) async fn update_import_index(
+    store: &IndexStore,
+    workspace: &Path,
+) -> Result<ImportIndex> {
+    // Unparseable files are recorded too, so they aren't retried every run
+    ImportIndex::update(store, IMPORTS_FILE, workspace, |path, _| async move {
+        parsers::parse_imports(&path).await.unwrap_or_default()
+    })
+    .await
+}
+
 impl ImportSignal {
-    pub fn new(workspace: PathBuf) -> Self {
+    pub fn new(workspace: PathBuf, store: IndexStore, ttl: Duration) -> Self {
         Self {
             workspace,
+            store,
+            ttl,
             cache: RwLock::new(ImportGraphCache::new()),
         }
     }
@@ -33,45 +53,27 @@ impl ImportSignal {
     async fn build_import_graph(&self, workspace: &Path) -> Result<()> {
         debug!("Building import graph for {:?}", workspace);
 
+        let index = update_import_index(&self.store, workspace).await?;
         let mut imports_map: HashMap<PathBuf, Vec<PathBuf>> = HashMap::new();
         let mut imported_by_map: HashMap<PathBuf, Vec<PathBuf>> = HashMap::new();
 
-        let walker = WalkBuilder::new(workspace)
-            .hidden(true)
-            .git_ignore(true)
-            .build();
-
-        for entry in walker.filter_map(std::result::Result::ok) {
-            let path = entry.path();
-            if !path.is_file() {
-                continue;
-            }
-
-            let ext = path.extension().and_then(|e| e.to_str()).unwrap_or("");
-            if !parsers::is_supported_extension(ext) {
-                continue;
-            }
-
-            let Ok(raw_imports) = parsers::parse_imports(path).await else {
-                continue;
-            };
-
-            let mut resolved = Vec::new();
-            for import in raw_imports {
-                if let Some(resolved_path) = resolve_import(workspace, path, ext, &import) {
-                    resolved.push(resolved_path);
-                }
-            }
-
-            let path_buf = path.to_owned();
-            imports_map.insert(path_buf.clone(), resolved.clone());
-
-            for target in resolved {
+        for (relative, file) in index.files {
+            let path = workspace.join(relative);
+            let mut resolved: Vec<PathBuf> = file
+                .data
+                .iter()
+                .flat_map(|import| parsers::resolve_import(workspace, &path, import))
+                .collect();
+            resolved.sort();
+            resolved.dedup();
+
+            for target in &resolved {
                 imported_by_map
-                    .entry(target)
+                    .entry(target.clone())
                     .or_default()
-                    .push(path_buf.clone());
+                    .push(path.clone());
             }
+            imports_map.insert(path, resolved);
         }
 
         let mut cache = self.cache.write().unwrap();
@@ -93,9 +95,9 @@ impl ImportSignal {
         Ok(())
     }
 
-    fn ensure_cache(&self, workspace: &Path) -> bool {
+    fn cache_is_fresh(&self, workspace: &Path) -> bool {
         let cache = self.cache.read().unwrap();
-        cache.is_valid(&workspace.to_owned())
+        cache.is_valid(&workspace.to_owned(), self.ttl)
     }
 }
 
@@ -106,7 +108,7 @@ impl Signal for ImportSignal {
     }
 
     async fn score(&self, query: &Path, workspace: &Path) -> Result<Vec<(String, f64)>> {
-        if !self.ensure_cache(workspace) {
+        if !self.cache_is_fresh(workspace) {
             self.build_import_graph(workspace).await?;
         }
 
@@ -184,20 +186,3 @@ impl Signal for ImportSignal {
         cache.workspace = None;
     }
 }
-
-/// Resolve an import to a file path based on language
-fn resolve_import(
-    workspace: &Path,
-    source_file: &Path,
-    ext: &str,
-    import: &str,
-) -> Option<PathBuf> {
-    match ext {
-        "rs" => parsers::rust::resolve_import(workspace, source_file, import),
-        "ts" | "tsx" | "js" | "jsx" | "mts" | "mjs" => {
-            parsers::typescript::resolve_import(workspace, source_file, import)
-        }
-        "py" => parsers::python::resolve_import(workspace, source_file, import),
-        _ => None,
-    }
-}
@@ -0,0 +1,227 @@
+//! Lexical similarity signal - finds files using the same vocabulary as the query file,
+//! ranked with BM25 over the words of their identifiers
+
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+use std::sync::RwLock;
+use std::time::{Duration, Instant};
+
+use anyhow::Result;
+use async_trait::async_trait;
+use tracing::debug;
+
+use super::Signal;
+use crate::cache::{Cached, LexicalIndexCache};
+use crate::index::{FileIndex, IndexStore, TERMS_FILE};
+use crate::parsers::terms::extract_terms;
+
+/// Identifier words of each source file of a workspace and how often they occur, as
+/// stored on disk
+pub(crate) type TermIndex = FileIndex<BTreeMap<String, u32>>;
+
+/// BM25 term frequency saturation
+const K1: f64 = 1.2;
+
+/// BM25 document length normalization
+const B: f64 = 0.75;
+
+/// Terms in more than this share of files relate nearly everything, and are skipped
+const MAX_DOCUMENT_SHARE: f64 = 0.5;
+
+/// Only the most similar files are returned, since every file shares a word or two
+const MAX_RESULTS: usize = 20;
+
+/// Signal based on the words files' identifiers are made of
+pub struct LexicalSignal {
+    store: IndexStore,
+    ttl: Duration,
+    cache: RwLock<Cached<LexicalIndexCache>>,
+}
+
+impl LexicalSignal {
+    pub fn new(store: IndexStore, ttl: Duration) -> Self {
+        Self {
+            store,
+            ttl,
+            cache: RwLock::new(Cached::default()),
+        }
+    }
+
+    /// Build the inverted index from the workspace's source files
+    async fn build_term_index(&self, workspace: &Path) -> Result<()> {
+        debug!("Building term index for {:?}", workspace);
+
+        let index = TermIndex::update(&self.store, TERMS_FILE, workspace, |path, content| {
+            let terms = extract_terms(&path, &content).unwrap_or_default();
+            async move { terms }
+        })
+        .await?;
+
+        let mut cache = LexicalIndexCache::default();
+        let mut postings: HashMap<String, Vec<(PathBuf, u32)>> = HashMap::new();
+        let mut total_length = 0_u64;
+
+        for (relative, file) in index.files {
+            let path = workspace.join(relative);
+            let length: u32 = file.data.values().sum();
+            total_length += u64::from(length);
+            for (term, &count) in &file.data {
+                postings
+                    .entry(term.clone())
+                    .or_default()
+                    .push((path.clone(), count));
+            }
+            cache.lengths.insert(path.clone(), length);
+            cache.terms.insert(path, file.data);
+        }
+        for (term, files) in postings {
+            cache.postings.insert(term, files);
+        }
+        cache.average_length = total_length as f64 / cache.terms.len().max(1) as f64;
+
+        debug!("Built term index with {} terms", cache.postings.len());
+
+        *self.cache.write().unwrap() = Cached {
+            data: cache,
+            built_at: Some(Instant::now()),
+            workspace: Some(workspace.to_owned()),
+        };
+
+        Ok(())
+    }
+
+    fn cache_is_fresh(&self, workspace: &Path) -> bool {
+        let cache = self.cache.read().unwrap();
+        cache.is_valid(workspace, self.ttl)
+    }
+}
+
+#[async_trait]
+impl Signal for LexicalSignal {
+    fn name(&self) -> &'static str {
+        "lexical"
+    }
+
+    async fn score(&self, query: &Path, workspace: &Path) -> Result<Vec<(String, f64)>> {
+        if !self.cache_is_fresh(workspace) {
+            self.build_term_index(workspace).await?;
+        }
+
+        let cache = self.cache.read().unwrap();
+        let mut scores: Vec<(PathBuf, f64)> = score_bm25(&cache.data, query).into_iter().collect();
+        scores.sort_by(|a, b| b.1.total_cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+        scores.truncate(MAX_RESULTS);
+
+        let results = scores
+            .into_iter()
+            .filter(|(path, _)| path.exists())
+            .map(|(path, score)| (path.to_string_lossy().to_string(), score))
+            .collect();
+
+        Ok(results)
+    }
+
+    async fn warm_cache(&self, workspace: &Path) -> Result<()> {
+        self.build_term_index(workspace).await
+    }
+
+    fn clear_cache(&self) {
+        self.cache.write().unwrap().reset();
+    }
+}
+
+/// BM25 scores of every file sharing terms with `query`, taking the query file's terms
+/// as the search, each weighted by how often the query uses it. Scores are relative to the
+/// query file's own, so only a file using the query's words as much as it does scores 1,
+/// and files sharing a word or two stay low even when nothing shares more.
+fn score_bm25(cache: &LexicalIndexCache, query: &Path) -> HashMap<PathBuf, f64> {
+    let mut scores: HashMap<PathBuf, f64> = HashMap::new();
+    let Some(query_terms) = cache.terms.get(query) else {
+        return scores;
+    };
+    let files = cache.terms.len() as f64;
+    let saturate = |count: f64, norm: f64| count * (K1 + 1.0) / (count + K1 * norm);
+
+    for (term, &query_count) in query_terms.value() {
+        let Some(postings) = cache.postings.get(term) else {
+            continue;
+        };
+        let containing = postings.len() as f64;
+        if containing > 1.0 && containing > files * MAX_DOCUMENT_SHARE {
+            continue;
+        }
+        let idf = (1.0 + (files - containing + 0.5) / (containing + 0.5)).ln();
+        let weight = idf * saturate(f64::from(query_count), 1.0);
+
+        for (file, count) in postings.value() {
+            let length = cache.lengths.get(file).map_or(0, |l| *l) as f64;
+            let norm = 1.0 - B + B * length / cache.average_length.max(1.0);
+            *scores.entry(file.clone()).or_default() += weight * saturate(f64::from(*count), norm);
+        }
+    }
+
+    let Some(own) = scores.remove(query).filter(|own| *own > 0.0) else {
+        return HashMap::new();
+    };
+    for score in scores.values_mut() {
+        *score = (*score / own).min(1.0);
+    }
+    scores
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_score_bm25() {
+        let mut cache = LexicalIndexCache::default();
+        let files: [(&str, &[(&str, u32)]); 6] = [
+            (
+                "stripe.rs",
+                &[("charge", 4), ("refund", 2), ("stripe", 6), ("config", 1)],
+            ),
+            (
+                "paypal.rs",
+                &[("charge", 3), ("refund", 3), ("paypal", 5), ("config", 1)],
+            ),
+            ("render.rs", &[("render", 8), ("config", 2)]),
+            ("main.rs", &[("config", 1), ("charge", 1)]),
+            ("auth.rs", &[("login", 3), ("session", 2)]),
+            ("db.rs", &[("query", 5), ("pool", 2), ("config", 1)]),
+        ];
+        let mut total = 0;
+        for (file, terms) in files {
+            let path = PathBuf::from(file);
+            let terms: BTreeMap<String, u32> =
+                terms.iter().map(|(t, c)| (t.to_string(), *c)).collect();
+            for (term, &count) in &terms {
+                cache
+                    .postings
+                    .entry(term.clone())
+                    .or_default()
+                    .push((path.clone(), count));
+            }
+            let length = terms.values().sum();
+            total += length;
+            cache.lengths.insert(path.clone(), length);
+            cache.terms.insert(path, terms);
+        }
+        cache.average_length = f64::from(total) / 6.0;
+
+        let scores = score_bm25(&cache, Path::new("stripe.rs"));
+        // The parallel implementation ranks first
+        assert!(scores[Path::new("paypal.rs")] > scores[Path::new("main.rs")]);
+        // `config` is in most files, so it relates nothing
+        assert!(!scores.contains_key(Path::new("render.rs")));
+        assert!(!scores.contains_key(Path::new("auth.rs")));
+        assert!(!scores.contains_key(Path::new("stripe.rs")));
+        // Scores are relative to the query's own, so even the best match sharing two of its
+        // three words is far from 1, and one shared word stays under `min_score` once weighted
+        assert!(scores[Path::new("paypal.rs")] > 0.4 && scores[Path::new("paypal.rs")] < 0.5);
+        assert!(scores[Path::new("main.rs")] < 0.25);
+
+        // Nothing else uses `login` or `session`
+        assert!(score_bm25(&cache, Path::new("auth.rs")).is_empty());
+    }
+}
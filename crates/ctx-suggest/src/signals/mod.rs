@@ -2,6 +2,9 @@
 
 pub mod git_cochange;
 pub mod imports;
+pub mod lexical;
+pub mod symbols;
+pub mod test_pairing;
 
 use std::path::Path;
 
@@ -0,0 +1,210 @@
+//! Symbol reference signal - finds files that use what the query file defines
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::RwLock;
+use std::time::{Duration, Instant};
+
+use anyhow::Result;
+use async_trait::async_trait;
+use tracing::debug;
+
+use super::Signal;
+use crate::cache::{Cached, SymbolIndexCache};
+use crate::index::{FileIndex, IndexStore, SYMBOLS_FILE};
+use crate::parsers::symbols::{Symbols, extract_symbols};
+
+/// Symbols defined and referenced by each source file of a workspace, as stored on disk
+pub(crate) type SymbolIndex = FileIndex<Symbols>;
+
+/// Share of a symbol's weight given to files defining what the query references, as
+/// opposed to files referencing what the query defines
+const DEFINER_SHARE: f64 = 0.5;
+
+/// Signal based on symbols defined in one file and referenced in another
+pub struct SymbolSignal {
+    store: IndexStore,
+    ttl: Duration,
+    cache: RwLock<Cached<SymbolIndexCache>>,
+}
+
+impl SymbolSignal {
+    pub fn new(store: IndexStore, ttl: Duration) -> Self {
+        Self {
+            store,
+            ttl,
+            cache: RwLock::new(Cached::default()),
+        }
+    }
+
+    /// Build the symbol tables from the workspace's source files
+    async fn build_symbol_index(&self, workspace: &Path) -> Result<()> {
+        debug!("Building symbol index for {:?}", workspace);
+
+        let index = SymbolIndex::update(&self.store, SYMBOLS_FILE, workspace, |path, content| {
+            let symbols = extract_symbols(&path, &content).unwrap_or_default();
+            async move { symbols }
+        })
+        .await?;
+
+        let cache = SymbolIndexCache::default();
+        let mut definers: HashMap<String, Vec<PathBuf>> = HashMap::new();
+        let mut referencers: HashMap<String, Vec<PathBuf>> = HashMap::new();
+
+        for (relative, file) in index.files {
+            let path = workspace.join(relative);
+            for symbol in &file.data.defines {
+                definers
+                    .entry(symbol.clone())
+                    .or_default()
+                    .push(path.clone());
+            }
+            for symbol in &file.data.references {
+                referencers
+                    .entry(symbol.clone())
+                    .or_default()
+                    .push(path.clone());
+            }
+            cache.defines.insert(path.clone(), file.data.defines);
+            cache.references.insert(path, file.data.references);
+        }
+        for (symbol, files) in definers {
+            cache.definers.insert(symbol, files);
+        }
+        for (symbol, files) in referencers {
+            cache.referencers.insert(symbol, files);
+        }
+
+        debug!("Built symbol index with {} symbols", cache.definers.len());
+
+        *self.cache.write().unwrap() = Cached {
+            data: cache,
+            built_at: Some(Instant::now()),
+            workspace: Some(workspace.to_owned()),
+        };
+
+        Ok(())
+    }
+
+    fn cache_is_fresh(&self, workspace: &Path) -> bool {
+        let cache = self.cache.read().unwrap();
+        cache.is_valid(workspace, self.ttl)
+    }
+}
+
+#[async_trait]
+impl Signal for SymbolSignal {
+    fn name(&self) -> &'static str {
+        "symbol"
+    }
+
+    async fn score(&self, query: &Path, workspace: &Path) -> Result<Vec<(String, f64)>> {
+        if !self.cache_is_fresh(workspace) {
+            self.build_symbol_index(workspace).await?;
+        }
+
+        let cache = self.cache.read().unwrap();
+        let scores = score_symbols(&cache.data, query);
+
+        let max_score = scores.values().copied().fold(0.0_f64, f64::max);
+        let results = scores
+            .into_iter()
+            .filter(|(path, _)| path.exists())
+            .map(|(path, score)| (path.to_string_lossy().to_string(), score / max_score))
+            .collect();
+
+        Ok(results)
+    }
+
+    async fn warm_cache(&self, workspace: &Path) -> Result<()> {
+        self.build_symbol_index(workspace).await
+    }
+
+    fn clear_cache(&self) {
+        self.cache.write().unwrap().reset();
+    }
+}
+
+/// Raw scores for files sharing symbols with `query`. Each symbol counts for less the
+/// more files define it, so `new` or `Error` relate files far less than `AuthToken`.
+fn score_symbols(cache: &SymbolIndexCache, query: &Path) -> HashMap<PathBuf, f64> {
+    let files = cache.defines.len().max(1) as f64;
+    let weight = |definers: usize| (1.0 + files / definers.max(1) as f64).ln();
+    let mut scores: HashMap<PathBuf, f64> = HashMap::new();
+
+    // Files referencing what the query defines
+    if let Some(defines) = cache.defines.get(query) {
+        for symbol in defines.value() {
+            let definers = cache.definers.get(symbol).map_or(1, |d| d.len());
+            let Some(referencers) = cache.referencers.get(symbol) else {
+                continue;
+            };
+            for file in referencers.value() {
+                *scores.entry(file.clone()).or_default() += weight(definers);
+            }
+        }
+    }
+
+    // Files defining what the query references, splitting the weight between them
+    if let Some(references) = cache.references.get(query) {
+        for symbol in references.value() {
+            let Some(definers) = cache.definers.get(symbol) else {
+                continue;
+            };
+            let share = DEFINER_SHARE * weight(definers.len()) / definers.len() as f64;
+            for file in definers.value() {
+                *scores.entry(file.clone()).or_default() += share;
+            }
+        }
+    }
+
+    scores.remove(query);
+    scores
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn strings(names: &[&str]) -> Vec<String> {
+        names.iter().map(|n| n.to_string()).collect()
+    }
+
+    #[test]
+    fn test_score_symbols() {
+        let cache = SymbolIndexCache::default();
+        let files = [
+            ("token.rs", &["AuthToken", "new"][..], &["Error"][..]),
+            ("login.rs", &["login", "new"], &["AuthToken", "Error"]),
+            ("error.rs", &["Error", "new"], &[]),
+            ("other.rs", &["render", "new"], &["Error"]),
+        ];
+        for (file, defines, references) in files {
+            let path = PathBuf::from(file);
+            for symbol in defines {
+                cache
+                    .definers
+                    .entry(symbol.to_string())
+                    .or_default()
+                    .push(path.clone());
+            }
+            for symbol in references {
+                cache
+                    .referencers
+                    .entry(symbol.to_string())
+                    .or_default()
+                    .push(path.clone());
+            }
+            cache.defines.insert(path.clone(), strings(defines));
+            cache.references.insert(path, strings(references));
+        }
+
+        let scores = score_symbols(&cache, Path::new("token.rs"));
+        // Uses `AuthToken`, defined only in the query
+        assert!(scores[Path::new("login.rs")] > scores[Path::new("error.rs")]);
+        // Defines `Error`, which the query uses
+        assert!(scores[Path::new("error.rs")] > 0.0);
+        assert!(!scores.contains_key(Path::new("other.rs")));
+        assert!(!scores.contains_key(Path::new("token.rs")));
+    }
+}
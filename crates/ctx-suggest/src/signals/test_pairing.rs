@@ -0,0 +1,423 @@
+//! Test pairing signal - pairs source files with their tests, and tests with the code
+//! they exercise
+
+use std::collections::{HashMap, HashSet};
+use std::path::{Component, Path, PathBuf};
+use std::sync::RwLock;
+use std::time::{Duration, Instant};
+
+use anyhow::Result;
+use async_trait::async_trait;
+use tracing::debug;
+
+use super::Signal;
+use super::imports::update_import_index;
+use crate::cache::{Cached, TestFile, TestPairingCache};
+use crate::index::IndexStore;
+
+/// Test file name conventions per family of extensions. `{stem}` stands for the name of
+/// the file under test and `{ext}` for the test's own extension.
+const CONVENTIONS: &[(&[&str], &[&str])] = &[
+    (
+        &["rs"],
+        &[
+            "{stem}_test.{ext}",
+            "{stem}_tests.{ext}",
+            "test_{stem}.{ext}",
+        ],
+    ),
+    (
+        &["ts", "tsx", "js", "jsx", "mts", "mjs", "cts", "cjs"],
+        &["{stem}.test.{ext}", "{stem}.spec.{ext}"],
+    ),
+    (&["py"], &["test_{stem}.{ext}", "{stem}_test.{ext}"]),
+    (&["go"], &["{stem}_test.{ext}"]),
+    (
+        &["java", "kt", "kts"],
+        &[
+            "{stem}Test.{ext}",
+            "{stem}Tests.{ext}",
+            "{stem}IT.{ext}",
+            "Test{stem}.{ext}",
+        ],
+    ),
+    (&["cs"], &["{stem}Tests.{ext}", "{stem}Test.{ext}"]),
+    (
+        &["rb"],
+        &[
+            "{stem}_spec.{ext}",
+            "{stem}_test.{ext}",
+            "test_{stem}.{ext}",
+        ],
+    ),
+    (&["php"], &["{stem}Test.{ext}"]),
+    (
+        &["c", "h", "cc", "cpp", "cxx", "hh", "hpp", "hxx"],
+        &[
+            "test_{stem}.{ext}",
+            "{stem}_test.{ext}",
+            "{stem}_unittest.{ext}",
+        ],
+    ),
+];
+
+/// Directories whose files are tests whatever they're called, as in `tests/auth.rs`
+const TEST_DIRS: &[&str] = &["tests", "test", "spec", "specs", "__tests__", "testing"];
+
+/// Files named after the directory they're in rather than after themselves
+const MODULE_FILES: &[&str] = &["mod", "lib", "index", "__init__"];
+
+/// Directories that mirror each other between sources and tests, and so don't make a
+/// test any further from its source (`src/main/java` and `src/test/java`)
+const LAYOUT_DIRS: &[&str] = &["src", "lib", "main", "app"];
+
+/// Score of a test named after a file but not importing it
+const NAMED_SCORE: f64 = 0.8;
+
+/// Score of a test importing a file it isn't named after
+const IMPORTED_SCORE: f64 = 0.5;
+
+/// Names shorter than this are too common (`db`, `api`) to pair on imports alone
+const MIN_IMPORTED_LEN: usize = 4;
+
+/// Signal based on test file naming conventions and what tests import
+pub struct TestPairingSignal {
+    store: IndexStore,
+    ttl: Duration,
+    /// Test file name patterns tried for every language, on top of [`CONVENTIONS`]
+    patterns: Vec<String>,
+    cache: RwLock<Cached<TestPairingCache>>,
+}
+
+impl TestPairingSignal {
+    pub fn new(store: IndexStore, ttl: Duration, patterns: Vec<String>) -> Self {
+        Self {
+            store,
+            ttl,
+            patterns,
+            cache: RwLock::new(Cached::default()),
+        }
+    }
+
+    /// Sort the workspace's source files into tests and the files they may cover
+    async fn build_test_pairs(&self, workspace: &Path) -> Result<()> {
+        debug!("Building test pairs for {:?}", workspace);
+
+        let index = update_import_index(&self.store, workspace).await?;
+
+        let cache = TestPairingCache::default();
+        let mut sources: HashMap<String, Vec<PathBuf>> = HashMap::new();
+
+        for (relative, file) in index.files {
+            let path = workspace.join(&relative);
+            match test_subject(&relative, &self.patterns) {
+                Some(subject) => {
+                    let imports = file.data.iter().flat_map(|import| words(import)).collect();
+                    cache.tests.insert(path, TestFile { subject, imports });
+                }
+                None => {
+                    if let Some(stem) = source_stem(&path) {
+                        sources.entry(stem).or_default().push(path);
+                    }
+                }
+            }
+        }
+        for (stem, files) in sources {
+            cache.sources.insert(stem, files);
+        }
+
+        debug!("Found {} test files", cache.tests.len());
+
+        *self.cache.write().unwrap() = Cached {
+            data: cache,
+            built_at: Some(Instant::now()),
+            workspace: Some(workspace.to_owned()),
+        };
+
+        Ok(())
+    }
+
+    fn cache_is_fresh(&self, workspace: &Path) -> bool {
+        let cache = self.cache.read().unwrap();
+        cache.is_valid(workspace, self.ttl)
+    }
+}
+
+#[async_trait]
+impl Signal for TestPairingSignal {
+    fn name(&self) -> &'static str {
+        "test_pairing"
+    }
+
+    async fn score(&self, query: &Path, workspace: &Path) -> Result<Vec<(String, f64)>> {
+        if !self.cache_is_fresh(workspace) {
+            self.build_test_pairs(workspace).await?;
+        }
+
+        let cache = self.cache.read().unwrap();
+        let results = score_pairs(&cache.data, query)
+            .into_iter()
+            .filter(|(path, _)| path.exists())
+            .map(|(path, score)| (path.to_string_lossy().to_string(), score))
+            .collect();
+
+        Ok(results)
+    }
+
+    async fn warm_cache(&self, workspace: &Path) -> Result<()> {
+        self.build_test_pairs(workspace).await
+    }
+
+    fn clear_cache(&self) {
+        self.cache.write().unwrap().reset();
+    }
+}
+
+/// Scores for the tests of `query`, or for the files it tests when it's a test itself
+fn score_pairs(cache: &TestPairingCache, query: &Path) -> HashMap<PathBuf, f64> {
+    let mut scores = HashMap::new();
+
+    if let Some(test) = cache.tests.get(query) {
+        // From a test to the code under test
+        let mut candidates: Vec<(PathBuf, String)> = Vec::new();
+        if let Some(subject) = &test.subject
+            && let Some(files) = cache.sources.get(subject)
+        {
+            candidates.extend(files.iter().map(|file| (file.clone(), subject.clone())));
+        }
+        for word in &test.imports {
+            if test.subject.as_ref() != Some(word)
+                && let Some(files) = cache.sources.get(word)
+            {
+                candidates.extend(files.iter().map(|file| (file.clone(), word.clone())));
+            }
+        }
+        for (file, stem) in candidates {
+            if let Some(score) = pair_score(query, test.value(), &file, &stem) {
+                scores.insert(file, score);
+            }
+        }
+    } else if let Some(stem) = source_stem(query) {
+        // From a file to its tests
+        for test in cache.tests.iter() {
+            if let Some(score) = pair_score(test.key(), test.value(), query, &stem) {
+                scores.insert(test.key().clone(), score);
+            }
+        }
+    }
+
+    scores
+}
+
+/// How likely the test at `test_path` exercises `source`, whose stem is `stem`
+fn pair_score(test_path: &Path, test: &TestFile, source: &Path, stem: &str) -> Option<f64> {
+    if family(test_path)? != family(source)? {
+        return None;
+    }
+    let named = test.subject.as_deref() == Some(stem);
+    let imported = test.imports.contains(stem);
+    let score = match (named, imported) {
+        (true, true) => 1.0,
+        (true, false) => NAMED_SCORE,
+        (false, true) if stem.chars().count() >= MIN_IMPORTED_LEN => IMPORTED_SCORE,
+        _ => return None,
+    };
+    Some(score * proximity(test_path, source))
+}
+
+/// For a test file, the normalized name of the file it tests when its name says so.
+/// `None` if `path` isn't a test, `Some(None)` if it's a test named after nothing in
+/// particular, like `tests/integration.rs`.
+fn test_subject(path: &Path, extra_patterns: &[String]) -> Option<Option<String>> {
+    let name = path.file_name()?.to_str()?;
+    let ext = path.extension()?.to_str()?;
+
+    let conventions = CONVENTIONS
+        .iter()
+        .find(|(exts, _)| exts.contains(&ext))
+        .map_or(&[][..], |(_, patterns)| *patterns);
+    let patterns = conventions
+        .iter()
+        .copied()
+        .chain(extra_patterns.iter().map(String::as_str));
+    for pattern in patterns {
+        if let Some(stem) = match_pattern(pattern, name, ext) {
+            return Some(Some(normalize(stem)));
+        }
+    }
+
+    let stem = name.strip_suffix(ext)?.strip_suffix('.')?;
+    // Rust's `auth/tests.rs`, the tests of the `auth` module
+    if matches!(stem, "tests" | "test") {
+        return Some(parent_name(path).map(normalize));
+    }
+    let in_test_dir = path.parent().is_some_and(|dir| {
+        dir.components()
+            .any(|c| matches!(c, Component::Normal(d) if TEST_DIRS.iter().any(|t| d == *t)))
+    });
+    if !in_test_dir {
+        return None;
+    }
+    if MODULE_FILES.contains(&stem) {
+        return Some(None);
+    }
+    Some(Some(normalize(stem)))
+}
+
+/// The `{stem}` of `name` when it matches `pattern`
+fn match_pattern<'a>(pattern: &str, name: &'a str, ext: &str) -> Option<&'a str> {
+    let pattern = pattern.replace("{ext}", ext);
+    let (prefix, suffix) = pattern.split_once("{stem}")?;
+    let stem = name.strip_prefix(prefix)?.strip_suffix(suffix)?;
+    (!stem.is_empty()).then_some(stem)
+}
+
+/// Normalized name of a source file, taken from its directory for `mod.rs` and the like
+fn source_stem(path: &Path) -> Option<String> {
+    let name = path.file_name()?.to_str()?;
+    let ext = path.extension()?.to_str()?;
+    let stem = name.strip_suffix(ext)?.strip_suffix('.')?;
+    if MODULE_FILES.contains(&stem) {
+        return parent_name(path).map(normalize);
+    }
+    Some(normalize(stem))
+}
+
+fn parent_name(path: &Path) -> Option<&str> {
+    path.parent()?.file_name()?.to_str()
+}
+
+/// Index of the convention family of the file's extension, so `auth.py` isn't paired
+/// with `auth_test.go`
+fn family(path: &Path) -> Option<usize> {
+    let ext = path.extension()?.to_str()?;
+    CONVENTIONS.iter().position(|(exts, _)| exts.contains(&ext))
+}
+
+/// Names compared case- and separator-insensitively, so `UserService` and
+/// `user_service` are the same
+fn normalize(name: &str) -> String {
+    name.chars()
+        .filter(|c| !matches!(c, '_' | '-' | '.'))
+        .flat_map(char::to_lowercase)
+        .collect()
+}
+
+/// Normalized words of an import such as `crate::auth::Token` or `../src/auth`
+fn words(import: &str) -> HashSet<String> {
+    import
+        .split(|c: char| !(c.is_alphanumeric() || c == '_' || c == '-'))
+        .filter(|word| !word.is_empty())
+        .map(normalize)
+        .collect()
+}
+
+/// 1.0 for a test in the same or a mirrored directory, less the further apart they are
+fn proximity(test: &Path, source: &Path) -> f64 {
+    let dirs = |path: &Path| -> Vec<String> {
+        path.parent()
+            .into_iter()
+            .flat_map(Path::components)
+            .filter_map(|c| match c {
+                Component::Normal(dir) => dir.to_str(),
+                _ => None,
+            })
+            .filter(|dir| !TEST_DIRS.contains(dir) && !LAYOUT_DIRS.contains(dir))
+            .map(str::to_owned)
+            .collect()
+    };
+    let (test, source) = (dirs(test), dirs(source));
+    let common = test.iter().zip(&source).take_while(|(a, b)| a == b).count();
+    let distance = test.len() + source.len() - 2 * common;
+    0.5 + 0.5 / (1.0 + distance as f64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn subject(path: &str) -> Option<Option<String>> {
+        test_subject(Path::new(path), &[])
+    }
+
+    #[test]
+    fn test_test_subject() {
+        let named = |s: &str| Some(Some(s.to_string()));
+        assert_eq!(subject("tests/auth_test.rs"), named("auth"));
+        assert_eq!(subject("tests/auth.rs"), named("auth"));
+        assert_eq!(subject("src/auth/tests.rs"), named("auth"));
+        assert_eq!(subject("src/auth.test.ts"), named("auth"));
+        assert_eq!(subject("src/__tests__/auth.ts"), named("auth"));
+        assert_eq!(subject("test_auth.py"), named("auth"));
+        assert_eq!(subject("auth_test.go"), named("auth"));
+        assert_eq!(
+            subject("src/test/java/com/acme/UserServiceTest.java"),
+            named("userservice")
+        );
+        assert_eq!(subject("spec/models/user_spec.rb"), named("user"));
+        assert_eq!(subject("tests/mod.rs"), Some(None));
+
+        assert_eq!(subject("src/auth.rs"), None);
+        assert_eq!(subject("src/Test.java"), None);
+        assert_eq!(subject("src/auth.integration.ts"), None);
+        assert_eq!(
+            test_subject(
+                Path::new("src/auth.integration.ts"),
+                &["{stem}.integration.{ext}".to_string()]
+            ),
+            named("auth")
+        );
+    }
+
+    #[test]
+    fn test_score_pairs() {
+        let cache = TestPairingCache::default();
+        let test = |subject: Option<&str>, imports: &[&str]| TestFile {
+            subject: subject.map(str::to_string),
+            imports: imports.iter().map(|i| i.to_string()).collect(),
+        };
+        cache.tests.insert(
+            PathBuf::from("/ws/tests/auth_test.rs"),
+            test(Some("auth"), &["crate", "auth"]),
+        );
+        cache.tests.insert(
+            PathBuf::from("/ws/tests/login_flow.rs"),
+            test(Some("loginflow"), &["crate", "auth", "session"]),
+        );
+        cache
+            .tests
+            .insert(PathBuf::from("/ws/auth_test.go"), test(Some("auth"), &[]));
+        for source in ["/ws/src/auth.rs", "/ws/src/session/mod.rs"] {
+            let path = PathBuf::from(source);
+            cache
+                .sources
+                .entry(source_stem(&path).unwrap())
+                .or_default()
+                .push(path);
+        }
+
+        let scores = score_pairs(&cache, Path::new("/ws/src/auth.rs"));
+        assert_eq!(scores[Path::new("/ws/tests/auth_test.rs")], 1.0);
+        assert_eq!(scores[Path::new("/ws/tests/login_flow.rs")], IMPORTED_SCORE);
+        assert!(!scores.contains_key(Path::new("/ws/auth_test.go")));
+
+        // And back from a test to what it exercises
+        let scores = score_pairs(&cache, Path::new("/ws/tests/login_flow.rs"));
+        assert!(scores.contains_key(Path::new("/ws/src/auth.rs")));
+        assert!(scores.contains_key(Path::new("/ws/src/session/mod.rs")));
+    }
+
+    #[test]
+    fn test_proximity() {
+        let close = proximity(
+            Path::new("src/test/java/com/acme/UserServiceTest.java"),
+            Path::new("src/main/java/com/acme/UserService.java"),
+        );
+        assert_eq!(close, 1.0);
+        let far = proximity(
+            Path::new("packages/web/src/auth.test.ts"),
+            Path::new("packages/api/src/auth.ts"),
+        );
+        assert!(far < close);
+    }
+}
@@ -0,0 +1,7 @@
+#pragma once
+
+#include "shop/types.h"
+
+struct cart {
+    item_id items[16];
+};
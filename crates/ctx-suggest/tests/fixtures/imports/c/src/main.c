@@ -0,0 +1,14 @@
+#include <stdio.h>
+#include <shop/cart.h>
+
+#include "util.h"
+
+#ifdef WITH_ZLIB
+#include "zlib.h"
+#endif
+
+int main(void) {
+    struct cart cart = {0};
+    printf("%d\n", util_count(&cart));
+    return 0;
+}
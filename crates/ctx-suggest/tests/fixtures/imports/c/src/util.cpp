@@ -0,0 +1,8 @@
+#include "util.h"
+#include <vector>
+
+namespace {
+std::vector<int> scratch;
+}
+
+int util_count(const struct cart *) { return static_cast<int>(scratch.size()); }
@@ -0,0 +1,4 @@
+#pragma once
+
+struct cart;
+int util_count(const struct cart *cart);
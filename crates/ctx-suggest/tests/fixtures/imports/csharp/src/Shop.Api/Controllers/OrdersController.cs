@@ -0,0 +1,11 @@
+using System;
+using Shop.Core.Orders;
+using Shop.Web.Models;
+using static Shop.Core.Pricing.Totals;
+
+namespace Shop.Web.Controllers;
+
+public class OrdersController
+{
+    public OrderDto Get(Order order) => new(Sum(order));
+}
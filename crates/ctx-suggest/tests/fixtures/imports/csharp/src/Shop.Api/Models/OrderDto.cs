@@ -0,0 +1,3 @@
+namespace Shop.Web.Models;
+
+public record OrderDto(decimal Total);
@@ -0,0 +1,8 @@
+using Shop.Core.Pricing;
+
+namespace Shop.Core.Orders;
+
+public class Order
+{
+    public decimal Total => Totals.Sum(this);
+}
@@ -0,0 +1,3 @@
+namespace Shop.Core.Orders;
+
+public record OrderLine(string Sku, int Quantity);
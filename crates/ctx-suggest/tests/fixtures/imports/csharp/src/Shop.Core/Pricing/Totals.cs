@@ -0,0 +1,7 @@
+namespace Shop.Core.Pricing
+{
+    public static class Totals
+    {
+        public static decimal Sum(object order) => 0m;
+    }
+}
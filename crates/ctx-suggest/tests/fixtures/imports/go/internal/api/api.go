@@ -0,0 +1,5 @@
+package api
+
+import "example.com/shop/internal/db"
+
+var Name = db.Table
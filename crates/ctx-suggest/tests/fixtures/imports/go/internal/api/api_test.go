@@ -0,0 +1,13 @@
+package api_test
+
+import (
+	"testing"
+
+	"example.com/shop/internal/api"
+)
+
+func TestRoutes(t *testing.T) {
+	if len(api.Routes()) == 0 {
+		t.Fatal("no routes")
+	}
+}
@@ -0,0 +1,5 @@
+package api
+
+func Routes() []string {
+	return []string{"/orders"}
+}
@@ -0,0 +1,3 @@
+package db
+
+const Table = "orders"
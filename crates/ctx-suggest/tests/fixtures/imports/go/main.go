@@ -0,0 +1,13 @@
+package main
+
+import (
+	"fmt"
+
+	"example.com/lib/strutil"
+	api "example.com/shop/internal/api"
+	_ "example.com/shop/internal/db"
+)
+
+func main() {
+	fmt.Println(strutil.Title(api.Name))
+}
@@ -0,0 +1,7 @@
+package strutil
+
+import "strings"
+
+func Title(s string) string {
+	return strings.ToUpper(s)
+}
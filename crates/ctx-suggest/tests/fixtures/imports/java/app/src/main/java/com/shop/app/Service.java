@@ -0,0 +1,10 @@
+package com.shop.app;
+
+import com.shop.model.*;
+import static com.shop.util.Strings.trim;
+
+public class Service {
+    public String describe(Order order, Item item) {
+        return trim(order.label());
+    }
+}
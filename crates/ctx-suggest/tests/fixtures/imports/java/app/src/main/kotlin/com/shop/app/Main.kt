@@ -0,0 +1,10 @@
+@file:JvmName("Main")
+
+package com.shop.app
+
+import com.shop.model.Order
+import kotlinx.coroutines.runBlocking
+
+fun main() = runBlocking {
+    println(Service().describe(Order(), com.shop.model.Item()))
+}
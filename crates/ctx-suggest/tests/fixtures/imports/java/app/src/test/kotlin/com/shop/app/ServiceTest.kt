@@ -0,0 +1,11 @@
+package com.shop.app
+
+import com.shop.app.Service
+import kotlin.test.Test
+
+class ServiceTest {
+    @Test
+    fun describes() {
+        Service()
+    }
+}
@@ -0,0 +1,3 @@
+package com.shop.model;
+
+public class Item {}
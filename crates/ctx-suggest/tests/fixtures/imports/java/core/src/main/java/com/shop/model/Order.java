@@ -0,0 +1,12 @@
+package com.shop.model;
+
+import java.util.List;
+import com.shop.util.Strings;
+
+public class Order {
+    public List<Item> items;
+
+    public String label() {
+        return Strings.trim("order");
+    }
+}
@@ -0,0 +1,7 @@
+package com.shop.util;
+
+public final class Strings {
+    public static String trim(String s) {
+        return s.trim();
+    }
+}
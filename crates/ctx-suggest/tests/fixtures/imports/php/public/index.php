@@ -0,0 +1,4 @@
+<?php
+
+require __DIR__ . '/../vendor/autoload.php';
+$config = include 'config.php';
@@ -0,0 +1,19 @@
+<?php
+
+namespace App\Http;
+
+use App\Models\Order;
+use App\Services\{Billing, Mail\Mailer as M};
+use Psr\Log\LoggerInterface;
+
+require_once __DIR__ . '/../helpers.php';
+
+class OrderController
+{
+    public function __construct(private Billing $billing, private M $mailer, private LoggerInterface $log) {}
+
+    public function show(Order $order): string
+    {
+        return format_order($order);
+    }
+}
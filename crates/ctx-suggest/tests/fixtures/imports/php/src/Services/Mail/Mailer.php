@@ -0,0 +1,5 @@
+<?php
+
+namespace App\Services\Mail;
+
+class Mailer {}
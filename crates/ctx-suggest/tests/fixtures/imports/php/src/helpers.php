@@ -0,0 +1,6 @@
+<?php
+
+function format_order(object $order): string
+{
+    return get_class($order);
+}
@@ -0,0 +1,14 @@
+<?php
+
+namespace Tests;
+
+use App\Models\Order;
+use Tests\Support\Factory;
+
+class OrderTest
+{
+    public function testCreate(): void
+    {
+        Factory::make(Order::class);
+    }
+}
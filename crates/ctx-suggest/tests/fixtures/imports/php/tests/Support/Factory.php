@@ -0,0 +1,11 @@
+<?php
+
+namespace Tests\Support;
+
+class Factory
+{
+    public static function make(string $class): object
+    {
+        return new $class();
+    }
+}
@@ -0,0 +1,14 @@
+import os
+import app.models as models
+from . import utils
+from .models import (
+    User,
+    Group,
+)
+from app.services import billing
+
+
+def run():
+    from .services.billing import charge
+
+    return charge(User(), Group(), models, utils, billing, os.environ)
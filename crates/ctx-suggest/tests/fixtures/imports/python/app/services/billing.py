@@ -0,0 +1,5 @@
+from ..models import User
+
+
+def charge(user: User, *args):
+    return user, args
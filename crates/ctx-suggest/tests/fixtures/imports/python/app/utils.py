@@ -0,0 +1,10 @@
+try:
+    import ujson as json
+except ImportError:
+    import json
+
+from app import models
+
+
+def dump(value):
+    return json.dumps(value), models
@@ -0,0 +1,7 @@
+require 'json'
+require 'shop/cart'
+require_relative 'shop/version'
+
+module Shop
+  autoload :Billing, 'shop/billing'
+end
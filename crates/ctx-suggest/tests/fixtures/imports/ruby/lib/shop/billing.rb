@@ -0,0 +1,4 @@
+module Shop
+  module Billing
+  end
+end
@@ -0,0 +1,7 @@
+require_relative 'item'
+
+module Shop
+  class Cart
+    def add(item) = (@items ||= []) << item
+  end
+end
@@ -0,0 +1,3 @@
+module Shop
+  Item = Struct.new(:sku, :price)
+end
@@ -0,0 +1,6 @@
+require 'shop'
+require_relative '../lib/shop/cart'
+
+RSpec.describe Shop::Cart do
+  it('adds items') { expect(described_class.new.add(1)).to eq([1]) }
+end
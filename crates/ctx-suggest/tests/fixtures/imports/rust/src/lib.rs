@@ -0,0 +1,29 @@
+//! Fixture crate: the ways a Rust file can depend on another module
+
+pub mod config;
+mod net;
+
+#[path = "generated/schema.rs"]
+mod schema;
+
+pub use crate::config::{load as load_config, Config};
+use crate::net::{
+    client::Client,
+    server::{self, Server},
+};
+
+pub fn start() -> Server {
+    use self::schema::VERSION;
+    server::bind(Client::new(load_config()), VERSION)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::config::Config;
+    use super::*;
+
+    #[test]
+    fn starts() {
+        let _ = (start(), Config);
+    }
+}
@@ -0,0 +1,9 @@
+use super::super::config::Config;
+
+pub struct Client(Config);
+
+impl Client {
+    pub fn new(config: Config) -> Self {
+        Self(config)
+    }
+}
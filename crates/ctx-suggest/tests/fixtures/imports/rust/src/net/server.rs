@@ -0,0 +1,19 @@
+mod tls;
+
+use crate::{
+    config::Config,
+    net::client::Client, // reached through the parent module
+};
+
+pub struct Server {
+    pub client: Client,
+    pub version: u32,
+}
+
+pub fn bind(client: Client, version: u32) -> Server {
+    tls::wrap(Server { client, version })
+}
+
+pub fn config() -> Config {
+    Config
+}
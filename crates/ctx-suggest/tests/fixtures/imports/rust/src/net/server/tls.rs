@@ -0,0 +1,5 @@
+use super::Server;
+
+pub fn wrap(server: Server) -> Server {
+    server
+}
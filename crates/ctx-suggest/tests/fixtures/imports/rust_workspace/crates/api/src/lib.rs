@@ -0,0 +1,9 @@
+use serde::Serialize;
+use shop_core::util::slug;
+use shop_core::{LineItem, Money, Order};
+use store::Db;
+
+pub fn handle(db: &Db, order: Order) -> Vec<LineItem> {
+    let _ = (db, slug("x"), Money(0));
+    order.items
+}
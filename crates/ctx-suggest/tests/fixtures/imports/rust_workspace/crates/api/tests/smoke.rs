@@ -0,0 +1,6 @@
+use shop_api::handle;
+
+#[test]
+fn smoke() {
+    let _ = handle;
+}
@@ -0,0 +1,5 @@
+use shop_core::util;
+
+pub fn run(order: shop_core::Order) {
+    let _ = (order, util::slug("run"));
+}
@@ -0,0 +1,8 @@
+mod commands;
+
+use commands::run;
+use shop_core::Order;
+
+fn main() {
+    run(Order { items: vec![] });
+}
@@ -0,0 +1,6 @@
+mod model;
+mod money;
+pub mod util;
+
+pub use model::{Item as LineItem, Order};
+pub use money::*;
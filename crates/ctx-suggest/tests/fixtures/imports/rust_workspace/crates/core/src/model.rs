@@ -0,0 +1,9 @@
+use crate::money::Money;
+
+pub struct Item {
+    pub price: Money,
+}
+
+pub struct Order {
+    pub items: Vec<Item>,
+}
@@ -0,0 +1 @@
+pub struct Money(pub i64);
@@ -0,0 +1,15 @@
+import {
+  Button,
+  Card,
+} from './components/button';
+// import { Config } from './config';
+
+const legacy = require('./legacy');
+
+export function App() {
+  return (
+    <Card>
+      <Button label="import x from './types'" onClick={legacy.run} />
+    </Card>
+  );
+}
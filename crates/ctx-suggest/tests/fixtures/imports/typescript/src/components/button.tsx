@@ -0,0 +1,5 @@
+export const Button = (props: { label: string; onClick: () => void }) => (
+  <button onClick={props.onClick}>{props.label}</button>
+);
+
+export const Card = (props: { children: unknown }) => <div>{props.children}</div>;
@@ -0,0 +1,14 @@
+import { App } from './app';
+import type { Config } from "./config";
+import * as util from './util';
+import './styles.css';
+import React from 'react';
+
+export { helper } from './util';
+export * from './types';
+
+export const loadLazy = () => import('./lazy');
+
+export function main(config: Config) {
+  return util.helper(React, App, config);
+}
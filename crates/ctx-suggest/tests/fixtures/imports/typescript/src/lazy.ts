@@ -0,0 +1,3 @@
+import config = require('./config');
+
+export default config;
@@ -0,0 +1,3 @@
+const { Config } = require('./config');
+
+module.exports = { run: () => Config };
@@ -0,0 +1 @@
+export const env = { start: '2024-01-01' };
@@ -0,0 +1,9 @@
+import React from 'react';
+import { Api } from '@acme/api';
+import { format } from '@acme/utils';
+import { parseDate } from '@acme/utils/date';
+import { Button, Card } from '@app/components';
+import { env } from '@app/env';
+import { log } from '@shared/log';
+
+log(format(parseDate(env.start)), new Api(), Button, Card, React);
@@ -0,0 +1,5 @@
+import { format } from '@acme/utils';
+
+export class Api {
+  now = format(new Date());
+}
@@ -0,0 +1 @@
+export const log = (...args: unknown[]) => console.log(...args);
@@ -0,0 +1 @@
+export const parseDate = (s: string) => new Date(s);
@@ -0,0 +1,3 @@
+import { parseDate } from './date';
+
+export const format = (d: Date) => parseDate(d.toISOString()).toString();
@@ -1,6 +1,6 @@
 use crate::file_browser::FileBrowser;
 use anyhow::Result;
-use ctx_core::{ArtifactType, OrderingStrategy, Pack, RenderPolicy, render::RenderResult};
+use ctx_core::{OrderingStrategy, Pack, RenderPolicy, render::RenderResult};
 use ctx_sources::{SourceHandlerRegistry, SourceOptions};
 use ctx_storage::{PackItem, Storage};
 use std::collections::HashMap;
@@ -408,10 +408,7 @@ impl App {
 
         match registry.parse(&uri, options).await {
             Ok(artifact) => {
-                let is_collection = matches!(
-                    artifact.artifact_type,
-                    ArtifactType::CollectionMdDir { .. } | ArtifactType::CollectionGlob { .. }
-                );
+                let is_collection = artifact.artifact_type.is_collection();
 
                 let result: Result<()> = async {
                     if is_collection {
@@ -546,6 +543,7 @@ impl App {
             RenderPolicy {
                 budget_tokens: budget,
                 ordering: OrderingStrategy::PriorityThenTime,
+                line_numbers: false,
             },
         );
 
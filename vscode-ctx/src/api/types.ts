@@ -36,6 +36,7 @@ export interface Artifact {
 export interface ArtifactMetadata {
   size_bytes: number;
   mime_type?: string;
+  render_mode?: 'full' | 'outline' | 'signatures';
 }
 
 export interface PackItem {
@@ -74,12 +74,15 @@ export class ArtifactTreeItem extends vscode.TreeItem {
       case "markdown":
         return new vscode.ThemeIcon("file");
       case "collection_glob":
+      case "collection_dir":
       case "collection_md_dir":
         return new vscode.ThemeIcon("file-directory");
       case "text":
         return new vscode.ThemeIcon("note");
       case "git_diff":
         return new vscode.ThemeIcon("git-compare");
+      case "issue":
+        return new vscode.ThemeIcon("issues");
       default:
         return new vscode.ThemeIcon("symbol-misc");
     }